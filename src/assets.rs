@@ -0,0 +1,505 @@
+//! Extraction of image and media assets.
+//!
+//! Dioxus bundles files referenced with `asset!("/assets/...")`, so with
+//! [`Options::extract_assets`](crate::Options::extract_assets) every relative `src` and `poster`
+//! attribute is rewritten to an `asset!()` call and listed in the [`Conversion`](crate::Conversion).
+//! A `srcset` becomes a `format!()` of its candidates, with an `asset!()` for each local one.
+//! Inline `data:` URIs are decoded and given a file name derived from their contents, so the same
+//! image pasted twice becomes one file. Different files that would land on the same path get a
+//! number appended to their name.
+
+use crate::emit::{parse_rsx, quote_rust};
+use dioxus_rsx::{AttributeValue, BodyNode, HotLiteral};
+use std::fmt;
+
+/// Attributes that reference a file to bundle.
+const ASSET_ATTRIBUTES: [&str; 2] = ["src", "poster"];
+
+/// The attribute listing alternative image files with their widths or pixel densities.
+const SRCSET_ATTRIBUTE: &str = "srcset";
+
+/// The directory `asset!()` paths are rooted in.
+const ASSET_DIR: &str = "/assets/";
+
+/// A file referenced by the converted RSX through `asset!()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Asset {
+    /// The path passed to `asset!()`, relative to the crate root, e.g. `/assets/img/logo.png`.
+    pub path: String,
+    /// The attribute value in the input: a relative path or a `data:` URI.
+    pub source: String,
+    /// The decoded contents of a `data:` URI, which need writing to `path`. `None` for files that
+    /// need copying from `source`.
+    pub data: Option<Vec<u8>>,
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.data {
+            Some(data) => {
+                let mime = self.source["data:".len()..]
+                    .split([';', ','])
+                    .next()
+                    .unwrap_or_default();
+                write!(f, "{} <- inline {mime} ({} bytes)", self.path, data.len())
+            }
+            None => write!(f, "{} <- {}", self.path, self.source),
+        }
+    }
+}
+
+/// Rewrites the `src`, `poster` and `srcset` attributes in `nodes` that reference local files or
+/// data URIs to `asset!()` calls, and returns the referenced assets without duplicates.
+pub(crate) fn extract_assets(nodes: &mut [BodyNode]) -> Vec<Asset> {
+    let mut assets = Vec::new();
+    rewrite(nodes, &mut assets);
+    assets
+}
+
+fn rewrite(nodes: &mut [BodyNode], assets: &mut Vec<Asset>) {
+    for node in nodes {
+        let BodyNode::Element(element) = node else {
+            continue;
+        };
+
+        for attribute in &mut element.raw_attributes {
+            let AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) = &attribute.value else {
+                continue;
+            };
+            // Values with interpolations aren't known until runtime
+            if !value.formatted_input.is_static() {
+                continue;
+            }
+            let name = attribute.name.to_string();
+            if name != SRCSET_ATTRIBUTE && !ASSET_ATTRIBUTES.contains(&name.as_str()) {
+                continue;
+            }
+            // The pipeline has already escaped braces for RSX
+            let source = value
+                .formatted_input
+                .source
+                .value()
+                .replace("{{", "{")
+                .replace("}}", "}");
+            let rewritten = if name == SRCSET_ATTRIBUTE {
+                srcset(&source, assets)
+            } else {
+                asset(&source).map(|asset| asset_call(asset, assets))
+            };
+            let Some(rewritten) = rewritten else {
+                continue;
+            };
+
+            *attribute =
+                parse_rsx(&format!("{name}: {rewritten}")).expect("asset attributes are valid RSX");
+        }
+
+        rewrite(&mut element.children, assets);
+    }
+}
+
+/// Lists `asset` unless it already is, and returns the `asset!()` call that references it.
+///
+/// An asset whose path is taken by a different file, like `../p.jpg` after `p.jpg`, is renamed to
+/// `p-2.jpg`, `p-3.jpg` and so on until the path is free or taken by the same file.
+fn asset_call(mut asset: Asset, assets: &mut Vec<Asset>) -> String {
+    let path = asset.path.clone();
+    let mut number = 1;
+    loop {
+        match assets.iter().find(|other| other.path == asset.path) {
+            Some(other) if same_file(other, &asset) => break,
+            Some(_) => {
+                number += 1;
+                asset.path = numbered(&path, number);
+            }
+            None => {
+                assets.push(asset.clone());
+                break;
+            }
+        }
+    }
+    format!("asset!({})", quote_rust(&asset.path))
+}
+
+/// Returns true if two assets are the same file: the same inline data, or relative paths that only
+/// differ in `./` segments, queries and fragments.
+fn same_file(a: &Asset, b: &Asset) -> bool {
+    match (&a.data, &b.data) {
+        (Some(a), Some(b)) => a == b,
+        (None, None) => file_path(&a.source) == file_path(&b.source),
+        _ => false,
+    }
+}
+
+/// Normalizes the file part of a path, keeping `..` segments and whether it is root-relative.
+fn file_path(source: &str) -> String {
+    let file = source.split(['?', '#']).next().unwrap_or_default();
+    let segments: Vec<&str> = file
+        .split('/')
+        .filter(|segment| !matches!(*segment, "" | "."))
+        .collect();
+    let root = if file.starts_with('/') { "/" } else { "" };
+    format!("{root}{}", segments.join("/"))
+}
+
+/// Appends `-number` to the file name in `path`, before its extension.
+fn numbered(path: &str, number: usize) -> String {
+    let name_start = path.rfind('/').map_or(0, |slash| slash + 1);
+    let stem_end = path[name_start..]
+        .rfind('.')
+        .filter(|&dot| dot > 0)
+        .map_or(path.len(), |dot| name_start + dot);
+    format!("{}-{number}{}", &path[..stem_end], &path[stem_end..])
+}
+
+/// Rewrites the candidates of a `srcset` that reference local files or data URIs, returning a
+/// `format!()` call like `format!("{} 1x, https://example.com/b.png 2x", asset!("/assets/a.png"))`,
+/// or `None` if no candidate does.
+fn srcset(value: &str, assets: &mut Vec<Asset>) -> Option<String> {
+    let candidates = srcset_candidates(value);
+    if !candidates.iter().any(|(url, _)| asset(url).is_some()) {
+        return None;
+    }
+
+    let escape = |text: &str| text.replace('{', "{{").replace('}', "}}");
+    let mut template = Vec::new();
+    let mut calls = Vec::new();
+    for (url, descriptor) in candidates {
+        let url = match asset(url) {
+            Some(asset) => {
+                calls.push(asset_call(asset, assets));
+                "{}".to_string()
+            }
+            None => escape(url),
+        };
+        template.push(match descriptor {
+            "" => url,
+            descriptor => format!("{url} {}", escape(descriptor)),
+        });
+    }
+    Some(format!(
+        "format!({}, {})",
+        quote_rust(&template.join(", ")),
+        calls.join(", ")
+    ))
+}
+
+/// Splits a `srcset` into its URLs and descriptors, e.g. `a.png 1x, b.png 2x` into `("a.png",
+/// "1x")` and `("b.png", "2x")`.
+///
+/// As in the HTML spec, a URL runs up to the next whitespace, so the comma in a `data:` URI
+/// doesn't end the candidate, and a descriptor runs up to the next comma.
+fn srcset_candidates(value: &str) -> Vec<(&str, &str)> {
+    let mut candidates = Vec::new();
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() {
+            return candidates;
+        }
+        let url_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let url = &rest[..url_end];
+        // A comma right after the URL ends the candidate without descriptors
+        if url.ends_with(',') {
+            candidates.push((url.trim_end_matches(','), ""));
+            rest = &rest[url_end..];
+            continue;
+        }
+        let descriptor_end = rest[url_end..]
+            .find(',')
+            .map_or(rest.len(), |comma| url_end + comma);
+        candidates.push((url, rest[url_end..descriptor_end].trim()));
+        rest = &rest[descriptor_end..];
+    }
+}
+
+/// Works out the asset a `src` refers to, or `None` for URLs on other hosts and values that aren't
+/// files.
+fn asset(source: &str) -> Option<Asset> {
+    if let Some(uri) = source.strip_prefix("data:") {
+        let (mime, data) = decode_data_uri(uri)?;
+        return Some(Asset {
+            path: format!(
+                "{ASSET_DIR}inline-{:016x}.{}",
+                fnv1a(&data),
+                extension(&mime)
+            ),
+            source: source.to_string(),
+            data: Some(data),
+        });
+    }
+
+    // Absolute URLs (`https:`, `blob:`) and protocol-relative ones stay as they are
+    let scheme = source
+        .find(':')
+        .is_some_and(|colon| !source[..colon].contains(['/', '?', '#']));
+    if source.is_empty() || source.starts_with("//") || scheme || source.starts_with('#') {
+        return None;
+    }
+
+    let file = source.split(['?', '#']).next().unwrap_or_default();
+    let path: Vec<&str> = file
+        .split('/')
+        .filter(|segment| !matches!(*segment, "" | "." | ".."))
+        .collect();
+    if path.is_empty() {
+        return None;
+    }
+    let path = path.join("/");
+    let path = match path.strip_prefix(&ASSET_DIR[1..]) {
+        Some(_) => format!("/{path}"),
+        None => format!("{ASSET_DIR}{path}"),
+    };
+
+    Some(Asset {
+        path,
+        source: source.to_string(),
+        data: None,
+    })
+}
+
+/// Splits the part of a `data:` URI after the scheme into its media type and decoded contents.
+fn decode_data_uri(uri: &str) -> Option<(String, Vec<u8>)> {
+    let (header, payload) = uri.split_once(',')?;
+    let mut params = header.split(';');
+    let mime = match params.next().map(str::trim) {
+        Some("") | None => "text/plain".to_string(),
+        Some(mime) => mime.to_ascii_lowercase(),
+    };
+
+    let data = if params.any(|param| param.trim().eq_ignore_ascii_case("base64")) {
+        decode_base64(&percent_decode(payload))?
+    } else {
+        percent_decode(payload)
+    };
+    Some((mime, data))
+}
+
+/// Picks a file extension for a media type.
+fn extension(mime: &str) -> &str {
+    match mime {
+        "image/jpeg" => "jpg",
+        "image/svg+xml" => "svg",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        "text/plain" => "txt",
+        mime => mime
+            .split_once('/')
+            .map(|(_, subtype)| subtype)
+            .filter(|subtype| {
+                !subtype.is_empty() && subtype.chars().all(|c| c.is_ascii_alphanumeric())
+            })
+            .unwrap_or("bin"),
+    }
+}
+
+fn percent_decode(text: &str) -> Vec<u8> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Decodes standard or URL-safe base64, ignoring whitespace and padding.
+fn decode_base64(text: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(text.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in text {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => continue,
+            byte if byte.is_ascii_whitespace() => continue,
+            _ => return None,
+        };
+        buffer = buffer << 6 | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+/// 64-bit FNV-1a, used to name inline assets after their contents.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Options, convert_with_report};
+
+    fn extract(html: &str) -> crate::Conversion {
+        let options = Options {
+            extract_assets: true,
+            ..Options::default()
+        };
+        convert_with_report(html, &options).unwrap()
+    }
+
+    #[test]
+    fn relative_paths_become_assets() {
+        let conversion = extract(
+            r#"<img src="./img/logo.png?v=2" alt="Logo"><img src="https://example.com/a.png"><video poster="/assets/poster.jpg" src="../media/intro.mp4"></video>"#,
+        );
+
+        assert!(
+            conversion
+                .rsx
+                .contains(r#"img { alt: "Logo", src: asset!("/assets/img/logo.png") }"#),
+            "{}",
+            conversion.rsx
+        );
+        assert!(
+            conversion
+                .rsx
+                .contains(r#"src: "https://example.com/a.png""#),
+            "{}",
+            conversion.rsx
+        );
+        let listed: Vec<String> = conversion.assets.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            listed,
+            [
+                "/assets/img/logo.png <- ./img/logo.png?v=2",
+                "/assets/poster.jpg <- /assets/poster.jpg",
+                "/assets/media/intro.mp4 <- ../media/intro.mp4",
+            ]
+        );
+    }
+
+    #[test]
+    fn data_uris_are_decoded_once() {
+        let pixel = "data:image/png;base64,iVBORw0KGgo=";
+        let conversion = extract(&format!(
+            r#"<img src="{pixel}"><img src="{pixel}"><img src="data:image/svg+xml,%3Csvg%2F%3E">"#
+        ));
+
+        assert_eq!(conversion.assets.len(), 2);
+        assert_eq!(
+            conversion.assets[0].data.as_deref(),
+            Some(&b"\x89PNG\r\n\x1a\n"[..])
+        );
+        assert!(conversion.assets[0].path.ends_with(".png"));
+        assert_eq!(conversion.assets[1].data.as_deref(), Some(&b"<svg/>"[..]));
+        assert!(conversion.assets[1].path.ends_with(".svg"));
+        assert_eq!(
+            conversion.rsx.matches(&conversion.assets[0].path).count(),
+            2
+        );
+    }
+
+    #[test]
+    fn srcset_candidates_become_assets() {
+        let pixel = "data:image/png;base64,iVBORw0KGgo=";
+        let conversion = extract(&format!(
+            r#"<picture><source srcset="img/hero.webp"><img src="img/hero.png" srcset="img/hero.png 1x, {pixel} 2x,https://cdn.example.com/hero@3x.png 3x"></picture><img srcset="https://example.com/a.png 100w">"#
+        ));
+
+        for expected in [
+            r#"source { srcset: format!("{}", asset!("/assets/img/hero.webp")) }"#,
+            r#"src: asset!("/assets/img/hero.png")"#,
+            r#"srcset: format!(
+                "{} 1x, {} 2x, https://cdn.example.com/hero@3x.png 3x","#,
+            r#"img { srcset: "https://example.com/a.png 100w" }"#,
+        ] {
+            assert!(
+                conversion.rsx.contains(expected),
+                "missing {expected}:\n{}",
+                conversion.rsx
+            );
+        }
+        assert_eq!(conversion.assets.len(), 3, "{:?}", conversion.assets);
+        assert_eq!(
+            conversion.assets[2].data.as_deref(),
+            Some(&b"\x89PNG\r\n\x1a\n"[..])
+        );
+    }
+
+    #[test]
+    fn srcset_candidates_are_split_like_browsers() {
+        assert_eq!(
+            srcset_candidates(" a.png  1x ,b.png,, data:x,y 2x,c.png,"),
+            [
+                ("a.png", "1x"),
+                ("b.png", ""),
+                ("data:x,y", "2x"),
+                ("c.png", "")
+            ]
+        );
+    }
+
+    #[test]
+    fn different_files_get_unique_paths() {
+        let conversion = extract(
+            r#"<img src="img/p.jpg"><img src="../img/p.jpg"><img src="./img/p.jpg?v=2"><img src="/img/p.jpg"><img src="../img/p.jpg"><img src="img/archive"><img src="../img/archive">"#,
+        );
+
+        let listed: Vec<String> = conversion.assets.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            listed,
+            [
+                "/assets/img/p.jpg <- img/p.jpg",
+                "/assets/img/p-2.jpg <- ../img/p.jpg",
+                "/assets/img/p-3.jpg <- /img/p.jpg",
+                "/assets/img/archive <- img/archive",
+                "/assets/img/archive-2 <- ../img/archive",
+            ]
+        );
+        assert_eq!(
+            conversion
+                .rsx
+                .matches(r#"asset!("/assets/img/p-2.jpg")"#)
+                .count(),
+            2,
+            "{}",
+            conversion.rsx
+        );
+        assert_eq!(
+            conversion
+                .rsx
+                .matches(r#"asset!("/assets/img/p.jpg")"#)
+                .count(),
+            2,
+            "{}",
+            conversion.rsx
+        );
+    }
+
+    #[test]
+    fn other_values_are_left_alone() {
+        for source in [
+            "mailto:me@example.com",
+            "//cdn.example.com/a.png",
+            "#top",
+            "",
+        ] {
+            assert_eq!(asset(source), None, "{source}");
+        }
+        assert_eq!(asset("data:image/png;base64,!!"), None);
+    }
+}
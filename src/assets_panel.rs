@@ -0,0 +1,47 @@
+use dioxus::prelude::*;
+use dioxus_html_rsx::Asset;
+
+#[component]
+/// Lists the files the converted RSX references through `asset!()`. Inline images can be
+/// downloaded straight from their data URI.
+///
+/// # Arguments
+///
+/// * `assets` - The assets reported by `convert_with_report`.
+pub fn AssetsPanel(assets: Vec<Asset>) -> Element {
+    let count = assets.len();
+    rsx! {
+        div { style: "margin-top: 0.75rem; background-color: #1E2A22; border: 1px solid #166534; border-radius: 0.25rem; padding: 0.75rem; font-family: monospace; font-size: 0.875rem; max-height: 30vh; overflow: auto;",
+            div { style: "color: #4ADE80; font-weight: bold; margin-bottom: 0.5rem;",
+                if count == 1 {
+                    "1 asset to add to the project"
+                } else {
+                    "{count} assets to add to the project"
+                }
+            }
+            for asset in assets {
+                div { style: "display: flex; gap: 0.75rem; padding: 0.15rem 0;",
+                    span { style: "color: #E5E7EB;", "{asset.path}" }
+                    if asset.data.is_some() {
+                        a {
+                            href: "{asset.source}",
+                            download: "{file_name(&asset)}",
+                            style: "color: #60A5FA;",
+                            "download"
+                        }
+                    } else {
+                        span { style: "color: #9CA3AF;", "{copy_from(&asset)}" }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn file_name(asset: &Asset) -> &str {
+    asset.path.rsplit('/').next().unwrap_or_default()
+}
+
+fn copy_from(asset: &Asset) -> String {
+    format!("copy from {}", asset.source)
+}
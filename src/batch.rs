@@ -0,0 +1,251 @@
+use crate::component::{component_fn, component_module, component_name, module_name};
+use crate::{ConvertError, FormatOptions, Options, OutputMode, convert_with_report};
+use std::collections::HashSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// The outcome of converting a directory of HTML files with [`convert_dir`].
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Every `.rs` file that was written, including generated `mod.rs` files.
+    pub written: Vec<PathBuf>,
+    /// Input files that could not be converted, with the reason.
+    pub failed: Vec<(PathBuf, ConvertError)>,
+    /// Inputs whose module or component name was already taken by an earlier file or directory
+    /// next to them, like `user-card.html` and `user_card.html`, with the module name they were
+    /// written as instead.
+    pub renamed: Vec<(PathBuf, String)>,
+}
+
+/// Converts every HTML file under `input` into a component module under `output`.
+///
+/// The directory structure is mirrored: `input/cards/user-card.html` becomes
+/// `output/cards/user_card.rs` containing `pub fn UserCard() -> Element`, and each directory gets a
+/// `mod.rs` that declares its modules and re-exports the components. Files that fail to convert are
+/// recorded in the report and left out of `mod.rs`; I/O errors abort the whole run. Names that
+/// clash within a directory get a number appended, e.g. `user_card_2` and `UserCard2`.
+///
+/// For other input formats the files matching [`InputFormat::extensions`](crate::InputFormat::extensions)
+/// are converted instead.
+pub fn convert_dir(input: &Path, output: &Path, options: &Options) -> io::Result<BatchReport> {
+    let mut report = BatchReport::default();
+    convert_dir_into(input, output, options, &mut report)?;
+    Ok(report)
+}
+
+/// Converts one directory level and returns whether anything was emitted for it.
+fn convert_dir_into(
+    input: &Path,
+    output: &Path,
+    options: &Options,
+    report: &mut BatchReport,
+) -> io::Result<bool> {
+    let mut entries = std::fs::read_dir(input)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    let mut components = Vec::new();
+    let mut submodules = Vec::new();
+    // Module and component names already used in this directory
+    let mut modules = HashSet::new();
+    let mut names = HashSet::new();
+
+    for path in entries {
+        let Some(stem) = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+        else {
+            continue;
+        };
+
+        if path.is_dir() {
+            let (module, _) = unique_names(&module_name(&stem), None, &modules, &names);
+            if convert_dir_into(&path, &output.join(&module), options, report)? {
+                if module != module_name(&stem) {
+                    report.renamed.push((path, module.clone()));
+                }
+                modules.insert(module.clone());
+                submodules.push(module);
+            }
+            continue;
+        }
+
+        let extensions = options.input.extensions();
+        if path
+            .extension()
+            .is_none_or(|ext| !extensions.contains(&ext.to_string_lossy().as_ref()))
+        {
+            continue;
+        }
+
+        let html = std::fs::read_to_string(&path)?;
+        let (module, name) = unique_names(
+            &module_name(&stem),
+            Some(&component_name(&stem)),
+            &modules,
+            &names,
+        );
+        let name = name.unwrap_or_default();
+        // Generated components must not take the name of the file's component
+        let file_options = Options {
+            format: FormatOptions {
+                output: OutputMode::Component,
+                component_name: Some(name.clone()),
+                ..options.format.clone()
+            },
+            ..options.clone()
+        };
+        let source = match convert_with_report(&html, &file_options).and_then(|conversion| {
+            // Single-file components keep the props they declare, under the file's name
+            let mut source = match &conversion.component {
+                Some(component) => format!(
+                    "use dioxus::prelude::*;\n\n{}",
+                    component_fn(&name, &component.props, &conversion.rsx)?
+                ),
+                None => component_module(&name, &conversion.rsx)?,
+            };
+            for component in &conversion.components {
+                source.push('\n');
+                source.push_str(component);
+            }
+            Ok(options.format.apply(&source))
+        }) {
+            Ok(source) => source,
+            Err(error) => {
+                report.failed.push((path, error));
+                continue;
+            }
+        };
+
+        std::fs::create_dir_all(output)?;
+        let file = output.join(format!("{module}.rs"));
+        std::fs::write(&file, source)?;
+        report.written.push(file);
+        if module != module_name(&stem) {
+            report.renamed.push((path, module.clone()));
+        }
+        modules.insert(module.clone());
+        names.insert(name.clone());
+        components.push((module, name));
+    }
+
+    if components.is_empty() && submodules.is_empty() {
+        return Ok(false);
+    }
+
+    let mut mod_rs = String::new();
+    for module in &submodules {
+        mod_rs.push_str(&format!("pub mod {module};\n"));
+    }
+    for (module, _) in &components {
+        mod_rs.push_str(&format!("mod {module};\n"));
+    }
+    if !components.is_empty() {
+        mod_rs.push('\n');
+    }
+    for (module, name) in &components {
+        mod_rs.push_str(&format!("pub use {module}::{name};\n"));
+    }
+
+    let file = output.join("mod.rs");
+    std::fs::write(&file, mod_rs)?;
+    report.written.push(file);
+
+    Ok(true)
+}
+
+/// Appends the first number to `module` (and `name`) that makes both unused.
+fn unique_names(
+    module: &str,
+    name: Option<&str>,
+    modules: &HashSet<String>,
+    names: &HashSet<String>,
+) -> (String, Option<String>) {
+    let taken = |module: &str, name: Option<&str>| {
+        modules.contains(module) || name.is_some_and(|name| names.contains(name))
+    };
+    if !taken(module, name) {
+        return (module.to_string(), name.map(str::to_string));
+    }
+    (2..)
+        .map(|n| {
+            (
+                format!("{module}_{n}"),
+                name.map(|name| format!("{name}{n}")),
+            )
+        })
+        .find(|(module, name)| !taken(module, name.as_deref()))
+        .expect("some number is free")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh directory under the system temp directory, removed when dropped.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("html2rsx-{name}-{}", std::process::id()));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn clashing_file_names_get_unique_modules() {
+        let dir = TempDir::new("clash");
+        let input = dir.0.join("in");
+        std::fs::create_dir_all(&input).unwrap();
+        std::fs::write(input.join("user-card.html"), "<p>dash</p>").unwrap();
+        std::fs::write(input.join("user_card.html"), "<p>underscore</p>").unwrap();
+
+        let output = dir.0.join("out");
+        let report = convert_dir(&input, &output, &Options::default()).unwrap();
+
+        assert_eq!(
+            report.renamed,
+            vec![(input.join("user_card.html"), "user_card_2".to_string())]
+        );
+        let mod_rs = std::fs::read_to_string(output.join("mod.rs")).unwrap();
+        assert_eq!(
+            mod_rs,
+            "mod user_card;\nmod user_card_2;\n\npub use user_card::UserCard;\npub use user_card_2::UserCard2;\n"
+        );
+        let second = std::fs::read_to_string(output.join("user_card_2.rs")).unwrap();
+        assert!(second.contains("pub fn UserCard2() -> Element"), "{second}");
+        assert!(second.contains("underscore"), "{second}");
+    }
+
+    #[test]
+    fn file_next_to_directory_of_the_same_name() {
+        let dir = TempDir::new("dir-clash");
+        let input = dir.0.join("in");
+        std::fs::create_dir_all(input.join("sub")).unwrap();
+        std::fs::write(input.join("sub/inner.html"), "<p>inner</p>").unwrap();
+        std::fs::write(input.join("sub.html"), "<p>file</p>").unwrap();
+
+        let output = dir.0.join("out");
+        let report = convert_dir(&input, &output, &Options::default()).unwrap();
+
+        assert!(report.failed.is_empty());
+        assert_eq!(
+            report.renamed,
+            vec![(input.join("sub.html"), "sub_2".to_string())]
+        );
+        let mod_rs = std::fs::read_to_string(output.join("mod.rs")).unwrap();
+        assert_eq!(mod_rs, "pub mod sub;\nmod sub_2;\n\npub use sub_2::Sub2;\n");
+        assert!(output.join("sub/mod.rs").exists());
+        assert!(output.join("sub_2.rs").exists());
+        assert!(!output.join("sub.rs").exists());
+    }
+}
@@ -0,0 +1,377 @@
+//! Command-line front end for the HTML to RSX converter.
+//!
+//! ```text
+//! html2rsx [OPTIONS] [INPUT]...
+//! ```
+//!
+//! Reads each INPUT file (or stdin when no input or `-` is given) and writes the converted RSX to
+//! stdout, to a single `--output` file, or to `<stem>.rsx` files inside `--out-dir`. Directory
+//! inputs are converted into a tree of component modules inside `--out-dir`. With `--to-html`
+//! the inputs are RSX and the output is HTML.
+
+use dioxus_html_rsx::{
+    Asset, ConvertError, InputFormat, Options, OutputMode, check_round_trip, convert_dir,
+    convert_with_report, parse_rules, rsx_to_html,
+};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: html2rsx [OPTIONS] [INPUT]...
+
+Converts HTML files to Dioxus RSX. Reads stdin when no INPUT (or `-`) is given.
+A directory INPUT is converted into `#[component]` modules with a `mod.rs` in --out-dir.
+
+Options:
+  -o, --output <FILE>    Write the RSX to FILE instead of stdout (single input only)
+  -d, --out-dir <DIR>    Write each input to DIR/<name>.rsx
+      --jsx              Treat input as React JSX/TSX instead of HTML
+      --template <LANG>  Treat input as a jinja, askama or handlebars template
+      --vue              Treat input as a Vue single-file component
+      --svelte           Treat input as a Svelte component
+      --markdown         Treat input as Markdown
+      --heading-component <NAME>
+                         Render Markdown headings with the component NAME
+      --code-component <NAME>
+                         Render Markdown code blocks with the component NAME
+      --link-component <NAME>
+                         Render Markdown links with the component NAME, e.g. Link
+      --no-preprocess    Skip className renames and whitespace normalization
+      --split-styles     Turn inline style strings into individual style attributes
+      --rules <FILE>     Rewrite attributes with the rules in a TOML FILE before parsing
+      --handler-stubs    Turn on* attributes into event handler stubs and list them on stderr
+      --extract-components
+                         Turn repeated sibling elements into generated components
+      --icon-component <NAME>
+                         Turn each svg into a generated icon component called NAME
+      --extract-assets   Rewrite local src paths and data URIs to asset!() and list them on stderr
+      --assets-dir <DIR> Like --extract-assets, and write decoded data URIs into DIR, the
+                         project's assets directory
+      --indent <N>       Indent the output by N spaces per level (default 4)
+      --max-width <N>    Break elements longer than N columns into one attribute per line
+      --max-attributes <N>
+                         Put each attribute on its own line for elements with more than N
+      --emit <MODE>      Write the bare body, or wrap it in rsx, a component or an app's main.rs
+                         (body, rsx, component or main)
+      --name <NAME>      Name of the component written by --emit component or main
+      --to-html          Convert RSX input back to HTML (writes DIR/<name>.html)
+      --check            Convert the RSX back to HTML and report anything that changed
+  -h, --help             Print this help
+
+Exit status is 0 on success, 1 if any input failed to convert (or --check found differences)
+and 2 on usage or I/O errors.";
+
+/// Exit code used when at least one input failed to convert.
+const EXIT_CONVERSION: u8 = 1;
+/// Exit code used for bad arguments and I/O failures.
+const EXIT_USAGE: u8 = 2;
+
+/// Where converted RSX is written.
+enum Destination {
+    Stdout,
+    File(PathBuf),
+    Dir(PathBuf),
+}
+
+struct Args {
+    inputs: Vec<String>,
+    destination: Destination,
+    options: Options,
+    /// Convert RSX to HTML instead of the other way around.
+    to_html: bool,
+    /// Report differences between the input and the RSX converted back to HTML.
+    check: bool,
+    /// Where decoded `data:` URIs are written.
+    assets_dir: Option<PathBuf>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut inputs = Vec::new();
+    let mut destination = Destination::Stdout;
+    let mut options = Options::default();
+    let mut to_html = false;
+    let mut check = false;
+    let mut assets_dir = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Err(String::new()),
+            "-o" | "--output" => {
+                let path = args.next().ok_or("missing value for --output")?;
+                destination = Destination::File(path.into());
+            }
+            "-d" | "--out-dir" => {
+                let path = args.next().ok_or("missing value for --out-dir")?;
+                destination = Destination::Dir(path.into());
+            }
+            "--jsx" => options.input = InputFormat::Jsx,
+            "--vue" => options.input = InputFormat::Vue,
+            "--svelte" => options.input = InputFormat::Svelte,
+            "--markdown" => options.input = InputFormat::Markdown,
+            "--heading-component" => {
+                let name = args.next().ok_or("missing value for --heading-component")?;
+                options.markdown_components.heading = Some(name);
+            }
+            "--code-component" => {
+                let name = args.next().ok_or("missing value for --code-component")?;
+                options.markdown_components.code_block = Some(name);
+            }
+            "--link-component" => {
+                let name = args.next().ok_or("missing value for --link-component")?;
+                options.markdown_components.link = Some(name);
+            }
+            "--template" => {
+                let language = args.next().ok_or("missing value for --template")?;
+                options.input = match language.to_ascii_lowercase().as_str() {
+                    "jinja" | "jinja2" => InputFormat::Jinja,
+                    "askama" => InputFormat::Askama,
+                    "handlebars" | "hbs" => InputFormat::Handlebars,
+                    _ => return Err(format!("unknown template language `{language}`")),
+                };
+            }
+            "--no-preprocess" => options.preprocess = false,
+            "--split-styles" => options.split_styles = true,
+            "--rules" => {
+                let path = args.next().ok_or("missing value for --rules")?;
+                let config =
+                    std::fs::read_to_string(&path).map_err(|err| format!("{path}: {err}"))?;
+                options.rules = parse_rules(&config).map_err(|err| format!("{path}: {err}"))?;
+            }
+            "--handler-stubs" => options.handler_stubs = true,
+            "--extract-components" => options.extract_components = true,
+            "--extract-assets" => options.extract_assets = true,
+            "--assets-dir" => {
+                let dir = args.next().ok_or("missing value for --assets-dir")?;
+                options.extract_assets = true;
+                assets_dir = Some(PathBuf::from(dir));
+            }
+            "--icon-component" => {
+                let name = args.next().ok_or("missing value for --icon-component")?;
+                options.icon_component = Some(name);
+            }
+            "--indent" => options.format.indent_width = number(args.next(), "--indent")?,
+            "--max-width" => {
+                options.format.max_line_length = Some(number(args.next(), "--max-width")?)
+            }
+            "--max-attributes" => {
+                options.format.max_attributes_per_line =
+                    Some(number(args.next(), "--max-attributes")?)
+            }
+            "--emit" => {
+                let mode = args.next().ok_or("missing value for --emit")?;
+                options.format.output = match mode.as_str() {
+                    "body" => OutputMode::Body,
+                    "rsx" => OutputMode::Rsx,
+                    "component" => OutputMode::Component,
+                    "main" => OutputMode::App,
+                    _ => return Err(format!("unknown output mode `{mode}`")),
+                };
+            }
+            "--name" => {
+                let name = args.next().ok_or("missing value for --name")?;
+                options.format.component_name = Some(name);
+            }
+            "--to-html" => to_html = true,
+            "--check" => check = true,
+            "-" => inputs.push(arg),
+            flag if flag.starts_with('-') => return Err(format!("unknown option `{flag}`")),
+            _ => inputs.push(arg),
+        }
+    }
+
+    if inputs.is_empty() {
+        inputs.push("-".to_string());
+    }
+
+    if matches!(destination, Destination::File(_)) && inputs.len() > 1 {
+        return Err("--output can only be used with a single input".to_string());
+    }
+
+    if !matches!(destination, Destination::Dir(_)) && inputs.iter().any(|i| Path::new(i).is_dir()) {
+        return Err("directory inputs require --out-dir".to_string());
+    }
+
+    if to_html && inputs.iter().any(|i| Path::new(i).is_dir()) {
+        return Err("--to-html does not support directory inputs".to_string());
+    }
+
+    if check && to_html {
+        return Err("--check cannot be combined with --to-html".to_string());
+    }
+
+    if check && inputs.iter().any(|i| Path::new(i).is_dir()) {
+        return Err("--check does not support directory inputs".to_string());
+    }
+
+    Ok(Args {
+        inputs,
+        destination,
+        options,
+        to_html,
+        check,
+        assets_dir,
+    })
+}
+
+/// Parses the value of a numeric option.
+fn number(value: Option<String>, flag: &str) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("missing value for {flag}"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value `{value}` for {flag}"))
+}
+
+fn read_input(input: &str) -> std::io::Result<String> {
+    if input == "-" {
+        let mut html = String::new();
+        std::io::stdin().read_to_string(&mut html)?;
+        Ok(html)
+    } else {
+        std::fs::read_to_string(input)
+    }
+}
+
+/// Prints a conversion error prefixed with the input it came from.
+fn report_error(input: &str, error: &ConvertError) {
+    let name = if input == "-" { "<stdin>" } else { input };
+    eprintln!("{name}: error: {error}");
+}
+
+fn output_path(dir: &Path, input: &str, extension: &str) -> PathBuf {
+    let stem = Path::new(input)
+        .file_stem()
+        .filter(|_| input != "-")
+        .map_or("stdin".into(), |stem| stem.to_string_lossy());
+    dir.join(format!("{stem}.{extension}"))
+}
+
+/// Writes the decoded data URIs among `assets` into the project's assets directory `dir`.
+fn write_assets(dir: &Path, assets: &[Asset]) -> std::io::Result<()> {
+    for asset in assets {
+        let Some(data) = &asset.data else {
+            continue;
+        };
+        let relative = asset.path.trim_start_matches('/');
+        let path = dir.join(relative.strip_prefix("assets/").unwrap_or(relative));
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, data)?;
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> std::io::Result<bool> {
+    let mut all_converted = true;
+    let mut stdout = std::io::stdout().lock();
+
+    if let Destination::Dir(dir) = &args.destination {
+        std::fs::create_dir_all(dir)?;
+    }
+
+    for input in &args.inputs {
+        if let Destination::Dir(dir) = &args.destination
+            && Path::new(input).is_dir()
+        {
+            let report = convert_dir(Path::new(input), dir, &args.options)?;
+            for (path, error) in &report.failed {
+                report_error(&path.to_string_lossy(), error);
+            }
+            for (path, module) in &report.renamed {
+                eprintln!(
+                    "{}: name already taken, written as `{module}`",
+                    path.display()
+                );
+            }
+            all_converted &= report.failed.is_empty();
+            continue;
+        }
+
+        let html = read_input(input)
+            .map_err(|err| std::io::Error::new(err.kind(), format!("{input}: {err}")))?;
+        let mut assets = Vec::new();
+
+        let converted = if args.to_html {
+            rsx_to_html(&html)
+        } else if args.check {
+            check_round_trip(&html, &args.options).map(|report| {
+                let name = if input == "-" { "<stdin>" } else { input };
+                for difference in &report.differences {
+                    eprintln!("{name}: {difference}");
+                }
+                all_converted &= report.is_lossless();
+                report.rsx
+            })
+        } else {
+            convert_with_report(&html, &args.options).map(|conversion| {
+                let name = if input == "-" { "<stdin>" } else { input };
+                // Stubs also come from `event` rewrite rules, not just `--handler-stubs`
+                for handler in &conversion.handlers {
+                    eprintln!("{name}: handler needs porting: {handler}");
+                }
+                for asset in &conversion.assets {
+                    eprintln!("{name}: asset: {asset}");
+                }
+                assets.extend(conversion.assets.iter().cloned());
+                conversion.output()
+            })
+        };
+        let output = match converted {
+            Ok(output) => output,
+            Err(error) => {
+                report_error(input, &error);
+                all_converted = false;
+                continue;
+            }
+        };
+        let output = format!("{}\n", output.trim_matches('\n'));
+
+        if let Some(dir) = &args.assets_dir {
+            write_assets(dir, &assets)?;
+        }
+
+        match &args.destination {
+            Destination::Stdout => {
+                if args.inputs.len() > 1 {
+                    if args.to_html {
+                        writeln!(stdout, "<!-- {input} -->")?;
+                    } else {
+                        writeln!(stdout, "// {input}")?;
+                    }
+                }
+                stdout.write_all(output.as_bytes())?;
+            }
+            Destination::File(path) => std::fs::write(path, output)?,
+            Destination::Dir(dir) => {
+                let extension = if args.to_html { "html" } else { "rsx" };
+                std::fs::write(output_path(dir, input, extension), output)?
+            }
+        }
+    }
+
+    Ok(all_converted)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) if message.is_empty() => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(message) => {
+            eprintln!("html2rsx: {message}\n\n{USAGE}");
+            return ExitCode::from(EXIT_USAGE);
+        }
+    };
+
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(EXIT_CONVERSION),
+        Err(err) => {
+            eprintln!("html2rsx: {err}");
+            ExitCode::from(EXIT_USAGE)
+        }
+    }
+}
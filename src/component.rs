@@ -0,0 +1,178 @@
+use crate::ConvertError;
+use crate::emit::{fill_placeholders, protect_comments};
+use dioxus_autofmt::IndentOptions;
+
+/// Width rustfmt wraps function signatures at.
+const MAX_WIDTH: usize = 100;
+
+/// Keywords that cannot be used as plain identifiers.
+pub(crate) const RUST_KEYWORDS: [&str; 51] = [
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+/// Turns a file name or user-provided label into a PascalCase Rust identifier for a component.
+///
+/// Anything that isn't alphanumeric splits words, so `user-card` becomes `UserCard`. Names that
+/// would start with a digit are prefixed with `Component`, and `Self`, the only PascalCase
+/// keyword, gets it as a suffix.
+pub fn component_name(raw: &str) -> String {
+    let name: String = words(raw)
+        .map(|word| {
+            let mut chars = word.chars();
+            chars
+                .next()
+                .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                .unwrap_or_default()
+        })
+        .collect();
+
+    match name.chars().next() {
+        None => "Component".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("Component{name}"),
+        Some(_) if name == "Self" => "SelfComponent".to_string(),
+        Some(_) => name,
+    }
+}
+
+/// Turns a file name into a snake_case Rust module name.
+pub fn module_name(raw: &str) -> String {
+    let name = words(raw)
+        .map(|word| word.to_ascii_lowercase())
+        .collect::<Vec<_>>()
+        .join("_");
+
+    match name.chars().next() {
+        None => "component".to_string(),
+        Some(first) if first.is_ascii_digit() => format!("component_{name}"),
+        Some(_) if RUST_KEYWORDS.contains(&name.as_str()) => format!("{name}_"),
+        Some(_) => name,
+    }
+}
+
+/// Splits on non-alphanumeric characters and on lower-to-upper case changes (`myCard` -> `my`, `Card`).
+fn words(raw: &str) -> impl Iterator<Item = String> + '_ {
+    raw.split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|chunk| {
+            let mut words = Vec::new();
+            let mut current = String::new();
+            let mut previous_lower = false;
+            for c in chunk.chars() {
+                if c.is_ascii_uppercase() && previous_lower {
+                    words.push(std::mem::take(&mut current));
+                }
+                previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+                current.push(c);
+            }
+            words.push(current);
+            words
+        })
+        .filter(|word| !word.is_empty())
+}
+
+/// Wraps a formatted RSX body (as returned by [`convert`](crate::convert)) in a compilable
+/// component module:
+///
+/// ```text
+/// use dioxus::prelude::*;
+///
+/// #[component]
+/// pub fn Name() -> Element {
+///     rsx! { ... }
+/// }
+/// ```
+pub fn component_module(name: &str, rsx: &str) -> Result<String, ConvertError> {
+    Ok(format!(
+        "use dioxus::prelude::*;\n\n{}",
+        component_fn(name, "", rsx)?
+    ))
+}
+
+/// Like [`component_module`], but also launches the component from `main`, as the `main.rs` of a
+/// new app.
+pub(crate) fn app_main(name: &str, props: &str, rsx: &str) -> Result<String, ConvertError> {
+    Ok(format!(
+        "use dioxus::prelude::*;\n\nfn main() {{\n    dioxus::launch({name});\n}}\n\n{}",
+        component_fn(name, props, rsx)?
+    ))
+}
+
+/// Renders just the `#[component] pub fn` item for a formatted RSX body, taking the given
+/// comma-separated `props`.
+pub(crate) fn component_fn(name: &str, props: &str, rsx: &str) -> Result<String, ConvertError> {
+    // Re-indent the body so it sits inside `rsx! { }` within the function. The formatter drops
+    // comments inside expressions, like those in handler stubs, so they are swapped out first.
+    let (protected, comments) = protect_comments(rsx);
+    let body = dioxus_autofmt::fmt_block(&protected, 1, IndentOptions::default())
+        .ok_or(ConvertError::Format)?;
+    let body = fill_placeholders(body, &comments);
+    let close = if body.contains('\n') { "    }" } else { "}" };
+
+    // Like rustfmt, put each prop on its own line once the signature gets too long
+    let mut signature = format!("pub fn {name}({props}) -> Element {{");
+    if signature.len() > MAX_WIDTH {
+        let props: String = split_props(props)
+            .map(|prop| format!("    {prop},\n"))
+            .collect();
+        signature = format!("pub fn {name}(\n{props}) -> Element {{");
+    }
+
+    Ok(format!(
+        "#[component]\n{signature}\n    rsx! {{{body}{close}\n}}\n"
+    ))
+}
+
+/// Splits a comma-separated list of props, ignoring commas inside brackets and string literals.
+fn split_props(props: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0i32;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    let mut parts = Vec::new();
+    for (i, c) in props.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' | '[' | '<' | '{' => depth += 1,
+            ')' | ']' | '>' | '}' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&props[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&props[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|prop| !prop.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn component_names_are_valid_identifiers() {
+        assert_eq!(component_name("user-card"), "UserCard");
+        assert_eq!(component_name("myCard"), "MyCard");
+        assert_eq!(component_name("404"), "Component404");
+        assert_eq!(component_name("self"), "SelfComponent");
+        assert_eq!(component_name("Self"), "SelfComponent");
+        assert_eq!(component_name("self-card"), "SelfCard");
+        assert_eq!(component_name("--"), "Component");
+    }
+
+    #[test]
+    fn module_names_avoid_keywords() {
+        assert_eq!(module_name("UserCard"), "user_card");
+        assert_eq!(module_name("type"), "type_");
+        assert_eq!(module_name("2col"), "component_2col");
+    }
+}
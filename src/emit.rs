@@ -0,0 +1,261 @@
+//! Helpers for front ends that build RSX source text directly instead of going through rosetta.
+//!
+//! The text is parsed as a `CallBody` to make sure it is valid before handing it to
+//! `dioxus-autofmt`, which would otherwise panic on malformed input.
+
+use crate::ConvertError;
+use dioxus_autofmt::IndentOptions;
+use dioxus_rsx::CallBody;
+use proc_macro2::{Group, TokenStream, TokenTree};
+use syn::parse::Parse;
+
+/// Prefix of the identifiers used to smuggle comments through the formatter.
+const PLACEHOLDER_PREFIX: &str = "__html2rsx_placeholder_";
+
+/// Accumulates unformatted RSX source.
+#[derive(Debug, Default)]
+pub(crate) struct RsxBuilder {
+    out: String,
+    /// Comments that replace placeholder identifiers after formatting.
+    placeholders: Vec<String>,
+}
+
+impl RsxBuilder {
+    pub fn push(&mut self, text: &str) {
+        self.out.push_str(text);
+    }
+
+    /// Writes `// ...` comment lines among attributes, one per line of `text`.
+    pub fn comment(&mut self, text: &str) {
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            self.out.push_str("\n// ");
+            self.out.push_str(line.trim());
+        }
+        self.out.push('\n');
+    }
+
+    /// Writes `// ...` comment lines in place of a child node, one per line of `text`.
+    ///
+    /// `dioxus-autofmt` misplaces comments right before a closing brace, so each line is written
+    /// as a `{placeholder}` node and swapped for the comment once formatting is done.
+    pub fn node_comment(&mut self, text: &str) {
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let ident = placeholder(self.placeholders.len());
+            self.placeholders.push(format!("// {}", line.trim()));
+            self.out.push_str(&format!("{{{ident}}}\n"));
+        }
+    }
+
+    /// Writes an event handler whose body is a comment, e.g. `move |_| { /* save() */ }`.
+    ///
+    /// `dioxus-autofmt` drops comments inside expressions, so the closure body is written as a
+    /// placeholder identifier and swapped for the comment once formatting is done.
+    pub fn handler_stub(&mut self, comment: &str) {
+        let ident = placeholder(self.placeholders.len());
+        self.placeholders.push(block_comment(comment));
+        self.out.push_str(&format!("move |_| {{ {ident} }}"));
+    }
+
+    /// Parses and formats the accumulated RSX.
+    pub fn finish(self) -> Result<String, ConvertError> {
+        format_rsx(&self.out, &self.placeholders)
+    }
+}
+
+/// Formats raw RSX source the same way [`convert`](crate::convert) formats rosetta output.
+pub(crate) fn format_rsx(source: &str, placeholders: &[String]) -> Result<String, ConvertError> {
+    syn::parse_str::<CallBody>(source).map_err(|_| ConvertError::Format)?;
+    let formatted = dioxus_autofmt::fmt_block(source, 0, IndentOptions::default())
+        .ok_or(ConvertError::Format)?;
+    let formatted = fill_placeholders(formatted, placeholders);
+
+    Ok(formatted.trim_end().to_string())
+}
+
+/// The identifier that stands in for the `index`th placeholder until formatting is done.
+pub(crate) fn placeholder(index: usize) -> String {
+    format!("{PLACEHOLDER_PREFIX}{index}")
+}
+
+/// Swaps placeholder identifiers in formatted RSX for the comments they stand in for.
+///
+/// Placeholders for `//` comments are written as `{placeholder}` nodes, and take up their own
+/// lines. The formatter may still put a short node on the same line as its parent, in which case
+/// the comment becomes a `/* */` comment so the closing brace isn't commented out.
+pub(crate) fn fill_placeholders(formatted: String, placeholders: &[String]) -> String {
+    let mut lines = Vec::new();
+    for line in formatted.split('\n') {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let mut line = line.to_string();
+        // Replace from the highest index down so `_1` never matches the start of `_10`
+        for (index, replacement) in placeholders.iter().enumerate().rev() {
+            let ident = placeholder(index);
+            if !line.contains(&ident) {
+                continue;
+            }
+            let node = format!("{{{ident}}}");
+            if replacement.starts_with("//") && line.trim() == node {
+                line = replacement
+                    .lines()
+                    .map(|comment| format!("{indent}{comment}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+            } else if replacement.starts_with("//") {
+                let text: Vec<&str> = replacement
+                    .lines()
+                    .map(|comment| comment.trim_start_matches('/').trim())
+                    .collect();
+                line = line.replace(&node, &block_comment(&text.join(" ")));
+            } else {
+                line = line.replace(&ident, replacement);
+            }
+        }
+        lines.push(tidy_for_pattern(line));
+    }
+    lines.join("\n")
+}
+
+/// Removes the spaces the formatter leaves between the tokens of a `for` loop's pattern, e.g.
+/// `for (index , item) in` becomes `for (index, item) in`.
+fn tidy_for_pattern(line: String) -> String {
+    let indent = line.len() - line.trim_start().len();
+    let Some(pattern) = line[indent..].strip_prefix("for ") else {
+        return line;
+    };
+    let Some(end) = pattern.find(" in ") else {
+        return line;
+    };
+    let pattern = &pattern[..end];
+    let mut tidy = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        let next = chars.peek().copied();
+        let skip = c == ' '
+            && (matches!(next, Some(',' | ')' | ']'))
+                || tidy.ends_with(['(', '[', '&'])
+                || (next == Some('(')
+                    && tidy.ends_with(|c: char| c.is_alphanumeric() || c == '_')));
+        if !skip {
+            tidy.push(c);
+        }
+    }
+    format!(
+        "{}for {tidy}{}",
+        &line[..indent],
+        &line[indent + "for ".len() + end..]
+    )
+}
+
+/// Swaps the comments in formatted RSX that the formatter would drop or misplace for placeholders,
+/// so it can be formatted again. These are `/* */` comments, like those in handler stubs, and `//`
+/// comments right before a closing brace. [`fill_placeholders`] puts them back.
+pub(crate) fn protect_comments(rsx: &str) -> (String, Vec<String>) {
+    let mut comments = Vec::new();
+
+    let lines: Vec<&str> = rsx.split('\n').collect();
+    let mut protected_lines = Vec::with_capacity(lines.len());
+    for (i, line) in lines.iter().enumerate() {
+        let trimmed = line.trim();
+        let trailing = trimmed.starts_with("//")
+            && lines[i + 1..]
+                .iter()
+                .map(|line| line.trim())
+                .find(|line| !line.is_empty() && !line.starts_with("//"))
+                .is_some_and(|line| line.starts_with('}'));
+        if trailing {
+            let indent = &line[..line.len() - line.trim_start().len()];
+            protected_lines.push(format!("{indent}{{{}}}", placeholder(comments.len())));
+            comments.push(trimmed.to_string());
+        } else {
+            protected_lines.push(line.to_string());
+        }
+    }
+    let joined = protected_lines.join("\n");
+
+    let mut protected = String::with_capacity(joined.len());
+    let mut rest = joined.as_str();
+    while let Some(start) = rest.find("/*") {
+        let Some(end) = rest[start..].find("*/") else {
+            break;
+        };
+        protected.push_str(&rest[..start]);
+        protected.push_str(&placeholder(comments.len()));
+        comments.push(rest[start..start + end + 2].to_string());
+        rest = &rest[start + end + 2..];
+    }
+    protected.push_str(rest);
+    (protected, comments)
+}
+
+/// Parses generated Rust source into tokens without source locations.
+///
+/// `dioxus-autofmt` prints expressions from their spans, and every parsed string starts at line 1,
+/// so tokens parsed from a string and added to a tree would be printed from the wrong place.
+pub(crate) fn rust_tokens(source: &str) -> Option<TokenStream> {
+    let tokens: TokenStream = source.parse().ok()?;
+    Some(respan(tokens))
+}
+
+/// Parses generated RSX, like an attribute, into a node the formatter can print. See
+/// [`rust_tokens`].
+pub(crate) fn parse_rsx<T: Parse>(source: &str) -> Option<T> {
+    syn::parse2(rust_tokens(source)?).ok()
+}
+
+fn respan(tokens: TokenStream) -> TokenStream {
+    tokens
+        .into_iter()
+        .map(|tree| match tree {
+            TokenTree::Group(group) => {
+                let mut respanned = Group::new(group.delimiter(), respan(group.stream()));
+                respanned.set_span(proc_macro2::Span::call_site());
+                TokenTree::Group(respanned)
+            }
+            mut tree => {
+                tree.set_span(proc_macro2::Span::call_site());
+                tree
+            }
+        })
+        .collect()
+}
+
+/// Wraps text in a `/* */` comment, collapsing whitespace and defusing any `*/` inside it.
+pub(crate) fn block_comment(text: &str) -> String {
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("/* {} */", text.replace("*/", "* /"))
+}
+
+/// Quotes text as an RSX string literal. Braces are doubled so they are not treated as
+/// interpolations.
+pub(crate) fn quote_text(text: &str) -> String {
+    format!("\"{}\"", escape_text(text))
+}
+
+/// Escapes text for use inside an RSX string literal.
+pub(crate) fn escape_text(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '{' => out.push_str("{{"),
+            '}' => out.push_str("}}"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Quotes text as a plain Rust string literal, e.g. for `todo!("...")` messages.
+pub(crate) fn quote_rust(text: &str) -> String {
+    format!("{text:?}")
+}
+
+/// Writes a placeholder expression for code that could not be translated to Rust.
+pub(crate) fn todo_expr(source: &str) -> String {
+    let source = source.split_whitespace().collect::<Vec<_>>().join(" ");
+    format!("todo!({})", quote_rust(&source))
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+/// A 1-based line and column in the original input. Columns count characters, not bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Position {
+    /// Computes the position of a byte offset in `source`.
+    pub fn from_offset(source: &str, offset: usize) -> Self {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+
+    /// Computes the byte offset of this position in `source`, clamped to the end of the line.
+    pub fn to_offset(self, source: &str) -> usize {
+        let mut line_start = 0;
+        for _ in 1..self.line {
+            match source[line_start..].find('\n') {
+                Some(i) => line_start += i + 1,
+                None => return source.len(),
+            }
+        }
+
+        let line = &source[line_start..];
+        let line = &line[..line.find('\n').unwrap_or(line.len())];
+        line_start
+            + line
+                .char_indices()
+                .nth(self.column.saturating_sub(1))
+                .map_or(line.len(), |(i, _)| i)
+    }
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// A range of the original input, used to point errors at the offending markup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    /// Builds a span from a byte range in `source`.
+    pub fn from_offsets(source: &str, start: usize, end: usize) -> Self {
+        Self {
+            start: Position::from_offset(source, start),
+            end: Position::from_offset(source, end.max(start)),
+        }
+    }
+
+    /// Returns true if the given 1-based line is covered by this span.
+    pub fn contains_line(&self, line: usize) -> bool {
+        (self.start.line..=self.end.line).contains(&line)
+    }
+}
+
+/// Errors that can occur while converting HTML to RSX.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConvertError {
+    /// The input could not be parsed.
+    Parse { message: String, span: Option<Span> },
+    /// The generated RSX could not be formatted.
+    Format,
+    /// The input is valid HTML but uses something RSX cannot express.
+    Unsupported {
+        construct: String,
+        span: Option<Span>,
+    },
+}
+
+impl ConvertError {
+    /// The location of the error in the original input, if known.
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            ConvertError::Parse { span, .. } | ConvertError::Unsupported { span, .. } => *span,
+            ConvertError::Format => None,
+        }
+    }
+}
+
+impl fmt::Display for ConvertError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConvertError::Parse { message, .. } => write!(f, "failed to parse input: {message}")?,
+            ConvertError::Format => write!(f, "failed to format the generated RSX")?,
+            ConvertError::Unsupported { construct, .. } => {
+                write!(f, "unsupported construct: {construct}")?
+            }
+        }
+
+        if let Some(span) = self.span() {
+            write!(
+                f,
+                " (line {}, column {})",
+                span.start.line, span.start.column
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ConvertError {}
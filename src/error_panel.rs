@@ -0,0 +1,85 @@
+use dioxus::prelude::*;
+use dioxus_html_rsx::{ConvertError, Span};
+use std::ops::RangeInclusive;
+
+/// Number of lines shown above and below the offending span.
+const CONTEXT_LINES: usize = 2;
+
+/// The numbers of the lines shown for `span`: `CONTEXT_LINES` on either side, without going
+/// before the first line.
+fn context_lines(span: Span) -> RangeInclusive<usize> {
+    span.start.line.saturating_sub(CONTEXT_LINES).max(1)..=span.end.line + CONTEXT_LINES
+}
+
+#[component]
+/// Renders a conversion error along with the input lines it points at.
+///
+/// # Arguments
+///
+/// * `error` - The error returned by the conversion.
+/// * `input` - The HTML that was being converted, used to show the offending lines.
+pub fn ErrorPanel(error: ConvertError, input: String) -> Element {
+    let span = error.span();
+    let lines: Vec<(usize, &str)> = match span {
+        Some(span) => input
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line))
+            .filter(|(number, _)| context_lines(span).contains(number))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    rsx! {
+        div { style: "background-color: #2A1515; border: 1px solid #7F1D1D; border-radius: 0.25rem; padding: 0.75rem; font-family: monospace; font-size: 0.875rem;",
+            div { style: "color: #F87171; font-weight: bold; margin-bottom: 0.5rem; display: flex; align-items: center; gap: 0.5rem;",
+                span { "⚠️" }
+                "Conversion failed"
+            }
+            div { style: "color: #FCA5A5; margin-bottom: 0.75rem; white-space: pre-wrap;",
+                "{error}"
+            }
+
+            if !lines.is_empty() {
+                pre { style: "margin: 0; padding: 0.5rem 0; background-color: #1A1A1A; border-radius: 0.25rem; overflow-x: auto;",
+                    for (number, line) in lines {
+                        div {
+                            style: if span.is_some_and(|span| span.contains_line(number)) { "display: flex; background-color: #7F1D1D; color: #FFFFFF;" } else { "display: flex; color: #9CA3AF;" },
+                            span { style: "display: inline-block; min-width: 3rem; padding-right: 0.75rem; text-align: right; color: #6B7280; user-select: none;",
+                                "{number}"
+                            }
+                            span { style: "white-space: pre;", "{line}" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus_html_rsx::Position;
+
+    fn span(start: usize, end: usize) -> Span {
+        Span {
+            start: Position {
+                line: start,
+                column: 1,
+            },
+            end: Position {
+                line: end,
+                column: 1,
+            },
+        }
+    }
+
+    #[test]
+    fn context_is_clamped_to_the_first_line() {
+        assert_eq!(context_lines(span(1, 1)), 1..=3);
+        assert_eq!(context_lines(span(2, 2)), 1..=4);
+        assert_eq!(context_lines(span(3, 3)), 1..=5);
+        assert_eq!(context_lines(span(10, 12)), 8..=14);
+    }
+}
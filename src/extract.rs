@@ -0,0 +1,464 @@
+//! Extraction of repeated sibling subtrees into components.
+//!
+//! Markup copied from component libraries often repeats the same block several times with only the
+//! text or a few attribute values changed (accordion items, cards, feature lists). Runs of siblings
+//! with the same structure are replaced by invocations of a generated `#[component]`, with a
+//! `String` prop for every text or attribute value that differs between them and an
+//! `Option<String>` prop for attributes only some of them have:
+//!
+//! ```text
+//! Collapse { checked: "checked", text: "First" }
+//! Collapse { text: "Second" }
+//! ```
+//!
+//! Extracted subtrees are not searched again, so repetitions inside them stay as they are.
+
+use crate::ConvertError;
+use crate::component::{component_fn, component_name, module_name};
+use crate::emit::parse_rsx;
+use dioxus_rsx::{
+    Attribute, AttributeName, AttributeValue, BodyNode, CallBody, Element, HotLiteral,
+    TemplateBody, TextNode,
+};
+use proc_macro2::{Ident, Span};
+use quote::{ToTokens, quote};
+use syn::LitStr;
+
+/// How many siblings in a row must share a structure before they are extracted.
+const MIN_REPEATS: usize = 2;
+
+/// How many elements and text nodes a subtree needs before it is worth a component.
+const MIN_NODES: usize = 3;
+
+/// Prop names that RSX treats specially.
+const RESERVED_PROPS: [&str; 2] = ["key", "children"];
+
+/// Replaces runs of structurally identical siblings in `nodes` with component invocations and
+/// returns the source of the generated `#[component]` functions. Their names are added to `names`,
+/// which holds the component names already in use.
+pub(crate) fn extract_components(
+    nodes: &mut Vec<BodyNode>,
+    names: &mut Vec<String>,
+) -> Result<Vec<String>, ConvertError> {
+    let mut extractor = Extractor {
+        names,
+        components: Vec::new(),
+    };
+    extractor.extract(nodes)?;
+    Ok(extractor.components)
+}
+
+/// Returns `base`, or `base` with the lowest number from 2 up appended, whichever isn't in `names`
+/// yet, and adds it to `names`.
+pub(crate) fn unique_name(base: &str, names: &mut Vec<String>) -> String {
+    let name = (1..)
+        .map(|n| match n {
+            1 => base.to_string(),
+            n => format!("{base}{n}"),
+        })
+        .find(|name| !names.contains(name))
+        .expect("there is always an unused name");
+    names.push(name.clone());
+    name
+}
+
+struct Extractor<'a> {
+    names: &'a mut Vec<String>,
+    components: Vec<String>,
+}
+
+/// A text or attribute value that differs between the instances of a component.
+struct Slot {
+    prop: String,
+    /// The brace-escaped value in each instance, `None` where the attribute is missing.
+    values: Vec<Option<String>>,
+}
+
+impl Extractor<'_> {
+    fn extract(&mut self, nodes: &mut Vec<BodyNode>) -> Result<(), ConvertError> {
+        let shapes: Vec<Option<String>> = nodes.iter().map(shape).collect();
+        let mut remaining = std::mem::take(nodes).into_iter();
+
+        let mut index = 0;
+        while let Some(mut node) = remaining.next() {
+            let run = match &shapes[index] {
+                Some(shape) => {
+                    1 + shapes[index + 1..]
+                        .iter()
+                        .take_while(|other| other.as_ref() == Some(shape))
+                        .count()
+                }
+                None => 1,
+            };
+
+            if run >= MIN_REPEATS && size(&node) >= MIN_NODES {
+                let mut instances = vec![node];
+                instances.extend(remaining.by_ref().take(run - 1));
+                nodes.extend(self.component(&instances)?);
+                index += run;
+                continue;
+            }
+
+            if let BodyNode::Element(element) = &mut node {
+                self.extract(&mut element.children)?;
+            }
+            nodes.push(node);
+            index += 1;
+        }
+        Ok(())
+    }
+
+    /// Generates a component for `instances`, which all have the same shape, and returns an
+    /// invocation of it for each instance.
+    fn component(&mut self, instances: &[BodyNode]) -> Result<Vec<BodyNode>, ConvertError> {
+        let name = self.name(instances);
+        let mut slots = Vec::new();
+        let refs: Vec<&BodyNode> = instances.iter().collect();
+        let template = template(&refs, "", &mut slots);
+
+        let rsx =
+            dioxus_autofmt::write_block_out(&CallBody::new(TemplateBody::new(vec![template])))
+                .ok_or(ConvertError::Format)?;
+        let props: Vec<String> = slots
+            .iter()
+            .map(|slot| {
+                let ty = if slot.values.iter().any(Option::is_none) {
+                    "Option<String>"
+                } else {
+                    "String"
+                };
+                format!("{}: {ty}", slot.prop)
+            })
+            .collect();
+        self.components
+            .push(component_fn(&name, &props.join(", "), &rsx)?);
+
+        let ident = Ident::new(&name, Span::call_site());
+        Ok((0..instances.len())
+            .map(|instance| {
+                let fields = slots.iter().filter_map(|slot| {
+                    let value = slot.values[instance].as_ref()?;
+                    let prop = Ident::new(&slot.prop, Span::call_site());
+                    let value = LitStr::new(value, Span::call_site());
+                    Some(quote! { #prop: #value })
+                });
+                syn::parse2(quote! { #ident { #(#fields),* } })
+                    .expect("component invocations are valid RSX")
+            })
+            .collect())
+    }
+
+    /// Names a component after the first class its root elements share, or after their tag.
+    fn name(&mut self, instances: &[BodyNode]) -> String {
+        let BodyNode::Element(element) = &instances[0] else {
+            unreachable!("only elements are extracted");
+        };
+        let class = literal_attribute(element, "class")
+            .and_then(|class| class.split_whitespace().next().map(str::to_string))
+            .filter(|class| {
+                instances.iter().all(|instance| {
+                    let BodyNode::Element(element) = instance else {
+                        return false;
+                    };
+                    literal_attribute(element, "class")
+                        .is_some_and(|other| other.split_whitespace().next() == Some(class))
+                })
+            });
+        let base = match class {
+            Some(class) => component_name(&class),
+            None => component_name(&format!("{} item", element.name)),
+        };
+        unique_name(&base, self.names)
+    }
+}
+
+/// Describes a subtree's structure without its text and attribute values, or returns `None` if it
+/// contains anything other than elements with literal attributes, static text and components
+/// without props, like extracted icons.
+fn shape(node: &BodyNode) -> Option<String> {
+    match node {
+        BodyNode::Text(_) => Some("\"\"".to_string()),
+        BodyNode::Component(component)
+            if component.fields.is_empty() && component.children.roots.is_empty() =>
+        {
+            Some(format!("{} {{}}", component.name.to_token_stream()))
+        }
+        BodyNode::Element(element) => {
+            if !element.spreads.is_empty()
+                || element
+                    .raw_attributes
+                    .iter()
+                    .any(|attribute| literal(attribute).is_none())
+            {
+                return None;
+            }
+            let children = element
+                .children
+                .iter()
+                .map(shape)
+                .collect::<Option<Vec<_>>>()?;
+            Some(format!("{} {{ {} }}", element.name, children.join(", ")))
+        }
+        _ => None,
+    }
+}
+
+/// Counts the elements and text nodes in a subtree.
+fn size(node: &BodyNode) -> usize {
+    match node {
+        BodyNode::Element(element) => 1 + element.children.iter().map(size).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+fn literal(attribute: &Attribute) -> Option<String> {
+    match &attribute.value {
+        AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) => {
+            Some(value.formatted_input.source.value())
+        }
+        _ => None,
+    }
+}
+
+fn literal_attribute(element: &Element, name: &str) -> Option<String> {
+    element
+        .raw_attributes
+        .iter()
+        .find(|attribute| attribute.name.to_string() == name)
+        .and_then(literal)
+}
+
+/// Builds the component body from the nodes at the same position in every instance, adding a slot
+/// for each value that differs.
+fn template(instances: &[&BodyNode], parent: &str, slots: &mut Vec<Slot>) -> BodyNode {
+    match instances[0] {
+        BodyNode::Text(first) => {
+            let values: Vec<Option<String>> = instances
+                .iter()
+                .map(|node| match node {
+                    BodyNode::Text(text) => Some(text.input.source.value()),
+                    _ => None,
+                })
+                .collect();
+            if values.iter().all(|value| *value == values[0]) {
+                return BodyNode::Text(first.clone());
+            }
+            let prop = prop_name(slots, "text", parent);
+            let text = TextNode::from_text(&format!("{{{prop}}}"));
+            slots.push(Slot { prop, values });
+            BodyNode::Text(text)
+        }
+        BodyNode::Element(first) => {
+            let elements: Vec<&Element> = instances
+                .iter()
+                .map(|node| match node {
+                    BodyNode::Element(element) => element,
+                    _ => unreachable!("instances have the same shape"),
+                })
+                .collect();
+            let tag = first.name.to_string();
+
+            // Every attribute name used by any instance, in order of appearance
+            let mut names: Vec<&AttributeName> = Vec::new();
+            for element in &elements {
+                for attribute in &element.raw_attributes {
+                    if !names.iter().any(|name| **name == attribute.name) {
+                        names.push(&attribute.name);
+                    }
+                }
+            }
+
+            let mut attributes: Vec<Attribute> = names
+                .into_iter()
+                .map(|name| {
+                    let values: Vec<Option<String>> = elements
+                        .iter()
+                        .map(|element| {
+                            element
+                                .raw_attributes
+                                .iter()
+                                .find(|attribute| attribute.name == *name)
+                                .and_then(literal)
+                        })
+                        .collect();
+                    attribute(name, values, &tag, slots)
+                })
+                .collect();
+            attributes.sort_by_key(|attribute| attribute.name.to_string());
+
+            let children = (0..first.children.len())
+                .map(|index| {
+                    let children: Vec<&BodyNode> = elements
+                        .iter()
+                        .map(|element| &element.children[index])
+                        .collect();
+                    template(&children, &tag, slots)
+                })
+                .collect();
+
+            let mut element = first.clone();
+            element.raw_attributes = attributes;
+            element.children = children;
+            BodyNode::Element(element)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Builds a template attribute: the literal if every instance has the same value, otherwise a
+/// reference to a new prop.
+fn attribute(
+    name: &AttributeName,
+    values: Vec<Option<String>>,
+    tag: &str,
+    slots: &mut Vec<Slot>,
+) -> Attribute {
+    if let Some(Some(value)) = values.first()
+        && values.iter().all(|other| other.as_ref() == Some(value))
+    {
+        return Attribute::from_raw(
+            name.clone(),
+            AttributeValue::AttrLiteral(HotLiteral::from_raw_text(value)),
+        );
+    }
+
+    let raw = name.to_string();
+    let prop = prop_name(slots, raw.trim_start_matches("r#"), tag);
+    let optional = values.iter().any(Option::is_none);
+    slots.push(Slot {
+        prop: prop.clone(),
+        values,
+    });
+
+    if optional {
+        parse_rsx(&format!("{}: {prop}", name.to_token_stream()))
+            .expect("prop attributes are valid RSX")
+    } else {
+        Attribute::from_raw(
+            name.clone(),
+            AttributeValue::AttrLiteral(HotLiteral::from_raw_text(&format!("{{{prop}}}"))),
+        )
+    }
+}
+
+/// Picks an unused snake_case prop name for a value of `base` (an attribute name or `text`) inside
+/// a `tag` element, qualifying it with the tag and then a number when it is taken.
+fn prop_name(slots: &[Slot], base: &str, tag: &str) -> String {
+    let base = module_name(base);
+    let qualified = module_name(&format!("{tag} {base}"));
+    let taken =
+        |name: &str| RESERVED_PROPS.contains(&name) || slots.iter().any(|slot| slot.prop == name);
+
+    [base, qualified.clone()]
+        .into_iter()
+        .chain((2..).map(|n| format!("{qualified}_{n}")))
+        .find(|name| !taken(name))
+        .expect("there is always an unused name")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Options, convert_with_report};
+
+    fn extract(html: &str) -> crate::Conversion {
+        let options = Options {
+            extract_components: true,
+            ..Options::default()
+        };
+        convert_with_report(html, &options).unwrap()
+    }
+
+    #[test]
+    fn repeated_siblings_become_a_component() {
+        let conversion = extract(
+            r#"<ul>
+  <li class="card"><h3>One</h3><a href="/one">Read</a></li>
+  <li class="card"><h3>Two</h3><a href="/two">Read</a></li>
+  <li class="card"><h3>Three</h3><a href="/three" target="_blank">Read</a></li>
+</ul>"#,
+        );
+
+        let invocations: Vec<&str> = conversion.rsx.lines().map(str::trim).collect();
+        assert_eq!(
+            invocations,
+            [
+                "",
+                "ul {",
+                r#"Card { text: "One", href: "/one" }"#,
+                r#"Card { text: "Two", href: "/two" }"#,
+                r#"Card { text: "Three", href: "/three", target: "_blank" }"#,
+                "}"
+            ]
+        );
+        assert_eq!(conversion.components.len(), 1);
+        let component = &conversion.components[0];
+        assert!(
+            component.contains(
+                "pub fn Card(text: String, href: String, target: Option<String>) -> Element"
+            ),
+            "{component}"
+        );
+        assert!(component.contains(r#"h3 { "{text}" }"#), "{component}");
+        assert!(
+            component.contains(r#"a { href: "{href}", target, "Read" }"#),
+            "{component}"
+        );
+    }
+
+    #[test]
+    fn different_structures_are_left_alone() {
+        let conversion =
+            extract("<div><p>One <b>bold</b></p><p>Two <i>italic</i></p><p>Three</p></div>");
+        assert!(conversion.components.is_empty());
+        assert!(
+            conversion.rsx.contains("b { \"bold\" }"),
+            "{}",
+            conversion.rsx
+        );
+    }
+
+    #[test]
+    fn names_and_props_do_not_clash() {
+        let conversion = extract(
+            r#"<section>
+  <div><p>a</p><p>b</p></div>
+  <div><p>c</p><p>d</p></div>
+</section>
+<section>
+  <div><span>a</span><b>b</b></div>
+  <div><span>c</span><b>d</b></div>
+</section>"#,
+        );
+
+        assert_eq!(conversion.components.len(), 2);
+        assert!(
+            conversion.components[0].contains("pub fn DivItem(text: String, p_text: String)"),
+            "{}",
+            conversion.components[0]
+        );
+        assert!(
+            conversion.components[1].contains("pub fn DivItem2("),
+            "{}",
+            conversion.components[1]
+        );
+        assert!(
+            conversion
+                .rsx
+                .contains(r#"DivItem { text: "a", p_text: "b" }"#),
+            "{}",
+            conversion.rsx
+        );
+    }
+
+    #[test]
+    fn braces_stay_escaped() {
+        let conversion = extract("<p><b>{a}</b><i>x</i></p><p><b>{b}</b><i>y</i></p>");
+        assert!(
+            conversion
+                .rsx
+                .contains(r#"PItem { text: "{{a}}", i_text: "x" }"#),
+            "{}",
+            conversion.rsx
+        );
+    }
+}
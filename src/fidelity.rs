@@ -0,0 +1,546 @@
+//! Round-trip verification of a conversion.
+//!
+//! The HTML is converted to RSX, the RSX is serialized back to HTML with
+//! [`rsx_to_html`](crate::rsx_to_html), and both documents are parsed and normalized before their
+//! trees are compared. Any difference means the conversion lost or changed something.
+
+use crate::jsx::decode_entities;
+use crate::preprocess::Preprocessed;
+use crate::rules::{self, Marked};
+use crate::style::parse_declarations;
+use crate::whitespace::{preserves_whitespace, restore_whitespace};
+use crate::{
+    ConvertError, FormatOptions, InputFormat, Options, convert, is_boolean_attribute, rsx_to_html,
+    validate,
+};
+use dioxus_rsx_rosetta::{Dom, Node};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Longest text shown when describing a text node in a difference.
+const MAX_TEXT_PREVIEW: usize = 40;
+
+/// The result of a round-trip check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FidelityReport {
+    /// The RSX the input converted to.
+    pub rsx: String,
+    /// The HTML the RSX serialized back to.
+    pub html: String,
+    /// Structural differences between the input and `html`, in document order.
+    pub differences: Vec<Difference>,
+}
+
+impl FidelityReport {
+    /// Returns true if the round trip preserved every element, attribute and text node.
+    pub fn is_lossless(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// A single difference between the input and the round-tripped HTML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// XPath-like location of the node in the input, e.g. `/div[1]/p[2]`.
+    pub path: String,
+    pub kind: DifferenceKind,
+}
+
+/// What changed at a [`Difference`]'s path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DifferenceKind {
+    /// An attribute of the input is missing from the output.
+    MissingAttribute { name: String, value: String },
+    /// The output has an attribute the input did not.
+    ExtraAttribute { name: String, value: String },
+    /// An attribute kept its name but not its value.
+    ChangedAttribute {
+        name: String,
+        before: String,
+        after: String,
+    },
+    /// A text node's content changed.
+    ChangedText { before: String, after: String },
+    /// A node of the input is missing from the output.
+    MissingNode { node: String },
+    /// The output has a node the input did not.
+    ExtraNode { node: String },
+    /// A node is present in both, but at a different position among its siblings.
+    Reordered { node: String },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.path)?;
+        match &self.kind {
+            DifferenceKind::MissingAttribute { name, value } => {
+                write!(f, "missing attribute {name}=\"{value}\"")
+            }
+            DifferenceKind::ExtraAttribute { name, value } => {
+                write!(f, "extra attribute {name}=\"{value}\"")
+            }
+            DifferenceKind::ChangedAttribute {
+                name,
+                before,
+                after,
+            } => write!(
+                f,
+                "attribute {name} changed from \"{before}\" to \"{after}\""
+            ),
+            DifferenceKind::ChangedText { before, after } => {
+                write!(f, "text changed from {before:?} to {after:?}")
+            }
+            DifferenceKind::MissingNode { node } => write!(f, "missing {node}"),
+            DifferenceKind::ExtraNode { node } => write!(f, "extra {node}"),
+            DifferenceKind::Reordered { node } => write!(f, "{node} moved"),
+        }
+    }
+}
+
+/// Converts `html` to RSX and back, and reports every structural difference between the input
+/// and the result.
+///
+/// Only HTML input can be checked, since the round trip produces HTML. Components are never
+/// extracted for the check and assets are left as they are, since neither can be turned back into
+/// HTML.
+pub fn check_round_trip(html: &str, options: &Options) -> Result<FidelityReport, ConvertError> {
+    if options.input != InputFormat::Html {
+        return Err(ConvertError::Unsupported {
+            construct: "round-trip checks of non-HTML input".to_string(),
+            span: None,
+        });
+    }
+
+    let options = &Options {
+        extract_components: false,
+        icon_component: None,
+        extract_assets: false,
+        // Only the bare body converts back to HTML
+        format: FormatOptions::default(),
+        ..options.clone()
+    };
+    let rsx = convert(html, options)?;
+    let round_tripped = rsx_to_html(&rsx)?;
+
+    // Compare against the same text the converter parsed, so preprocessing renames don't count
+    let source = Preprocessed::for_options(html, options);
+    let mut before =
+        Dom::parse(&source.text).map_err(|err| validate::parse_error(err, html, &source))?;
+    // Custom attributes written by rewrite rules need the same rules to parse again
+    let output = Preprocessed::for_options(&round_tripped, options);
+    let mut after = Dom::parse(&output.text).map_err(|err| ConvertError::Parse {
+        message: format!("round-tripped HTML did not parse: {err}"),
+        span: None,
+    })?;
+    restore_whitespace(&mut before.children);
+    restore_whitespace(&mut after.children);
+
+    let mut differences = Vec::new();
+    diff_children(
+        "",
+        &normalize(&before.children, false, &source.marked),
+        &normalize(&after.children, false, &output.marked),
+        &mut differences,
+    );
+
+    Ok(FidelityReport {
+        rsx,
+        html: round_tripped,
+        differences,
+    })
+}
+
+/// A DOM node with everything that doesn't affect rendering stripped out.
+#[derive(Debug, PartialEq)]
+enum NormalNode {
+    Element {
+        name: String,
+        attributes: BTreeMap<String, String>,
+        children: Vec<NormalNode>,
+    },
+    Text(String),
+}
+
+impl NormalNode {
+    /// The element name, or `text()` for text, used to match siblings up.
+    fn signature(&self) -> &str {
+        match self {
+            NormalNode::Element { name, .. } => name,
+            NormalNode::Text(_) => "text()",
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            NormalNode::Element { name, .. } => format!("<{name}>"),
+            NormalNode::Text(text) if text.chars().count() > MAX_TEXT_PREVIEW => {
+                let preview: String = text.chars().take(MAX_TEXT_PREVIEW).collect();
+                format!("text {:?}", preview + "…")
+            }
+            NormalNode::Text(text) => format!("text {text:?}"),
+        }
+    }
+}
+
+/// Drops comments and whitespace-only text, collapses whitespace, decodes entities, drops the values
+/// of boolean attributes and sorts attributes and style declarations.
+///
+/// Inside whitespace-sensitive elements (`preformatted`), text is kept exactly as written.
+/// Attributes marked by rewrite rules get their final names back, except event handlers, which
+/// have no HTML form.
+fn normalize(nodes: &[Node], preformatted: bool, marked: &[Marked]) -> Vec<NormalNode> {
+    let mut normalized = Vec::new();
+
+    for node in nodes {
+        match node {
+            Node::Text(text) => {
+                let text = if preformatted {
+                    decode_entities(text)
+                } else {
+                    collapse_whitespace(&decode_entities(text))
+                };
+                if text.is_empty() {
+                    continue;
+                }
+                // Removing a comment can leave two text nodes next to each other
+                if let Some(NormalNode::Text(previous)) = normalized.last_mut() {
+                    if !preformatted {
+                        previous.push(' ');
+                    }
+                    previous.push_str(&text);
+                } else {
+                    normalized.push(NormalNode::Text(text));
+                }
+            }
+            Node::Element(element) => {
+                let mut attributes = BTreeMap::new();
+                if let Some(id) = &element.id {
+                    attributes.insert("id".to_string(), decode_entities(id));
+                }
+                if !element.classes.is_empty() {
+                    let classes: Vec<String> = element
+                        .classes
+                        .iter()
+                        .map(|class| decode_entities(class))
+                        .collect();
+                    attributes.insert("class".to_string(), classes.join(" "));
+                }
+                for (name, value) in &element.attributes {
+                    let name = match rules::marked(marked, name) {
+                        Some(Marked::Custom(name)) => name,
+                        Some(Marked::Event(_)) => continue,
+                        None => name,
+                    };
+                    let value = decode_entities(value.as_deref().unwrap_or_default());
+                    let value = match name.as_str() {
+                        "style" => normalize_style(&value),
+                        // Only the presence of a boolean attribute matters to a browser
+                        name if is_boolean_attribute(name) => String::new(),
+                        _ => value,
+                    };
+                    attributes.insert(name.to_ascii_lowercase(), value);
+                }
+
+                let preformatted = preformatted
+                    || preserves_whitespace(
+                        &element.name,
+                        attributes.get("style").map(String::as_str),
+                    );
+                normalized.push(NormalNode::Element {
+                    name: element.name.to_ascii_lowercase(),
+                    attributes,
+                    children: normalize(&element.children, preformatted, marked),
+                });
+            }
+            Node::Comment(_) => {}
+        }
+    }
+
+    normalized
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Sorts declarations so splitting styles into attributes and merging them back isn't a change.
+fn normalize_style(style: &str) -> String {
+    let mut declarations: Vec<String> = parse_declarations(style)
+        .into_iter()
+        .map(|(property, value)| format!("{}: {value}", property.to_ascii_lowercase()))
+        .collect();
+    declarations.sort();
+    declarations.join("; ")
+}
+
+/// Builds the path of a child, numbering it among siblings with the same signature.
+fn child_path(parent: &str, siblings: &[NormalNode], index: usize) -> String {
+    let signature = siblings[index].signature();
+    let position = siblings[..index]
+        .iter()
+        .filter(|sibling| sibling.signature() == signature)
+        .count()
+        + 1;
+    format!("{parent}/{signature}[{position}]")
+}
+
+fn diff_children(
+    path: &str,
+    before: &[NormalNode],
+    after: &[NormalNode],
+    differences: &mut Vec<Difference>,
+) {
+    let pairs = longest_common_subsequence(before, after);
+    let mut unmatched_after: Vec<usize> = (0..after.len())
+        .filter(|j| !pairs.iter().any(|&(_, b)| b == *j))
+        .collect();
+
+    let mut pairs = pairs.into_iter().peekable();
+    for (i, node) in before.iter().enumerate() {
+        let node_path = child_path(path, before, i);
+
+        if let Some((_, j)) = pairs.next_if(|&(b, _)| b == i) {
+            diff_node(&node_path, node, &after[j], differences);
+            continue;
+        }
+
+        // A node that only failed to line up with its siblings was moved, not dropped
+        let moved = unmatched_after
+            .iter()
+            .position(|&j| after[j].signature() == node.signature());
+        let kind = match moved {
+            Some(position) => {
+                unmatched_after.remove(position);
+                DifferenceKind::Reordered {
+                    node: node.describe(),
+                }
+            }
+            None => DifferenceKind::MissingNode {
+                node: node.describe(),
+            },
+        };
+        differences.push(Difference {
+            path: node_path,
+            kind,
+        });
+    }
+
+    for j in unmatched_after {
+        differences.push(Difference {
+            path: child_path(path, after, j),
+            kind: DifferenceKind::ExtraNode {
+                node: after[j].describe(),
+            },
+        });
+    }
+}
+
+fn diff_node(
+    path: &str,
+    before: &NormalNode,
+    after: &NormalNode,
+    differences: &mut Vec<Difference>,
+) {
+    let mut push = |kind| {
+        differences.push(Difference {
+            path: path.to_string(),
+            kind,
+        })
+    };
+
+    match (before, after) {
+        (NormalNode::Text(before), NormalNode::Text(after)) => {
+            if before != after {
+                push(DifferenceKind::ChangedText {
+                    before: before.clone(),
+                    after: after.clone(),
+                });
+            }
+        }
+        (
+            NormalNode::Element {
+                attributes: before_attributes,
+                children: before_children,
+                ..
+            },
+            NormalNode::Element {
+                attributes: after_attributes,
+                children: after_children,
+                ..
+            },
+        ) => {
+            for (name, value) in before_attributes {
+                match after_attributes.get(name) {
+                    None => push(DifferenceKind::MissingAttribute {
+                        name: name.clone(),
+                        value: value.clone(),
+                    }),
+                    Some(after) if after != value => push(DifferenceKind::ChangedAttribute {
+                        name: name.clone(),
+                        before: value.clone(),
+                        after: after.clone(),
+                    }),
+                    Some(_) => {}
+                }
+            }
+            for (name, value) in after_attributes {
+                if !before_attributes.contains_key(name) {
+                    push(DifferenceKind::ExtraAttribute {
+                        name: name.clone(),
+                        value: value.clone(),
+                    });
+                }
+            }
+            diff_children(path, before_children, after_children, differences);
+        }
+        // Siblings are only paired up when their signatures match
+        _ => unreachable!("paired nodes of different kinds"),
+    }
+}
+
+/// Pairs up siblings with equal signatures, keeping their relative order.
+fn longest_common_subsequence(before: &[NormalNode], after: &[NormalNode]) -> Vec<(usize, usize)> {
+    let (n, m) = (before.len(), after.len());
+    let mut lengths = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lengths[i][j] = if before[i].signature() == after[j].signature() {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if before[i].signature() == after[j].signature() {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn differences(html: &str) -> Vec<Difference> {
+        check_round_trip(html, &Options::default())
+            .unwrap()
+            .differences
+    }
+
+    fn diff(before: &str, after: &str) -> Vec<Difference> {
+        let normal = |html: &str| {
+            let mut dom = Dom::parse(html).unwrap();
+            restore_whitespace(&mut dom.children);
+            normalize(&dom.children, false, &[])
+        };
+        let mut differences = Vec::new();
+        diff_children("", &normal(before), &normal(after), &mut differences);
+        differences
+    }
+
+    #[test]
+    fn named_and_numeric_entities_survive() {
+        assert_eq!(
+            differences(
+                r#"<p title="x &amp; y &mdash; z">a &mdash; b &#169; &#x27;c&#x27; &amp; &lt;d&gt;</p><a href="/?a=1&amp;b=2">l</a>"#
+            ),
+            []
+        );
+    }
+
+    #[test]
+    fn entities_compare_equal_to_their_characters() {
+        assert_eq!(diff("<p>&copy; &#8212;</p>", "<p>© —</p>"), []);
+        assert_eq!(
+            diff("<p>&copy;</p>", "<p>(c)</p>"),
+            [Difference {
+                path: "/p[1]/text()[1]".to_string(),
+                kind: DifferenceKind::ChangedText {
+                    before: "©".to_string(),
+                    after: "(c)".to_string(),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn whitespace_only_matters_in_preformatted_text() {
+        assert_eq!(differences("<div>\n  <p>a\n   b</p>\n</div>"), []);
+        assert_eq!(diff("<p>a\n   b</p>", "<p>a b</p>"), []);
+        assert_eq!(
+            diff("<pre>a\n   b</pre>", "<pre>a b</pre>"),
+            [Difference {
+                path: "/pre[1]/text()[1]".to_string(),
+                kind: DifferenceKind::ChangedText {
+                    before: "a\n   b".to_string(),
+                    after: "a b".to_string(),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn boolean_attributes_compare_as_rendered() {
+        assert_eq!(
+            differences(
+                r#"<input type="checkbox" checked="checked" disabled=""><select><option selected="selected">a</option></select>"#
+            ),
+            []
+        );
+        assert_eq!(
+            diff(
+                r#"<input checked="checked">"#,
+                &rsx_to_html(r#"input { checked: "checked" }"#).unwrap()
+            ),
+            [Difference {
+                path: "/input[1]".to_string(),
+                kind: DifferenceKind::MissingAttribute {
+                    name: "checked".to_string(),
+                    value: String::new(),
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn attribute_and_style_order_is_ignored() {
+        assert_eq!(
+            diff(
+                r#"<a href="/" class="x" style="color: red; margin: 0">l</a>"#,
+                r#"<a style="margin:0;color:red" class="x" href="/">l</a>"#
+            ),
+            []
+        );
+        assert_eq!(
+            differences(r#"<input type="text" name="q" style="width: 1px; color: red">"#),
+            []
+        );
+    }
+
+    #[test]
+    fn moved_siblings_are_reported_as_reordered() {
+        assert_eq!(
+            diff(
+                "<div><p>a</p><span>b</span></div>",
+                "<div><span>b</span><p>a</p></div>"
+            ),
+            [Difference {
+                path: "/div[1]/p[1]".to_string(),
+                kind: DifferenceKind::Reordered {
+                    node: "<p>".to_string(),
+                },
+            }]
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use dioxus::prelude::*;
+use dioxus_html_rsx::{DifferenceKind, FidelityReport};
+
+#[component]
+/// Renders the result of a round-trip check as a list of differences.
+///
+/// # Arguments
+///
+/// * `report` - The report returned by `check_round_trip`.
+pub fn FidelityPanel(report: FidelityReport) -> Element {
+    if report.is_lossless() {
+        return rsx! {
+            div { style: "margin-top: 0.75rem; background-color: #132A1B; border: 1px solid #166534; border-radius: 0.25rem; padding: 0.75rem; color: #4ADE80; font-family: monospace; font-size: 0.875rem;",
+                "✔ Round trip is lossless"
+            }
+        };
+    }
+
+    let count = report.differences.len();
+    rsx! {
+        div { style: "margin-top: 0.75rem; background-color: #2A2415; border: 1px solid #854D0E; border-radius: 0.25rem; padding: 0.75rem; font-family: monospace; font-size: 0.875rem; max-height: 30vh; overflow: auto;",
+            div { style: "color: #FACC15; font-weight: bold; margin-bottom: 0.5rem;",
+                if count == 1 {
+                    "⚠ 1 difference after converting back to HTML"
+                } else {
+                    "⚠ {count} differences after converting back to HTML"
+                }
+            }
+            for difference in report.differences {
+                div { style: "display: flex; gap: 0.75rem; padding: 0.15rem 0;",
+                    span { style: "color: {kind_color(&difference.kind)}; min-width: 5rem;",
+                        "{kind_label(&difference.kind)}"
+                    }
+                    span { style: "color: #9CA3AF;", "{difference.path}" }
+                    span { style: "color: #E5E7EB; white-space: pre-wrap;",
+                        "{difference_detail(&difference.kind)}"
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn kind_label(kind: &DifferenceKind) -> &'static str {
+    match kind {
+        DifferenceKind::MissingAttribute { .. } | DifferenceKind::MissingNode { .. } => "missing",
+        DifferenceKind::ExtraAttribute { .. } | DifferenceKind::ExtraNode { .. } => "extra",
+        DifferenceKind::ChangedAttribute { .. } | DifferenceKind::ChangedText { .. } => "changed",
+        DifferenceKind::Reordered { .. } => "moved",
+    }
+}
+
+fn kind_color(kind: &DifferenceKind) -> &'static str {
+    match kind {
+        DifferenceKind::MissingAttribute { .. } | DifferenceKind::MissingNode { .. } => "#F87171",
+        DifferenceKind::ExtraAttribute { .. } | DifferenceKind::ExtraNode { .. } => "#60A5FA",
+        DifferenceKind::ChangedAttribute { .. } | DifferenceKind::ChangedText { .. } => "#FACC15",
+        DifferenceKind::Reordered { .. } => "#C084FC",
+    }
+}
+
+fn difference_detail(kind: &DifferenceKind) -> String {
+    match kind {
+        DifferenceKind::MissingAttribute { name, value }
+        | DifferenceKind::ExtraAttribute { name, value } => format!("{name}=\"{value}\""),
+        DifferenceKind::ChangedAttribute {
+            name,
+            before,
+            after,
+        } => format!("{name}: \"{before}\" → \"{after}\""),
+        DifferenceKind::ChangedText { before, after } => format!("{before:?} → {after:?}"),
+        DifferenceKind::MissingNode { node }
+        | DifferenceKind::ExtraNode { node }
+        | DifferenceKind::Reordered { node } => node.clone(),
+    }
+}
@@ -0,0 +1,364 @@
+//! Layout of the generated code.
+//!
+//! `dioxus-autofmt` always indents with four spaces and decides on its own when an element fits on
+//! one line. [`FormatOptions`] are applied to its output afterwards: elements that break the
+//! configured limits are written again with one attribute or child per line, and the indentation
+//! is changed to the configured width.
+
+use crate::component::component_name;
+use crate::emit::{fill_placeholders, protect_comments};
+use dioxus_autofmt::{IndentOptions, IndentType};
+use dioxus_rsx::{BodyNode, CallBody};
+use proc_macro2::LineColumn;
+use syn::parse::Parser;
+
+/// The indentation `dioxus-autofmt` writes.
+const AUTOFMT_INDENT: usize = 4;
+
+/// How converted RSX is laid out, see [`Options::format`](crate::Options::format).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// Spaces per indentation level.
+    pub indent_width: usize,
+    /// Break elements and components whose line is longer than this into one attribute or child
+    /// per line. Lines that can't be broken, like long text, are kept. Lines are never joined, so
+    /// this can only make the output narrower than the formatter's own limit of about 100.
+    pub max_line_length: Option<usize>,
+    /// Put each attribute on its own line for elements and components with more attributes than
+    /// this.
+    pub max_attributes_per_line: Option<usize>,
+    /// What the body is wrapped in.
+    pub output: OutputMode,
+    /// The name of the component written by [`OutputMode::Component`] and [`OutputMode::App`],
+    /// turned into PascalCase with [`component_name`](crate::component_name). Defaults to the name
+    /// a single-file component declares, or `App`.
+    pub component_name: Option<String>,
+}
+
+/// What [`Conversion::output`](crate::Conversion::output) wraps the converted body in.
+///
+/// Single-file components are always written as their `#[component]`, since their body refers to
+/// the props it declares.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputMode {
+    /// The bare RSX body.
+    #[default]
+    Body,
+    /// The body in `rsx! { }`, to paste into a function.
+    Rsx,
+    /// A `#[component]` function returning the body, with `use dioxus::prelude::*;`.
+    Component,
+    /// The component and a `main` that launches it, as the `main.rs` of a new app.
+    App,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            indent_width: AUTOFMT_INDENT,
+            max_line_length: None,
+            max_attributes_per_line: None,
+            output: OutputMode::default(),
+            component_name: None,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// The name of the component [`Conversion::output`](crate::Conversion::output) wraps the body
+    /// in, if any, which generated components must not take. Single-file components are named by
+    /// their own conversion.
+    pub(crate) fn wrapper_name(&self) -> Option<String> {
+        match self.output {
+            OutputMode::Body | OutputMode::Rsx => None,
+            OutputMode::Component | OutputMode::App => Some(component_name(
+                self.component_name.as_deref().unwrap_or("App"),
+            )),
+        }
+    }
+
+    /// Lays out formatted code, like [`Conversion::output`](crate::Conversion::output).
+    pub(crate) fn apply(&self, code: &str) -> String {
+        if *self == Self::default() {
+            return code.to_string();
+        }
+
+        // The formatter drops comments inside expressions, like those in handler stubs
+        let (protected, comments) = protect_comments(code);
+        let mut lines: Vec<String> = protected.lines().map(str::to_string).collect();
+        let mut i = 0;
+        while i < lines.len() {
+            if let Some((end, replacement)) = self.break_element(&lines, i) {
+                lines.splice(i..=end, replacement);
+            }
+            i += 1;
+        }
+
+        let mut out = String::with_capacity(code.len());
+        for line in &lines {
+            let indent = indent_of(line);
+            let levels = indent / AUTOFMT_INDENT;
+            out.push_str(&" ".repeat(levels * self.indent_width + indent % AUTOFMT_INDENT));
+            out.push_str(&line[indent..]);
+            out.push('\n');
+        }
+        if !code.ends_with('\n') {
+            out.pop();
+        }
+        fill_placeholders(out, &comments)
+    }
+
+    /// Lays out the element or component starting at `lines[start]` with one attribute or child
+    /// per line if it breaks the limits, returning the last line it took up and its new lines.
+    fn break_element(&self, lines: &[String], start: usize) -> Option<(usize, Vec<String>)> {
+        let first = &lines[start];
+        let indent = indent_of(first);
+        let trimmed = first.trim_end();
+        // Elements that already have a line per attribute end in their opening brace
+        if trimmed.ends_with('{') || !trimmed[indent..].starts_with(|c: char| c.is_alphabetic()) {
+            return None;
+        }
+
+        // One-line elements end on the same line, others on the next line closing at their indent
+        let end = if trimmed.ends_with('}') {
+            start
+        } else {
+            let closing = format!("{}}}", " ".repeat(indent));
+            start
+                + lines[start..]
+                    .iter()
+                    .position(|line| line.trim_end() == closing)?
+        };
+        let source = lines[start..=end]
+            .iter()
+            .map(|line| line.get(indent..).unwrap_or(line.trim_start()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let body = CallBody::parse_strict.parse_str(&source).ok()?;
+        let [root] = body.body.roots.as_slice() else {
+            return None;
+        };
+
+        let (attributes, children, brace) = match root {
+            BodyNode::Element(element) => (
+                element.raw_attributes.len() + element.spreads.len(),
+                &element.children,
+                element.brace.as_ref()?,
+            ),
+            BodyNode::Component(component) => (
+                component.fields.len() + component.spreads.len(),
+                &component.children.roots,
+                component.brace.as_ref()?,
+            ),
+            _ => return None,
+        };
+        let width = (indent / AUTOFMT_INDENT) * self.indent_width + trimmed.len() - indent;
+        let too_long = self.max_line_length.is_some_and(|max| width > max);
+        let too_many = self
+            .max_attributes_per_line
+            .is_some_and(|max| attributes > max);
+        if !(too_long || too_many) || attributes + children.len() == 0 {
+            return None;
+        }
+
+        // The formatter can put every attribute on its own line, but only for the whole tree, so
+        // the element is formatted without its children and they are formatted separately
+        let (opening, children) = match children.first() {
+            Some(first) => {
+                let range = offset(&source, first.span().start())?
+                    ..offset(&source, brace.span.close().start())?;
+                let bare = format!("{}{}", &source[..range.start], &source[range.end..]);
+                (bare, Some(source[range].to_string()))
+            }
+            _ => (source.clone(), None),
+        };
+        let opening = dioxus_autofmt::fmt_block(
+            &opening,
+            0,
+            IndentOptions::new(IndentType::Spaces, AUTOFMT_INDENT, true),
+        )?;
+        let mut opening = block_lines(&opening);
+        // Drop the closing brace, and the indent of the body
+        opening.pop();
+        for line in &mut opening {
+            line.drain(..AUTOFMT_INDENT.min(indent_of(line)));
+        }
+        if let [single] = opening.as_mut_slice() {
+            // Without attributes the element is empty, e.g. `div {}`
+            *single = format!(
+                "{} {{",
+                single.trim_end_matches('}').trim_end_matches(['{', ' '])
+            );
+        } else if children.is_some()
+            && let Some(last) = opening.last_mut()
+            && !last.ends_with(',')
+        {
+            last.push(',');
+        }
+        let children = match children {
+            Some(children) => block_lines(&dioxus_autofmt::fmt_block(
+                &children,
+                0,
+                IndentOptions::default(),
+            )?),
+            None => Vec::new(),
+        };
+
+        let prefix = " ".repeat(indent);
+        let replacement = opening
+            .into_iter()
+            .chain(children)
+            .chain(["}".to_string()])
+            .map(|line| format!("{prefix}{line}"))
+            .collect();
+        Some((end, replacement))
+    }
+}
+
+/// Wraps a formatted RSX body in `rsx! { }`.
+pub(crate) fn rsx_macro(rsx: &str) -> String {
+    let (protected, comments) = protect_comments(rsx);
+    match dioxus_autofmt::fmt_block(&protected, 0, IndentOptions::default()) {
+        Some(body) => format!("rsx! {{{}}}", fill_placeholders(body, &comments)),
+        None => rsx.to_string(),
+    }
+}
+
+/// The lines of a block formatted by `dioxus-autofmt` at indent level 0, which puts a single short
+/// node on the same line as the braces around it.
+fn block_lines(formatted: &str) -> Vec<String> {
+    if !formatted.contains('\n') {
+        return vec![format!(
+            "{}{}",
+            " ".repeat(AUTOFMT_INDENT),
+            formatted.trim()
+        )];
+    }
+    formatted
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// The byte offset of a position in `source`.
+fn offset(source: &str, position: LineColumn) -> Option<usize> {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(position.line.checked_sub(1)?)
+        .map(str::len)
+        .sum();
+    let line = source[line_start..].lines().next().unwrap_or_default();
+    let column = line
+        .char_indices()
+        .nth(position.column)
+        .map_or(line.len(), |(index, _)| index);
+    Some(line_start + column)
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FormatOptions, Options, OutputMode, convert};
+
+    fn convert_formatted(html: &str, format: FormatOptions) -> String {
+        let options = Options {
+            handler_stubs: true,
+            format,
+            ..Options::default()
+        };
+        convert(html, &options).unwrap()
+    }
+
+    #[test]
+    fn indent_width_applies_to_every_level() {
+        let output = convert_formatted(
+            "<ul><li><b>one</b></li><li>two</li></ul>",
+            FormatOptions {
+                indent_width: 2,
+                ..FormatOptions::default()
+            },
+        );
+        assert_eq!(
+            output.trim_start_matches('\n'),
+            "  ul {\n    li {\n      b { \"one\" }\n    }\n    li { \"two\" }\n  }"
+        );
+    }
+
+    #[test]
+    fn elements_over_the_limits_get_a_line_per_attribute() {
+        let html = r#"<div class="card" id="main"><a href="/x" onclick="go()">Go</a><p class="note">a paragraph that is too long for the line</p></div>"#;
+        let output = convert_formatted(
+            html,
+            FormatOptions {
+                max_line_length: Some(50),
+                max_attributes_per_line: Some(1),
+                ..FormatOptions::default()
+            },
+        );
+        let expected = r#"
+    div {
+        class: "card",
+        id: "main",
+        a {
+            href: "/x",
+            onclick: move |_| { /* go() */ },
+            "Go"
+        }
+        p {
+            class: "note",
+            "a paragraph that is too long for the line"
+        }
+    }"#;
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn body_is_wrapped_in_rsx() {
+        let output = convert_formatted(
+            "<p>one</p><p>two</p>",
+            FormatOptions {
+                output: OutputMode::Rsx,
+                ..FormatOptions::default()
+            },
+        );
+        assert_eq!(output, "rsx! {\n    p { \"one\" }\n    p { \"two\" }\n}");
+    }
+
+    #[test]
+    fn app_output_launches_the_named_component() {
+        let output = convert_formatted(
+            "<p>hi</p>",
+            FormatOptions {
+                output: OutputMode::App,
+                component_name: Some("landing page".to_string()),
+                ..FormatOptions::default()
+            },
+        );
+        assert_eq!(
+            output,
+            "use dioxus::prelude::*;\n\nfn main() {\n    dioxus::launch(LandingPage);\n}\n\n\
+             #[component]\npub fn LandingPage() -> Element {\n    rsx! {\n        p { \"hi\" }\n    }\n}"
+        );
+    }
+
+    #[test]
+    fn generated_components_avoid_the_wrapper_name() {
+        let options = Options {
+            icon_component: Some("Icon".to_string()),
+            format: FormatOptions {
+                output: OutputMode::Component,
+                component_name: Some("icon".to_string()),
+                ..FormatOptions::default()
+            },
+            ..Options::default()
+        };
+        let output = convert("<svg><path d=\"M0 0\"/></svg>", &options).unwrap();
+        assert!(output.contains("pub fn Icon() -> Element"), "{output}");
+        assert!(output.contains("Icon2 {}"), "{output}");
+        assert!(output.contains("pub fn Icon2("), "{output}");
+    }
+}
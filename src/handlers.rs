@@ -0,0 +1,162 @@
+//! Reporting of event handlers that were turned into stubs.
+//!
+//! Inline JavaScript like `onclick="save()"` can't run in Dioxus, so it is written as
+//! `onclick: move |_| { /* save() */ }` and listed in the [`Conversion`] so it can be ported by hand.
+
+use crate::component::{app_main, component_fn};
+use crate::format::rsx_macro;
+use crate::{Asset, ComponentSignature, FormatOptions, OutputMode, Span, component_name};
+use std::fmt;
+
+/// The result of [`convert_with_report`](crate::convert_with_report).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conversion {
+    /// The formatted RSX body.
+    pub rsx: String,
+    /// Every handler written as a stub, in document order.
+    pub handlers: Vec<HandlerStub>,
+    /// The `#[component]` functions generated for
+    /// [`Options::icon_component`](crate::Options::icon_component), followed by those generated by
+    /// [`Options::extract_components`](crate::Options::extract_components).
+    pub components: Vec<String>,
+    /// The files referenced through `asset!()` when
+    /// [`Options::extract_assets`](crate::Options::extract_assets) is set, in document order.
+    pub assets: Vec<Asset>,
+    /// The component a Vue or Svelte single-file component declares, which the body belongs in.
+    /// `None` for other input formats.
+    pub component: Option<ComponentSignature>,
+    /// How [`output`](Conversion::output) lays out the code, from
+    /// [`Options::format`](crate::Options::format). [`rsx`](Conversion::rsx) and
+    /// [`components`](Conversion::components) keep the formatter's layout.
+    pub format: FormatOptions,
+}
+
+impl Conversion {
+    /// The RSX body, wrapped as [`Options::format`](crate::Options::format) asks (and in its
+    /// `#[component]` for single-file components), followed by the extracted components, as
+    /// returned by [`convert`](crate::convert).
+    pub fn output(&self) -> String {
+        let name = match (&self.format.component_name, &self.component) {
+            (Some(name), _) => component_name(name),
+            (None, Some(component)) => component.name.clone(),
+            (None, None) => "App".to_string(),
+        };
+        let props = self
+            .component
+            .as_ref()
+            .map_or("", |component| component.props.as_str());
+        let wrapped = match (self.format.output, &self.component) {
+            (OutputMode::Body, None) => Ok(self.rsx.clone()),
+            (OutputMode::Rsx, None) => Ok(rsx_macro(&self.rsx)),
+            (OutputMode::Body | OutputMode::Rsx, Some(_)) => component_fn(&name, props, &self.rsx),
+            (OutputMode::Component, _) => component_fn(&name, props, &self.rsx)
+                .map(|component| format!("use dioxus::prelude::*;\n\n{component}")),
+            (OutputMode::App, _) => app_main(&name, props, &self.rsx),
+        };
+        // Conversion already checked that the body formats
+        let mut output = wrapped
+            .map(|wrapped| wrapped.trim_end().to_string())
+            .unwrap_or_else(|_| self.rsx.clone());
+        for component in &self.components {
+            output.push_str("\n\n");
+            output.push_str(component.trim_end());
+        }
+        self.format.apply(&output)
+    }
+}
+
+/// An event handler whose JavaScript was kept in a comment and needs porting to Rust.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HandlerStub {
+    /// The RSX event, e.g. `onclick`.
+    pub event: String,
+    /// The element or component the handler is on.
+    pub element: String,
+    /// The original JavaScript.
+    pub code: String,
+    /// Where the handler is in the input, when known.
+    pub span: Option<Span>,
+}
+
+impl fmt::Display for HandlerStub {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(span) = &self.span {
+            write!(f, "{}: ", span.start)?;
+        }
+        write!(f, "<{}> {}: {}", self.element, self.event, self.code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InputFormat, Options, convert_with_report};
+
+    fn stubs() -> Options {
+        Options {
+            handler_stubs: true,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn inline_handlers_become_stubs() {
+        let html = "<form onsubmit=\"return check()\">\n  <button onClick=\"save(&quot;draft&quot;)\">Save</button>\n</form>";
+        let conversion = convert_with_report(html, &stubs()).unwrap();
+
+        assert!(
+            conversion
+                .rsx
+                .contains("onsubmit: move |_| { /* return check() */ }"),
+            "{}",
+            conversion.rsx
+        );
+        assert!(
+            conversion
+                .rsx
+                .contains(r#"onclick: move |_| { /* save("draft") */ }"#),
+            "{}",
+            conversion.rsx
+        );
+
+        let report: Vec<String> = conversion
+            .handlers
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            report,
+            [
+                "1:7: <form> onsubmit: return check()",
+                "2:11: <button> onclick: save(\"draft\")"
+            ]
+        );
+    }
+
+    #[test]
+    fn handlers_stay_attributes_by_default() {
+        let conversion = convert_with_report(
+            "<button onclick=\"save()\">Save</button>",
+            &Options::default(),
+        )
+        .unwrap();
+        assert!(conversion.handlers.is_empty());
+        assert!(!conversion.rsx.contains("move |_|"), "{}", conversion.rsx);
+    }
+
+    #[test]
+    fn jsx_stubs_are_reported() {
+        let options = Options {
+            input: InputFormat::Jsx,
+            ..stubs()
+        };
+        let conversion = convert_with_report(
+            "<Button onClick={() => setOpen(true)} onBlur={blur} />",
+            &options,
+        )
+        .unwrap();
+
+        assert_eq!(conversion.handlers.len(), 1);
+        assert_eq!(conversion.handlers[0].element, "Button");
+        assert_eq!(conversion.handlers[0].code, "() => setOpen(true)");
+    }
+}
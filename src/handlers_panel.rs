@@ -0,0 +1,41 @@
+use dioxus::prelude::*;
+use dioxus_html_rsx::HandlerStub;
+
+#[component]
+/// Lists the event handlers that were written as stubs and still need porting to Rust.
+///
+/// # Arguments
+///
+/// * `handlers` - The handlers reported by `convert_with_report`.
+pub fn HandlersPanel(handlers: Vec<HandlerStub>) -> Element {
+    let count = handlers.len();
+    rsx! {
+        div { style: "margin-top: 0.75rem; background-color: #1E2530; border: 1px solid #1E40AF; border-radius: 0.25rem; padding: 0.75rem; font-family: monospace; font-size: 0.875rem; max-height: 30vh; overflow: auto;",
+            div { style: "color: #60A5FA; font-weight: bold; margin-bottom: 0.5rem;",
+                if count == 1 {
+                    "1 event handler needs porting"
+                } else {
+                    "{count} event handlers need porting"
+                }
+            }
+            for handler in handlers {
+                div { style: "display: flex; gap: 0.75rem; padding: 0.15rem 0;",
+                    span { style: "color: #9CA3AF; min-width: 4rem;", "{location(&handler)}" }
+                    span { style: "color: #C084FC;", "{target(&handler)}" }
+                    span { style: "color: #E5E7EB; white-space: pre-wrap;", "{handler.code}" }
+                }
+            }
+        }
+    }
+}
+
+fn location(handler: &HandlerStub) -> String {
+    handler
+        .span
+        .map(|span| span.start.to_string())
+        .unwrap_or_default()
+}
+
+fn target(handler: &HandlerStub) -> String {
+    format!("<{}> {}", handler.element, handler.event)
+}
@@ -0,0 +1,154 @@
+//! Best-effort translation of small JavaScript expressions into Rust.
+//!
+//! Only expressions made of identifiers, literals, member access, calls and the usual operators are
+//! translated; anything else (arrow functions, object literals, assignments, ...) returns `None`
+//! so the caller can fall back to a placeholder.
+
+/// Snake-cases a JavaScript identifier, e.g. `userName` -> `user_name`.
+pub(crate) fn snake_case(ident: &str) -> String {
+    let mut out = String::with_capacity(ident.len() + 4);
+    let mut previous_lower = false;
+    for c in ident.chars() {
+        if c.is_ascii_uppercase() {
+            if previous_lower {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+            previous_lower = false;
+        } else {
+            out.push(if c == '$' { '_' } else { c });
+            previous_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    out
+}
+
+/// Converts a camelCase name to kebab-case, e.g. `marginTop` -> `margin-top`.
+pub(crate) fn kebab_case(ident: &str) -> String {
+    snake_case(ident).replace('_', "-")
+}
+
+/// Translates a dotted identifier path such as `user.firstName` into `user.first_name`.
+///
+/// Returns `None` for anything other than a plain path.
+pub(crate) fn path_to_rust(js: &str) -> Option<String> {
+    let js = js.trim();
+    let segments: Vec<&str> = js.split('.').collect();
+    if segments.iter().any(|segment| !is_identifier(segment)) {
+        return None;
+    }
+
+    Some(
+        segments
+            .iter()
+            .enumerate()
+            .map(|(i, segment)| match *segment {
+                "length" if i > 0 => "len()".to_string(),
+                segment => snake_case(segment),
+            })
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+fn is_identifier(segment: &str) -> bool {
+    let mut chars = segment.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+}
+
+/// Translates a simple JavaScript expression into Rust, or returns `None` if it uses anything
+/// beyond identifiers, literals, member access, calls, indexing and operators.
+pub(crate) fn expr_to_rust(js: &str) -> Option<String> {
+    let mut out = String::new();
+    let mut chars = js.trim().char_indices().peekable();
+    let mut after_dot = false;
+
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if !out.ends_with(' ') {
+                    out.push(' ');
+                }
+            }
+            c if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let mut end = start + c.len_utf8();
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_' || c == '$') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let ident = &js.trim()[start..end];
+                match ident {
+                    "length" if after_dot => out.push_str("len()"),
+                    _ if after_dot => out.push_str(&snake_case(ident)),
+                    "true" | "false" => out.push_str(ident),
+                    "null" | "undefined" | "this" | "new" | "typeof" | "function" | "await"
+                    | "in" | "of" | "instanceof" | "void" | "delete" => return None,
+                    _ => out.push_str(&snake_case(ident)),
+                }
+                after_dot = false;
+                continue;
+            }
+            c if c.is_ascii_digit() => {
+                out.push(c);
+                while let Some(&(_, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '.' || c == '_') {
+                        break;
+                    }
+                    out.push(c);
+                    chars.next();
+                }
+            }
+            '"' | '\'' => {
+                let mut literal = String::new();
+                loop {
+                    match chars.next()?.1 {
+                        '\\' => literal.push(chars.next()?.1),
+                        q if q == c => break,
+                        other => literal.push(other),
+                    }
+                }
+                out.push_str(&format!("{literal:?}"));
+            }
+            '=' | '!' => {
+                // `===` and `!==` become `==` and `!=`; a lone `=` is an assignment
+                if chars.next_if(|&(_, next)| next == '=').is_some() {
+                    chars.next_if(|&(_, next)| next == '=');
+                    out.push(c);
+                    out.push('=');
+                } else if c == '!' {
+                    out.push('!');
+                } else {
+                    return None;
+                }
+            }
+            '&' | '|' => {
+                chars.next_if(|&(_, next)| next == c)?;
+                out.push(c);
+                out.push(c);
+            }
+            '<' | '>' => {
+                out.push(c);
+                if chars.next_if(|&(_, next)| next == '=').is_some() {
+                    out.push('=');
+                }
+            }
+            '.' => {
+                out.push('.');
+                after_dot = true;
+                continue;
+            }
+            '+' | '-' | '*' | '/' | '%' | '(' | ')' | '[' | ']' | ',' => out.push(c),
+            _ => return None,
+        }
+        after_dot = false;
+    }
+
+    let out = out.trim();
+    (!out.is_empty()).then(|| out.to_string())
+}
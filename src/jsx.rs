@@ -0,0 +1,1124 @@
+//! JSX/TSX input mode.
+//!
+//! JSX is not HTML: attributes can hold `{expressions}`, `style` takes an object and components
+//! are capitalized tags. Instead of going through `html_parser` and rosetta, the markup is parsed
+//! here and written out as RSX source, with JavaScript translated to Rust where that is safe and
+//! replaced by `todo!()` placeholders or comments where it is not.
+
+use crate::Options;
+use crate::emit::{RsxBuilder, escape_text, quote_text, todo_expr};
+use crate::error::{ConvertError, Span};
+use crate::handlers::{Conversion, HandlerStub};
+use crate::js::{expr_to_rust, kebab_case, path_to_rust, snake_case};
+use crate::style::{join_declarations, style_attribute};
+use crate::whitespace::is_void_element;
+use dioxus_html::{map_html_attribute_to_rsx, map_html_element_to_rsx};
+
+/// React prop names that differ from their HTML attribute.
+const REACT_ATTRIBUTES: [(&str, &str); 8] = [
+    ("className", "class"),
+    ("htmlFor", "for"),
+    ("defaultValue", "initial_value"),
+    ("defaultChecked", "initial_checked"),
+    ("acceptCharset", "accept-charset"),
+    ("httpEquiv", "http-equiv"),
+    ("xlinkHref", "href"),
+    ("dangerouslySetInnerHTML", "dangerous_inner_html"),
+];
+
+/// CSS properties whose numeric values React leaves without a `px` suffix.
+const UNITLESS_PROPERTIES: [&str; 14] = [
+    "opacity",
+    "z-index",
+    "flex",
+    "flex-grow",
+    "flex-shrink",
+    "font-weight",
+    "line-height",
+    "order",
+    "zoom",
+    "grid-row",
+    "grid-column",
+    "tab-size",
+    "orphans",
+    "widows",
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsxNode {
+    Element(JsxElement),
+    Fragment(Vec<JsxNode>),
+    Text(String),
+    Expr(String),
+    Comment(String),
+    /// A loop over `iter`, both already translated to Rust. Produced by front ends that have
+    /// control flow in the markup, like Vue's `v-for`.
+    For {
+        pattern: String,
+        iter: String,
+        body: Vec<JsxNode>,
+    },
+    /// An `if` chain with conditions already translated to Rust; `None` for the final `else`.
+    If(Vec<(Option<String>, Vec<JsxNode>)>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct JsxElement {
+    pub name: String,
+    pub attributes: Vec<JsxAttribute>,
+    pub children: Vec<JsxNode>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsxAttribute {
+    Named { name: String, value: JsxValue },
+    Spread(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsxValue {
+    /// A bare attribute like `disabled`.
+    True,
+    Str(String),
+    Expr(String),
+}
+
+/// Converts JSX or TSX markup to formatted RSX.
+///
+/// Any JavaScript around the markup (a `return (...)` or a whole function component) is skipped;
+/// conversion starts at the first tag and covers it and any sibling tags that follow.
+pub(crate) fn convert_jsx(source: &str, options: &Options) -> Result<Conversion, ConvertError> {
+    write_nodes(&parse(source)?, options)
+}
+
+/// Writes parsed markup out as formatted RSX.
+pub(crate) fn write_nodes(
+    nodes: &[JsxNode],
+    options: &Options,
+) -> Result<Conversion, ConvertError> {
+    let mut writer = Writer {
+        out: RsxBuilder::default(),
+        options,
+        handlers: Vec::new(),
+    };
+    writer.write_children(nodes);
+    Ok(Conversion {
+        rsx: writer.out.finish()?,
+        handlers: writer.handlers,
+        components: Vec::new(),
+        assets: Vec::new(),
+        component: None,
+        format: options.format.clone(),
+    })
+}
+
+fn parse(source: &str) -> Result<Vec<JsxNode>, ConvertError> {
+    let start = find_markup_start(source).ok_or_else(|| ConvertError::Parse {
+        message: "no JSX element found".to_string(),
+        span: None,
+    })?;
+
+    let mut parser = Parser {
+        source,
+        pos: start,
+        html: false,
+    };
+    let mut nodes = Vec::new();
+    loop {
+        nodes.push(parser.element()?);
+        parser.skip_whitespace();
+        if !parser.at_tag_start() {
+            break;
+        }
+    }
+    Ok(nodes)
+}
+
+/// Parses HTML-flavoured markup with JSX-style `{expressions}`, as found in Vue and Svelte
+/// templates: void elements like `<br>` need no closing tag, and text and expressions may appear
+/// between the top-level elements.
+pub(crate) fn parse_html(source: &str) -> Result<Vec<JsxNode>, ConvertError> {
+    let mut parser = Parser {
+        source,
+        pos: 0,
+        html: true,
+    };
+    let nodes = parser.children()?;
+    if !parser.rest().is_empty() {
+        return Err(parser.error("unexpected closing tag"));
+    }
+    Ok(nodes)
+}
+
+/// Finds the first `<` that opens a tag or fragment.
+///
+/// A `<` right after an identifier or `.` starts TypeScript type arguments, as in
+/// `useState<string>("")` or `React.FC<Props>`, not markup.
+fn find_markup_start(source: &str) -> Option<usize> {
+    source.match_indices('<').map(|(i, _)| i).find(|&i| {
+        let type_arguments = source[..i]
+            .chars()
+            .next_back()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '$' | '.'));
+        !type_arguments
+            && source[i + 1..]
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '>')
+    })
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    pos: usize,
+    /// Accept HTML void elements without a closing tag, and end the top level at the end of the
+    /// input instead of at a closing tag.
+    html: bool,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.source[self.pos..]
+    }
+
+    fn error(&self, message: impl Into<String>) -> ConvertError {
+        ConvertError::Parse {
+            message: message.into(),
+            span: Some(Span::from_offsets(self.source, self.pos, self.pos + 1)),
+        }
+    }
+
+    fn eat(&mut self, text: &str) -> bool {
+        if self.rest().starts_with(text) {
+            self.pos += text.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, text: &str) -> Result<(), ConvertError> {
+        if self.eat(text) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{text}`")))
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        let trimmed = self.rest().trim_start();
+        self.pos = self.source.len() - trimmed.len();
+    }
+
+    fn at_tag_start(&self) -> bool {
+        let rest = self.rest();
+        rest.starts_with('<') && !rest.starts_with("</")
+    }
+
+    /// Takes characters while `f` holds.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        let len = self.rest().find(|c| !f(c)).unwrap_or(self.rest().len());
+        self.pos += len;
+        &self.source[start..self.pos]
+    }
+
+    /// Parses `<name ...>children</name>`, `<name ... />` or a `<>...</>` fragment.
+    fn element(&mut self) -> Result<JsxNode, ConvertError> {
+        self.expect("<")?;
+
+        if self.eat(">") {
+            let children = self.children()?;
+            self.expect("</")?;
+            self.skip_whitespace();
+            self.expect(">")?;
+            return Ok(JsxNode::Fragment(children));
+        }
+
+        let name = self
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-' | '$'))
+            .to_string();
+        if name.is_empty() {
+            return Err(self.error("expected a tag name"));
+        }
+        self.skip_type_arguments();
+
+        let mut attributes = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.eat("/>") {
+                return Ok(JsxNode::Element(JsxElement {
+                    name,
+                    attributes,
+                    children: Vec::new(),
+                }));
+            }
+            if self.eat(">") {
+                if self.html && is_void_element(&name) {
+                    return Ok(JsxNode::Element(JsxElement {
+                        name,
+                        attributes,
+                        children: Vec::new(),
+                    }));
+                }
+                break;
+            }
+            if self.rest().is_empty() {
+                return Err(self.error(format!("unclosed tag `<{name}>`")));
+            }
+            attributes.push(self.attribute()?);
+        }
+
+        let children = self.children()?;
+        if self.rest().is_empty() {
+            return Err(self.error(format!("missing closing tag for `<{name}>`")));
+        }
+        self.expect("</")?;
+        self.skip_whitespace();
+        let closing = self
+            .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':' | '-' | '$'))
+            .to_string();
+        if closing != name {
+            return Err(self.error(format!("expected `</{name}>`, found `</{closing}>`")));
+        }
+        self.skip_whitespace();
+        self.expect(">")?;
+
+        Ok(JsxNode::Element(JsxElement {
+            name,
+            attributes,
+            children,
+        }))
+    }
+
+    /// Skips TSX type arguments such as `<Select<Option> />`.
+    fn skip_type_arguments(&mut self) {
+        if !self.rest().starts_with('<') {
+            return;
+        }
+        let mut depth = 0;
+        for (i, c) in self.rest().char_indices() {
+            match c {
+                '<' => depth += 1,
+                '>' => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                self.pos += i + 1;
+                return;
+            }
+        }
+    }
+
+    fn attribute(&mut self) -> Result<JsxAttribute, ConvertError> {
+        if self.rest().starts_with('{') {
+            let expr = self.braced()?;
+            let expr = expr.trim();
+            return match expr.strip_prefix("...") {
+                Some(spread) => Ok(JsxAttribute::Spread(spread.trim().to_string())),
+                // Svelte's shorthand `{value}` for `value={value}`
+                None if self.html && path_to_rust(expr).is_some() && !expr.contains('.') => {
+                    Ok(JsxAttribute::Named {
+                        name: expr.to_string(),
+                        value: JsxValue::Expr(expr.to_string()),
+                    })
+                }
+                None => Err(self.error("expected an attribute")),
+            };
+        }
+
+        let name = self
+            .take_while(|c| !c.is_whitespace() && !matches!(c, '=' | '>' | '/' | '{'))
+            .to_string();
+        if name.is_empty() {
+            return Err(self.error("expected an attribute"));
+        }
+
+        self.skip_whitespace();
+        if !self.eat("=") {
+            return Ok(JsxAttribute::Named {
+                name,
+                value: JsxValue::True,
+            });
+        }
+        self.skip_whitespace();
+
+        let value = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => {
+                self.pos += 1;
+                let value = self.take_while(|c| c != quote).to_string();
+                self.expect(&quote.to_string())?;
+                JsxValue::Str(decode_entities(&value))
+            }
+            Some('{') => JsxValue::Expr(self.braced()?),
+            _ => return Err(self.error(format!("expected a value for `{name}`"))),
+        };
+
+        Ok(JsxAttribute::Named { name, value })
+    }
+
+    /// Parses child nodes up to the next closing tag.
+    fn children(&mut self) -> Result<Vec<JsxNode>, ConvertError> {
+        let mut children = Vec::new();
+        loop {
+            let rest = self.rest();
+            if rest.is_empty() && self.html {
+                return Ok(children);
+            }
+            if rest.is_empty() {
+                return Err(self.error("unexpected end of input, missing closing tag"));
+            }
+            if rest.starts_with("</") {
+                return Ok(children);
+            }
+
+            if let Some(comment) = rest.strip_prefix("<!--") {
+                let end = comment
+                    .find("-->")
+                    .ok_or_else(|| self.error("unclosed comment"))?;
+                children.push(JsxNode::Comment(comment[..end].trim().to_string()));
+                self.pos += "<!--".len() + end + "-->".len();
+            } else if rest.starts_with('<') {
+                children.push(self.element()?);
+            } else if rest.starts_with('{') {
+                let expr = self.braced()?;
+                let trimmed = expr.trim();
+                if let Some(comment) = trimmed
+                    .strip_prefix("/*")
+                    .and_then(|c| c.strip_suffix("*/"))
+                {
+                    children.push(JsxNode::Comment(comment.trim().to_string()));
+                } else if !trimmed.is_empty() {
+                    children.push(JsxNode::Expr(trimmed.to_string()));
+                }
+            } else {
+                let text = self.take_while(|c| c != '<' && c != '{');
+                let text = jsx_text(text);
+                if !text.is_empty() {
+                    children.push(JsxNode::Text(decode_entities(&text)));
+                }
+            }
+        }
+    }
+
+    /// Consumes a `{...}` expression and returns its contents, skipping over nested braces, strings
+    /// and template literals.
+    fn braced(&mut self) -> Result<String, ConvertError> {
+        let start = self.pos;
+        let end = matching_brace(self.source, start).ok_or_else(|| self.error("unclosed `{`"))?;
+        self.pos = end + 1;
+        Ok(self.source[start + 1..end].to_string())
+    }
+}
+
+/// Returns the offset of the `}` matching the `{` at `open`.
+pub(crate) fn matching_brace(source: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut chars = source[open..].char_indices().map(|(i, c)| (i + open, c));
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            '"' | '\'' | '`' => {
+                // Skip the string, honouring escapes
+                while let Some((_, next)) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        next if next == c => break,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Applies JSX whitespace rules: lines are trimmed, blank lines dropped and the rest joined with a
+/// single space. Text on a single line is kept as-is.
+fn jsx_text(text: &str) -> String {
+    if !text.contains('\n') {
+        return text.to_string();
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    let last = lines.len() - 1;
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| match (i == 0, i == last) {
+            (true, _) => line.trim_end(),
+            (_, true) => line.trim_start(),
+            _ => line.trim(),
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Decodes named and numeric HTML entities, the same way rosetta decodes HTML text. Text with
+/// malformed entities is kept as written.
+pub(crate) fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    use htmlentity::entity::ICodedDataTrait;
+    htmlentity::entity::decode(text.as_bytes())
+        .to_string()
+        .unwrap_or_else(|_| text.to_string())
+}
+
+fn is_component(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase()) || name.contains('.')
+}
+
+/// Returns `{expr}` ready to splice into a formatted string, for expressions that are string
+/// literals or translate to Rust without quotes or braces.
+fn inline_expr(expr: &str) -> Option<String> {
+    // `children` is an `Element`, which renders as a node rather than as text
+    if expr.trim() == "children" || expr.trim().ends_with(".children") {
+        return None;
+    }
+    if let Some(literal) = string_literal(expr) {
+        return Some(escape_text(&literal));
+    }
+    if let Some(template) = template_literal(expr) {
+        return template
+            .strip_prefix('"')
+            .and_then(|template| template.strip_suffix('"'))
+            .map(str::to_string);
+    }
+    if find_markup_start(expr).is_some() {
+        return None;
+    }
+    let rust = expr_to_rust(expr)?;
+    (!rust.contains(['"', '{', '}'])).then(|| format!("{{{rust}}}"))
+}
+
+/// Returns the contents of a `"..."` or `'...'` literal without escapes.
+pub(crate) fn string_literal(expr: &str) -> Option<String> {
+    let expr = expr.trim();
+    let quote = expr.chars().next().filter(|c| matches!(c, '"' | '\''))?;
+    let inner = expr.strip_prefix(quote)?.strip_suffix(quote)?;
+    (!inner.contains(quote) && !inner.contains('\\')).then(|| inner.to_string())
+}
+
+/// Translates a template literal like `` `btn ${variant}` `` into a formatted string.
+fn template_literal(expr: &str) -> Option<String> {
+    let inner = expr.trim().strip_prefix('`')?.strip_suffix('`')?;
+    let mut out = String::new();
+    let mut rest = inner;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&escape_text(&rest[..start]));
+        let end = rest[start..].find('}')? + start;
+        out.push('{');
+        out.push_str(&expr_to_rust(&rest[start + 2..end])?);
+        out.push('}');
+        rest = &rest[end + 1..];
+    }
+    out.push_str(&escape_text(rest));
+    Some(format!("\"{out}\""))
+}
+
+/// Splits `cond && <X/>` at the `&&` that introduces the markup.
+fn split_guard(expr: &str) -> Option<(&str, &str)> {
+    expr.match_indices("&&")
+        .map(|(i, _)| (&expr[..i], &expr[i + 2..]))
+        .find(|(_, body)| strip_parens(body.trim()).starts_with('<'))
+}
+
+/// Parses JSX nested inside an expression.
+fn parse_markup(source: &str) -> Result<Vec<JsxNode>, ConvertError> {
+    let source = strip_parens(source.trim());
+    if !source.starts_with('<') {
+        return Err(ConvertError::Parse {
+            message: "expected JSX".to_string(),
+            span: None,
+        });
+    }
+    let nodes = parse(source)?;
+    Ok(nodes)
+}
+
+/// Parses one side of a ternary; `null`, `undefined` and `false` render nothing.
+fn parse_branch(source: &str) -> Result<Vec<JsxNode>, ConvertError> {
+    match strip_parens(source.trim()) {
+        "null" | "undefined" | "false" | "''" | "\"\"" => Ok(Vec::new()),
+        source => parse_markup(source),
+    }
+}
+
+pub(crate) fn strip_parens(mut expr: &str) -> &str {
+    while let Some(inner) = expr.strip_prefix('(').and_then(|e| e.strip_suffix(')')) {
+        expr = inner.trim();
+    }
+    expr
+}
+
+/// Splits `cond ? a : b` at the top level.
+fn split_ternary(expr: &str) -> Option<(&str, &str, &str)> {
+    let question = top_level_find(expr, '?')?;
+    let colon = top_level_find(&expr[question + 1..], ':')? + question + 1;
+    Some((
+        &expr[..question],
+        &expr[question + 1..colon],
+        &expr[colon + 1..],
+    ))
+}
+
+/// Finds `target` outside of brackets, strings and JSX tags.
+pub(crate) fn top_level_find(expr: &str, target: char) -> Option<usize> {
+    let mut depth = 0i32;
+    let mut quote = None;
+    for (i, c) in expr.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'' | '`') => quote = Some(c),
+            (None, '(' | '[' | '{' | '<') => depth += 1,
+            (None, ')' | ']' | '}' | '>') => depth -= 1,
+            (None, c) if c == target && depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Recognizes `array.map(item => <X/>)` and `array.map((item, index) => (<X/>))`.
+///
+/// Returns the Rust iterator expression, the loop pattern and the callback body.
+fn map_callback(expr: &str) -> Option<(String, String, &str)> {
+    let (array, callback) = expr.split_once(".map(")?;
+    let array = path_to_rust(array)?;
+    let callback = callback.strip_suffix(')')?;
+    let (params, body) = callback.split_once("=>")?;
+    let params: Vec<String> = strip_parens(params.trim())
+        .split(',')
+        .map(|param| path_to_rust(param.trim()))
+        .collect::<Option<_>>()?;
+
+    let body = body.trim();
+    let body = body
+        .strip_prefix('{')
+        .and_then(|b| b.strip_suffix('}'))
+        .map(|b| {
+            b.trim()
+                .trim_start_matches("return")
+                .trim()
+                .trim_end_matches(';')
+        })
+        .unwrap_or(body);
+
+    match params.as_slice() {
+        [item] => Some((format!("{array}.iter()"), item.clone(), body)),
+        [item, index] => Some((
+            format!("{array}.iter().enumerate()"),
+            format!("({index}, {item})"),
+            body,
+        )),
+        _ => None,
+    }
+}
+
+fn jsx_value_source(value: &JsxValue) -> String {
+    match value {
+        JsxValue::True => "true".to_string(),
+        JsxValue::Str(text) | JsxValue::Expr(text) => text.clone(),
+    }
+}
+
+/// Returns the lowercased Dioxus event name for `onClick`-style props.
+fn event_name(name: &str) -> Option<String> {
+    let rest = name.strip_prefix("on")?;
+    rest.starts_with(|c: char| c.is_ascii_uppercase())
+        .then(|| name.to_ascii_lowercase())
+}
+
+/// Maps a JSX attribute name to an RSX attribute name, quoting names Dioxus doesn't know.
+fn attribute_name(name: &str) -> String {
+    let html = REACT_ATTRIBUTES
+        .iter()
+        .find(|(react, _)| *react == name)
+        .map_or_else(|| name.to_string(), |(_, html)| html.to_string());
+
+    if html == "key" || html.contains('_') {
+        return html;
+    }
+    if let Some(rsx) = map_html_attribute_to_rsx(&html) {
+        return rsx.to_string();
+    }
+
+    let lowered = if html.contains('-') {
+        html.clone()
+    } else {
+        html.to_ascii_lowercase()
+    };
+    if let Some(rsx) = map_html_attribute_to_rsx(&lowered) {
+        return rsx.to_string();
+    }
+
+    // SVG props are camelCase in React but kebab-case in HTML (`strokeWidth` -> `stroke-width`)
+    if let Some(rsx) = map_html_attribute_to_rsx(&kebab_case(&html)) {
+        return rsx.to_string();
+    }
+
+    quote_text(&html)
+}
+
+fn attribute_value(value: &JsxValue) -> String {
+    match value {
+        JsxValue::True => "true".to_string(),
+        JsxValue::Str(text) => quote_text(text),
+        JsxValue::Expr(expr) => {
+            if let Some(literal) = string_literal(expr) {
+                quote_text(&literal)
+            } else if let Some(template) = template_literal(expr) {
+                template
+            } else if let Some(rust) = expr_to_rust(expr) {
+                rust
+            } else if let Some((cond, then, otherwise)) = split_ternary(expr)
+                && let Some(cond) = expr_to_rust(cond)
+            {
+                // `active ? 'on' : 'off'` becomes `if active { "on" } else { "off" }`
+                let then = attribute_value(&JsxValue::Expr(then.to_string()));
+                let otherwise = attribute_value(&JsxValue::Expr(otherwise.to_string()));
+                format!("if {cond} {{ {then} }} else {{ {otherwise} }}")
+            } else {
+                todo_expr(expr)
+            }
+        }
+    }
+}
+
+/// `dangerouslySetInnerHTML={{ __html: markup }}` becomes `dangerous_inner_html: markup`.
+fn inner_html(expr: &str) -> String {
+    let inner = expr.trim();
+    let value = inner
+        .strip_prefix('{')
+        .and_then(|i| i.strip_suffix('}'))
+        .and_then(|i| i.trim().strip_prefix("__html"))
+        .and_then(|i| i.trim().strip_prefix(':'));
+    match value {
+        Some(value) => attribute_value(&JsxValue::Expr(value.trim().to_string())),
+        None => todo_expr(expr),
+    }
+}
+
+/// Parses a `{ color: 'red', marginTop: 4 }` style object into CSS declarations.
+fn style_object(expr: &str) -> Option<Vec<(String, String)>> {
+    let inner = expr.trim().strip_prefix('{')?.strip_suffix('}')?;
+    let mut declarations = Vec::new();
+
+    for entry in split_top_level(inner, ',') {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            continue;
+        }
+        let colon = top_level_find(entry, ':')?;
+        let key = entry[..colon]
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'');
+        let property = if key.starts_with("--") {
+            key.to_string()
+        } else {
+            kebab_case(key)
+        };
+
+        let raw = entry[colon + 1..].trim();
+        let value = if let Some(literal) = string_literal(raw) {
+            escape_text(&literal)
+        } else if raw.parse::<f64>().is_ok() {
+            if raw == "0"
+                || property.starts_with("--")
+                || UNITLESS_PROPERTIES.contains(&property.as_str())
+            {
+                raw.to_string()
+            } else {
+                format!("{raw}px")
+            }
+        } else if let Some(template) = template_literal(raw) {
+            template.trim_matches('"').to_string()
+        } else if let Some(rust) = expr_to_rust(raw).filter(|r| !r.contains(['"', '{', '}'])) {
+            format!("{{{rust}}}")
+        } else {
+            return None;
+        };
+
+        declarations.push((property, value));
+    }
+
+    Some(declarations)
+}
+
+pub(crate) fn split_top_level(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = text;
+    while let Some(i) = top_level_find(rest, separator) {
+        parts.push(&rest[..i]);
+        rest = &rest[i + 1..];
+    }
+    parts.push(rest);
+    parts
+}
+
+/// Writes parsed JSX out as RSX source.
+struct Writer<'a> {
+    out: RsxBuilder,
+    options: &'a Options,
+    /// Handlers written as stubs so far.
+    handlers: Vec<HandlerStub>,
+}
+
+impl Writer<'_> {
+    fn write_children(&mut self, children: &[JsxNode]) {
+        // Text and simple interpolations are merged into one formatted string
+        let mut text = String::new();
+
+        for child in children {
+            match child {
+                JsxNode::Text(content) => text.push_str(&escape_text(content)),
+                JsxNode::Expr(expr) if inline_expr(expr).is_some() => {
+                    text.push_str(&inline_expr(expr).unwrap_or_default())
+                }
+                other => {
+                    self.flush_text(&mut text);
+                    self.write_node(other);
+                }
+            }
+        }
+
+        self.flush_text(&mut text);
+    }
+
+    fn flush_text(&mut self, text: &mut String) {
+        if !text.trim().is_empty() {
+            self.out.push(&format!("\"{text}\"\n"));
+        }
+        text.clear();
+    }
+
+    fn write_node(&mut self, node: &JsxNode) {
+        match node {
+            JsxNode::Element(el) if is_component(&el.name) => self.write_component(el),
+            JsxNode::Element(el) => self.write_element(el),
+            JsxNode::Fragment(children) => self.write_children(children),
+            JsxNode::Text(text) => self.out.push(&format!("{}\n", quote_text(text))),
+            JsxNode::Comment(comment) => self.out.node_comment(comment),
+            JsxNode::Expr(expr) => self.write_expr_child(expr),
+            JsxNode::For {
+                pattern,
+                iter,
+                body,
+            } => {
+                self.out.push(&format!("for {pattern} in {iter} {{\n"));
+                self.write_children(body);
+                self.out.push("}\n");
+            }
+            JsxNode::If(branches) => {
+                for (i, (cond, body)) in branches.iter().enumerate() {
+                    match (i, cond) {
+                        (0, Some(cond)) => self.out.push(&format!("if {cond} {{\n")),
+                        (_, Some(cond)) => self.out.push(&format!(" else if {cond} {{\n")),
+                        (_, None) => self.out.push(" else {\n"),
+                    }
+                    self.write_children(body);
+                    self.out.push("}");
+                }
+                self.out.push("\n");
+            }
+        }
+    }
+
+    /// Writes a `{...}` child, turning `cond && <X/>`, `cond ? <A/> : <B/>` and
+    /// `items.map(item => <X/>)` into RSX control flow.
+    fn write_expr_child(&mut self, expr: &str) {
+        let expr = strip_parens(expr.trim());
+
+        if let Some((array, pattern, body)) = map_callback(expr)
+            && let Ok(nodes) = parse_markup(body)
+        {
+            self.out.push(&format!("for {pattern} in {array} {{\n"));
+            self.write_children(&nodes);
+            self.out.push("}\n");
+            return;
+        }
+
+        if let Some((cond, then, otherwise)) = split_ternary(expr)
+            && let Some(cond) = expr_to_rust(cond)
+            && let Ok(then) = parse_branch(then)
+            && let Ok(otherwise) = parse_branch(otherwise)
+        {
+            self.out.push(&format!("if {cond} {{\n"));
+            self.write_children(&then);
+            self.out.push("}");
+            if !otherwise.is_empty() {
+                self.out.push(" else {\n");
+                self.write_children(&otherwise);
+                self.out.push("}");
+            }
+            self.out.push("\n");
+            return;
+        }
+
+        if let Some((cond, body)) = split_guard(expr)
+            && let Some(cond) = expr_to_rust(cond)
+            && let Ok(nodes) = parse_markup(body)
+        {
+            self.out.push(&format!("if {cond} {{\n"));
+            self.write_children(&nodes);
+            self.out.push("}\n");
+            return;
+        }
+
+        match expr_to_rust(expr).filter(|_| find_markup_start(expr).is_none()) {
+            Some(rust) => self.out.push(&format!("{{{rust}}}\n")),
+            None => self.out.push(&format!("{{{}}}\n", todo_expr(expr))),
+        }
+    }
+
+    fn write_element(&mut self, el: &JsxElement) {
+        let name = match map_html_element_to_rsx(&el.name) {
+            Some(name) => name.to_string(),
+            None if el.name.contains('-') => el.name.clone(),
+            None => snake_case(&el.name),
+        };
+
+        self.out.push(&format!("{name} {{ "));
+        self.write_attributes(&el.attributes, &el.name, Some(&name));
+        self.write_children(&el.children);
+        self.out.push("}\n");
+    }
+
+    fn write_component(&mut self, el: &JsxElement) {
+        self.out
+            .push(&format!("{} {{ ", el.name.replace('.', "::")));
+        self.write_attributes(&el.attributes, &el.name, None);
+        self.write_children(&el.children);
+        self.out.push("}\n");
+    }
+
+    /// Writes attributes for an element, or props for a component when `element` is `None`. `tag`
+    /// is the name as written in the JSX.
+    fn write_attributes(&mut self, attributes: &[JsxAttribute], tag: &str, element: Option<&str>) {
+        let component = element.is_none();
+        let mut spreads = Vec::new();
+
+        for attribute in attributes {
+            let (name, value) = match attribute {
+                JsxAttribute::Named { name, value } => (name.as_str(), value),
+                JsxAttribute::Spread(expr) => {
+                    spreads.push(expr);
+                    continue;
+                }
+            };
+
+            // React-only props with no Dioxus equivalent
+            if name == "ref" {
+                self.out.comment(&format!(
+                    "ref={{{}}} has no RSX equivalent",
+                    jsx_value_source(value)
+                ));
+                continue;
+            }
+
+            if let Some(event) = event_name(name) {
+                let event = if component { snake_case(name) } else { event };
+                self.out.push(&format!("{event}: "));
+                self.write_handler(value, &event, tag);
+                self.out.push(",\n");
+                continue;
+            }
+
+            if name == "style"
+                && let JsxValue::Expr(expr) = value
+                && let Some(declarations) = style_object(expr)
+            {
+                self.write_style(element, &declarations);
+                continue;
+            }
+
+            let name = if component {
+                snake_case(name)
+            } else {
+                attribute_name(name)
+            };
+
+            let value = match (name.as_str(), value) {
+                ("dangerous_inner_html", JsxValue::Expr(expr)) => inner_html(expr),
+                // Keys must be formatted strings in RSX
+                ("key", JsxValue::Expr(expr)) => match inline_expr(expr) {
+                    Some(key) => format!("\"{key}\""),
+                    None => todo_expr(expr),
+                },
+                (_, value) => attribute_value(value),
+            };
+            self.out.push(&format!("{name}: {value},\n"));
+        }
+
+        for spread in spreads {
+            match path_to_rust(spread) {
+                Some(path) => self.out.push(&format!("..{path},\n")),
+                None => self.out.comment(&format!("{{...{spread}}}")),
+            }
+        }
+    }
+
+    /// Writes a handler: `onClick={save}` becomes `move |evt| save(evt)`, anything else becomes a stub
+    /// with the original JavaScript in a comment.
+    fn write_handler(&mut self, value: &JsxValue, event: &str, tag: &str) {
+        let code = match value {
+            JsxValue::Expr(expr) => match path_to_rust(expr) {
+                Some(path) => {
+                    self.out.push(&format!("move |evt| {path}(evt)"));
+                    return;
+                }
+                None => expr.trim().to_string(),
+            },
+            other => jsx_value_source(other),
+        };
+
+        self.out.handler_stub(&code);
+        self.handlers.push(HandlerStub {
+            event: event.to_string(),
+            element: tag.to_string(),
+            code,
+            span: None,
+        });
+    }
+
+    /// Writes style declarations, as individual RSX attributes when `split_styles` is on and the
+    /// element has a typed attribute for the property, and as a `style` string otherwise.
+    fn write_style(&mut self, element: Option<&str>, declarations: &[(String, String)]) {
+        let mut fallback = Vec::new();
+        for declaration in declarations {
+            let (property, value) = declaration;
+            let name = element
+                .filter(|_| self.options.split_styles)
+                .and_then(|element| style_attribute(element, property, value));
+            match name {
+                Some(name) => self.out.push(&format!("{name}: \"{value}\",\n")),
+                None => fallback.push(declaration),
+            }
+        }
+        if !fallback.is_empty() {
+            self.out
+                .push(&format!("style: \"{}\",\n", join_declarations(fallback)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InputFormat, Options, convert};
+
+    fn convert_jsx(jsx: &str) -> String {
+        let options = Options {
+            input: InputFormat::Jsx,
+            ..Options::default()
+        };
+        convert(jsx, &options).unwrap()
+    }
+
+    #[test]
+    fn expressions_are_interpolated() {
+        assert_eq!(
+            convert_jsx("<p className=\"greeting\">Hello {name}, {`you have ${count} items`}</p>"),
+            "\n    p { class: \"greeting\", \"Hello {name}, you have {count} items\" }"
+        );
+        assert_eq!(
+            convert_jsx("<p>{items.filter(x => x.done).length}</p>"),
+            "\n    p { {todo!(\"items.filter(x => x.done).length\")} }"
+        );
+    }
+
+    #[test]
+    fn map_becomes_a_for_loop() {
+        assert_eq!(
+            convert_jsx("<ul>{items.map(item => <li key={item.id}>{item.label}</li>)}</ul>"),
+            "
+    ul {
+        for item in items.iter() {
+            li { key: \"{item.id}\", \"{item.label}\" }
+        }
+    }"
+        );
+    }
+
+    #[test]
+    fn conditionals_become_if_blocks() {
+        assert_eq!(
+            convert_jsx("<div>{open && <Modal />}{ready ? <Done /> : <Spinner />}</div>"),
+            "
+    div {
+        if open {
+            Modal {}
+        }
+        if ready {
+            Done {}
+        } else {
+            Spinner {}
+        }
+    }"
+        );
+    }
+
+    #[test]
+    fn style_objects_become_declarations() {
+        assert_eq!(
+            convert_jsx("<div style={{ marginTop: 4, opacity: 0.5, backgroundColor: 'red' }} />"),
+            "\n    div { style: \"margin-top: 4px; opacity: 0.5; background-color: red\" }"
+        );
+    }
+
+    #[test]
+    fn fragments_are_flattened() {
+        assert_eq!(
+            convert_jsx("<><h1>Title</h1><p>Body</p></>"),
+            "\n    h1 { \"Title\" }\n    p { \"Body\" }"
+        );
+    }
+
+    #[test]
+    fn comments_stay_in_place() {
+        assert_eq!(
+            convert_jsx("<div>{/* first */}<p>a</p>{/* last */}</div>"),
+            "
+    div {
+        // first
+        p { \"a\" }
+        // last
+    }"
+        );
+        assert_eq!(
+            convert_jsx("<div>{/* only */}</div>"),
+            "\n    div { /* only */ }"
+        );
+    }
+
+    #[test]
+    fn tsx_generics_are_not_markup() {
+        let tsx = r#"import React, { useState } from "react";
+
+const App: React.FC<Props> = ({ title }) => {
+  const [name, setName] = useState<string>("");
+  const items = new Map<string, Array<Item>>();
+  return (
+    <div className="app">
+      <h1>{title}</h1>
+    </div>
+  );
+};"#;
+        assert_eq!(
+            convert_jsx(tsx),
+            "\n    div { class: \"app\",\n        h1 { \"{title}\" }\n    }"
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! HTML to RSX conversion pipeline.
+//!
+//! The Dioxus UI in `main.rs` is one front end for this crate; build scripts, tests and other
+//! tools can call [`convert`] directly to get the same output.
+
+use dioxus_rsx::{AttributeValue, BodyNode, ElementName, HotLiteral, TextNode};
+use dioxus_rsx_rosetta::Dom;
+
+mod assets;
+mod batch;
+mod component;
+mod emit;
+mod error;
+mod extract;
+mod fidelity;
+mod format;
+mod handlers;
+mod js;
+mod jsx;
+mod markdown;
+mod preprocess;
+mod rules;
+mod sfc;
+mod style;
+mod svg;
+mod template;
+mod to_html;
+mod tokenizer;
+mod validate;
+mod whitespace;
+
+pub use assets::Asset;
+pub use batch::{BatchReport, convert_dir};
+pub use component::{component_module, component_name, module_name};
+pub use error::{ConvertError, Position, Span};
+pub use fidelity::{Difference, DifferenceKind, FidelityReport, check_round_trip};
+pub use format::{FormatOptions, OutputMode};
+pub use handlers::{Conversion, HandlerStub};
+pub use markdown::MarkdownComponents;
+use preprocess::Preprocessed;
+pub use preprocess::preprocess_html;
+pub use rules::{RewriteAction, RewriteRule, RulesError, parse_rules};
+pub use sfc::ComponentSignature;
+pub use to_html::rsx_to_html;
+
+/// The markup language of the input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Plain HTML, parsed with `html_parser` and converted by rosetta.
+    #[default]
+    Html,
+    /// React JSX or TSX, including `{expressions}`, style objects and components.
+    Jsx,
+    /// HTML with Jinja2 tags: `{{ expr }}`, `{% for %}`, `{% if %}` and `{# comments #}`.
+    Jinja,
+    /// HTML with Askama tags, which use Jinja's syntax with Rust expressions.
+    Askama,
+    /// HTML with Handlebars tags: `{{expr}}`, `{{#each}}`, `{{#if}}`, `{{#unless}}` and
+    /// `{{! comments }}`.
+    Handlebars,
+    /// A Vue single-file component: the `<template>` block with its directives, and the props
+    /// from `defineProps`.
+    Vue,
+    /// A Svelte component: the markup with its `{#if}` and `{#each}` blocks, and the props from
+    /// `export let`.
+    Svelte,
+    /// CommonMark with GitHub's tables, strikethrough, task lists and footnotes, rendered to HTML
+    /// and converted like HTML input.
+    Markdown,
+}
+
+impl InputFormat {
+    /// File extensions recognised for this format when converting directories.
+    pub fn extensions(self) -> &'static [&'static str] {
+        match self {
+            InputFormat::Html => &["html", "htm"],
+            InputFormat::Jsx => &["jsx", "tsx"],
+            InputFormat::Jinja => &["html", "htm", "j2", "jinja", "jinja2"],
+            InputFormat::Askama => &["html", "htm"],
+            InputFormat::Handlebars => &["hbs", "handlebars"],
+            InputFormat::Vue => &["vue"],
+            InputFormat::Svelte => &["svelte"],
+            InputFormat::Markdown => &["md", "markdown"],
+        }
+    }
+}
+
+/// Options controlling how HTML is converted to RSX.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    /// The markup language of the input.
+    pub input: InputFormat,
+    /// Run [`preprocess_html`] on the input before parsing it (React attribute renames and
+    /// whitespace normalization). Only applies to HTML input.
+    pub preprocess: bool,
+    /// Turn inline `style` strings into individual attributes like `margin_top: "4px"`.
+    /// Properties Dioxus has no attribute for stay in a `style` string.
+    pub split_styles: bool,
+    /// Attribute rewrite rules applied to HTML input before it is parsed, e.g. from
+    /// [`parse_rules`]. The first matching rule wins.
+    pub rules: Vec<RewriteRule>,
+    /// Turn inline `on*` attributes of HTML input into event handler stubs with the JavaScript in
+    /// a comment, instead of string attributes that don't compile. JSX handlers are always
+    /// converted.
+    pub handler_stubs: bool,
+    /// Replace runs of structurally identical sibling elements with invocations of a generated
+    /// `#[component]`, taking the text and attribute values that differ as props. Only applies to
+    /// HTML input.
+    pub extract_components: bool,
+    /// Replace every `svg` element of HTML input with an invocation of a generated icon component
+    /// with this name, passing the markup's `class` and `size` as props. Icons that only differ in
+    /// those share a component, and further icons get a number appended to the name.
+    pub icon_component: Option<String>,
+    /// Rewrite the `src`, `poster` and `srcset` attributes of HTML input that reference local
+    /// files or `data:` URIs to `asset!("/assets/...")` calls, listing the files in
+    /// [`Conversion::assets`].
+    pub extract_assets: bool,
+    /// Components to render the headings, code blocks and links of Markdown input with.
+    pub markdown_components: MarkdownComponents,
+    /// How the output is laid out.
+    pub format: FormatOptions,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            input: InputFormat::default(),
+            preprocess: true,
+            split_styles: false,
+            rules: Vec::new(),
+            handler_stubs: false,
+            extract_components: false,
+            icon_component: None,
+            extract_assets: false,
+            markdown_components: MarkdownComponents::default(),
+            format: FormatOptions::default(),
+        }
+    }
+}
+
+/// Converts an HTML snippet into a formatted RSX body.
+///
+/// This runs the full pipeline: optional preprocessing, parsing with `html_parser`, conversion
+/// with `dioxus-rsx-rosetta` and formatting with `dioxus-autofmt`. Errors carry spans into
+/// `html`, not into the preprocessed text.
+///
+/// Components generated by [`Options::extract_components`] and [`Options::icon_component`]
+/// follow the body.
+pub fn convert(html: &str, options: &Options) -> Result<String, ConvertError> {
+    convert_with_report(html, options).map(|conversion| conversion.output())
+}
+
+/// Like [`convert`], but keeps the body and the extracted components apart and also lists every
+/// event handler that was written as a stub and needs porting by hand.
+pub fn convert_with_report(html: &str, options: &Options) -> Result<Conversion, ConvertError> {
+    match options.input {
+        InputFormat::Html => convert_html(html, options, None),
+        InputFormat::Jsx => jsx::convert_jsx(html, options),
+        InputFormat::Jinja => template::convert_template(html, template::Dialect::Jinja, options),
+        InputFormat::Askama => template::convert_template(html, template::Dialect::Askama, options),
+        InputFormat::Handlebars => {
+            template::convert_template(html, template::Dialect::Handlebars, options)
+        }
+        InputFormat::Vue => sfc::convert_sfc(html, sfc::Framework::Vue, options),
+        InputFormat::Svelte => sfc::convert_sfc(html, sfc::Framework::Svelte, options),
+        InputFormat::Markdown => markdown::convert_markdown(html, options),
+    }
+}
+
+/// Converts HTML, or a template lowered to HTML with markers for its tags.
+fn convert_html(
+    html: &str,
+    options: &Options,
+    lowered: Option<&template::Lowered>,
+) -> Result<Conversion, ConvertError> {
+    let source = Preprocessed::for_options(html, options);
+
+    let mut dom =
+        Dom::parse(&source.text).map_err(|err| validate::parse_error(err, html, &source))?;
+    validate::check_dom(&dom, html, &source)?;
+    whitespace::restore_whitespace(&mut dom.children);
+
+    // Entities are decoded first so the `;` ending one doesn't split a style declaration
+    decode_attribute_entities(&mut dom.children);
+    if options.split_styles {
+        style::split_dom_styles(&mut dom.children);
+    }
+
+    mark_boolean_attributes(&mut dom.children);
+    let mut body = dioxus_rsx_rosetta::rsx_from_html(&dom);
+    let mut placeholders = Vec::new();
+    rules::restore_marked(&mut body.body.roots, &source.marked, &mut placeholders);
+    escape_braces(&mut body.body.roots);
+    restore_boolean_attributes(&mut body.body.roots);
+    escape_keyword_elements(&mut body.body.roots);
+    if let Some(lowered) = lowered {
+        lowered.restore_interpolations(&mut body.body.roots);
+        lowered.restore_blocks(&mut body.body.roots, &mut placeholders);
+    }
+    if options.input == InputFormat::Markdown {
+        markdown::map_components(&mut body.body.roots, &options.markdown_components);
+    }
+    let assets = if options.extract_assets {
+        assets::extract_assets(&mut body.body.roots)
+    } else {
+        Vec::new()
+    };
+    let mut components = Vec::new();
+    // Generated components must not take the name of the component the output is wrapped in
+    let mut names: Vec<String> = options.format.wrapper_name().into_iter().collect();
+    if let Some(name) = &options.icon_component {
+        components.extend(svg::extract_icons(&mut body.body.roots, name, &mut names)?);
+    }
+    if options.extract_components {
+        components.extend(extract::extract_components(
+            &mut body.body.roots,
+            &mut names,
+        )?);
+    }
+    let formatted = dioxus_autofmt::write_block_out(&body).ok_or(ConvertError::Format)?;
+    Ok(Conversion {
+        rsx: emit::fill_placeholders(formatted, &placeholders),
+        handlers: source.handlers,
+        components,
+        assets,
+        component: None,
+        format: options.format.clone(),
+    })
+}
+
+/// Decodes entities in attribute values, which rosetta only does for text.
+fn decode_attribute_entities(nodes: &mut [dioxus_rsx_rosetta::Node]) {
+    for node in nodes {
+        if let dioxus_rsx_rosetta::Node::Element(element) = node {
+            if let Some(id) = &mut element.id {
+                *id = jsx::decode_entities(id);
+            }
+            for class in &mut element.classes {
+                *class = jsx::decode_entities(class);
+            }
+            for value in element.attributes.values_mut().flatten() {
+                *value = jsx::decode_entities(value);
+            }
+            decode_attribute_entities(&mut element.children);
+        }
+    }
+}
+
+/// Attributes whose presence alone turns them on. Dioxus only sets them for `true` or `"true"`,
+/// so `disabled=""` or `checked="checked"` has to become `true`. This is the interpreter's list
+/// (`isBoolAttr` in `set_attribute.ts`).
+const BOOLEAN_ATTRIBUTES: [&str; 27] = [
+    "allowfullscreen",
+    "allowpaymentrequest",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "hidden",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+    "truespeed",
+    "webkitdirectory",
+];
+
+/// Returns true if the HTML attribute is on whenever it is present, whatever its value.
+pub(crate) fn is_boolean_attribute(name: &str) -> bool {
+    BOOLEAN_ATTRIBUTES.contains(&name.to_ascii_lowercase().as_str())
+}
+
+/// Stands in for the value of valueless and boolean attributes like `required` until rosetta is
+/// done, which would otherwise write valueless ones as `"false"`.
+const BOOLEAN_MARKER: &str = "__html2rsx_boolean__";
+
+/// Gives valueless and boolean attributes a marker value rosetta keeps.
+fn mark_boolean_attributes(nodes: &mut [dioxus_rsx_rosetta::Node]) {
+    for node in nodes {
+        if let dioxus_rsx_rosetta::Node::Element(element) = node {
+            for (name, value) in element.attributes.iter_mut() {
+                if value.is_none() || is_boolean_attribute(name) {
+                    *value = Some(BOOLEAN_MARKER.to_string());
+                }
+            }
+            mark_boolean_attributes(&mut element.children);
+        }
+    }
+}
+
+/// Turns the marked values of valueless and boolean attributes into `true`.
+fn restore_boolean_attributes(nodes: &mut [BodyNode]) {
+    for node in nodes {
+        if let BodyNode::Element(element) = node {
+            for attribute in &mut element.raw_attributes {
+                if let AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) = &attribute.value
+                    && value.formatted_input.source.value() == BOOLEAN_MARKER
+                {
+                    attribute.value = AttributeValue::AttrLiteral(HotLiteral::Bool(
+                        syn::LitBool::new(true, proc_macro2::Span::call_site()),
+                    ));
+                }
+            }
+            restore_boolean_attributes(&mut element.children);
+        }
+    }
+}
+
+/// Writes elements named after Rust keywords, like SVG's `use`, as raw identifiers such as
+/// `r#use`, which rosetta writes as plain keywords that don't compile.
+fn escape_keyword_elements(nodes: &mut [BodyNode]) {
+    for node in nodes {
+        if let BodyNode::Element(element) = node {
+            if let ElementName::Ident(ident) = &mut element.name {
+                let name = ident.to_string();
+                // `self`, `super` and `crate` can't be raw identifiers, nor HTML elements
+                if component::RUST_KEYWORDS.contains(&name.as_str())
+                    && !matches!(name.as_str(), "self" | "super" | "crate")
+                {
+                    *ident = proc_macro2::Ident::new_raw(&name, ident.span());
+                }
+            }
+            escape_keyword_elements(&mut element.children);
+        }
+    }
+}
+
+/// Doubles the braces in rosetta's text nodes and attribute values, which would otherwise be read
+/// as interpolations.
+fn escape_braces(nodes: &mut [BodyNode]) {
+    let escape = |text: String| text.replace('{', "{{").replace('}', "}}");
+
+    for node in nodes {
+        match node {
+            BodyNode::Text(text) => {
+                *text = TextNode::from_text(&escape(text.input.source.value()));
+            }
+            BodyNode::Element(element) => {
+                for attribute in &mut element.raw_attributes {
+                    if let AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) =
+                        &mut attribute.value
+                        && let HotLiteral::Fmted(escaped) =
+                            HotLiteral::from_raw_text(&escape(value.formatted_input.source.value()))
+                    {
+                        *value = escaped;
+                    }
+                }
+                escape_braces(&mut element.children);
+            }
+            BodyNode::Component(component) => escape_braces(&mut component.children.roots),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valueless_and_boolean_attributes_become_true() {
+        let rsx = convert(
+            r#"<input required disabled="" value="false" data-x=""><option selected="selected" hidden>A</option><details open="open"></details>"#,
+            &Options::default(),
+        )
+        .unwrap();
+        for expected in [
+            r#""data-x": """#,
+            "disabled: true",
+            "required: true",
+            r#"value: "false""#,
+        ] {
+            assert!(rsx.contains(expected), "missing {expected}:\n{rsx}");
+        }
+        assert!(
+            rsx.contains(r#"option { hidden: true, selected: true, "A" }"#),
+            "{rsx}"
+        );
+        assert!(rsx.contains("details { open: true }"), "{rsx}");
+    }
+
+    #[test]
+    fn braces_in_text_and_attributes_are_escaped() {
+        let rsx = convert(
+            r#"<p title="{a}">Text with {braces}</p>"#,
+            &Options::default(),
+        )
+        .unwrap();
+        assert!(
+            rsx.contains(r#"p { title: "{{a}}", "Text with {{braces}}" }"#),
+            "{rsx}"
+        );
+    }
+
+    #[test]
+    fn keyword_elements_are_raw_identifiers() {
+        let html = r##"<svg><use href="#a"/></svg>"##;
+        let plain = convert(html, &Options::default()).unwrap();
+        assert!(plain.contains(r##"r#use { href: "#a" }"##), "{plain}");
+
+        let options = Options {
+            icon_component: Some("Icon".to_string()),
+            ..Options::default()
+        };
+        let icon = convert(html, &options).unwrap();
+        assert!(icon.contains(r##"r#use { href: "#a" }"##), "{icon}");
+    }
+}
@@ -1,9 +1,21 @@
 use dioxus::prelude::*;
-use dioxus_rsx_rosetta::Dom;
-use std::borrow::Cow;
-
+use dioxus_html_rsx::{
+    Asset, ConvertError, FidelityReport, FormatOptions, HandlerStub, InputFormat,
+    MarkdownComponents, Options, OutputMode, check_round_trip, component_name, convert_with_report,
+    rsx_to_html,
+};
+
+mod assets_panel;
+mod error_panel;
+mod fidelity_panel;
+mod handlers_panel;
 mod syntax_highlight;
-use syntax_highlight::CodeBlock;
+use assets_panel::AssetsPanel;
+use error_panel::ErrorPanel;
+use fidelity_panel::FidelityPanel;
+use handlers_panel::HandlersPanel;
+use std::ops::Range;
+use syntax_highlight::{CodeBlock, Token, counterpart, token_at, tokenize};
 
 /// Copy text to clipboard (web only)
 #[cfg(feature = "web")]
@@ -46,109 +58,46 @@ fn to_clipboard(_text: &str) {
     // Do nothing on non-web platforms
 }
 
-/// Preprocesses HTML to convert React-style attributes to Dioxus RSX format and normalize whitespace
-/// - Converts `className` to `class`
-/// - Normalizes whitespace in text content while preserving attribute spacing
-fn preprocess_html(html: &str) -> Cow<str> {
-    // Start with a mutable string for multiple operations
-    let mut processed = html.to_string();
-
-    // Replace className with class - handle both quoted and unquoted attributes
-    if html.contains("className") {
-        // Handle className="value" (double quotes)
-        processed = processed.replace("className=\"", "class=\"");
-
-        // Handle className='value' (single quotes)
-        processed = processed.replace("className='", "class='");
+/// How long live mode waits after the last edit before converting.
+const LIVE_DEBOUNCE_MS: u32 = 300;
 
-        // Handle className=value (no quotes)
-        processed = processed.replace("className=", "class=");
-    }
-
-    // Only normalize whitespace in text content, not in attributes
-    // We'll parse the HTML more carefully to preserve attribute spacing
-    let mut result = String::new();
-    let mut in_tag = false;
-    let mut in_quotes = false;
-    let mut quote_char = '"'; // Default quote character
-    let mut last_char = ' '; // Initialize with space
-
-    for c in processed.chars() {
-        // Track if we're inside a tag
-        if c == '<' {
-            in_tag = true;
-            result.push(c);
-        }
-        // Track if we're at the end of a tag
-        else if c == '>' {
-            in_tag = false;
-            result.push(c);
-        }
-        // Handle quotes - track if we're inside quoted attribute values
-        else if (c == '"' || c == '\'') && in_tag {
-            if !in_quotes {
-                // Starting quotes
-                in_quotes = true;
-                quote_char = c;
-            } else if c == quote_char {
-                // Ending quotes (matching the opening quote type)
-                in_quotes = false;
-            }
-            result.push(c);
-        }
-        // Handle whitespace
-        else if c.is_whitespace() {
-            // Inside a tag or quotes, preserve all whitespace for attributes
-            if in_tag || in_quotes {
-                result.push(c);
-            }
-            // In text content, normalize whitespace
-            else {
-                // Only add a space if the previous character wasn't whitespace
-                if !last_char.is_whitespace() {
-                    result.push(' ');
-                }
-            }
-        }
-        // All other characters
-        else {
-            result.push(c);
-        }
+/// Waits for `ms` milliseconds. The timer runs in the page, so this works on every platform.
+async fn sleep(ms: u32) {
+    let _ = document::eval(&format!(
+        "await new Promise(resolve => setTimeout(resolve, {ms}));"
+    ))
+    .await;
+}
 
-        last_char = c;
-    }
+/// The `id` of the input textarea, which click-to-jump selects text in.
+const INPUT_ID: &str = "html-input";
+
+/// Selects the byte range `range` of `text` in the input textarea.
+fn select_input(text: &str, range: Range<usize>) {
+    // The DOM counts selection offsets in UTF-16 code units
+    let start = text[..range.start].encode_utf16().count();
+    let end = start + text[range].encode_utf16().count();
+    document::eval(&format!(
+        "const input = document.getElementById({INPUT_ID:?}); input.focus(); input.setSelectionRange({start}, {end});"
+    ));
+}
 
-    // Final cleanup - remove spaces immediately after '>' and before '<'
-    let mut final_result = String::new();
-    let mut last_was_tag_end = false;
-    let mut chars = result.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        if c == '>' {
-            last_was_tag_end = true;
-            final_result.push(c);
-        } else if c == '<' {
-            final_result.push(c);
-            last_was_tag_end = false;
-        } else if c.is_whitespace() {
-            // Skip space after '>' or before '<'
-            if last_was_tag_end {
-                // Check if next char is '<'
-                if let Some(&next) = chars.peek() {
-                    if next != '<' {
-                        final_result.push(c);
-                    }
-                }
-            } else {
-                final_result.push(c);
-            }
-        } else {
-            final_result.push(c);
-            last_was_tag_end = false;
+/// Reads the caret position in the input textarea as a byte offset into `text`.
+async fn input_caret(text: &str) -> Option<usize> {
+    let caret = document::eval(&format!(
+        "return document.getElementById({INPUT_ID:?}).selectionStart;"
+    ))
+    .join::<usize>()
+    .await
+    .ok()?;
+    let mut units = 0;
+    for (offset, c) in text.char_indices() {
+        if units >= caret {
+            return Some(offset);
         }
+        units += c.len_utf16();
     }
-
-    Cow::Owned(final_result)
+    Some(text.len())
 }
 
 fn main() {
@@ -160,6 +109,117 @@ fn app() -> Element {
     let mut html_input = use_signal(String::new);
     let mut rsx_output = use_signal(|| "Your Generated RSX".to_string());
     let mut copied = use_signal(|| false);
+    let mut input_format = use_signal(InputFormat::default);
+    let mut split_styles = use_signal(|| false);
+    let mut handler_stubs = use_signal(|| false);
+    let mut extract_components = use_signal(|| false);
+    let mut icon_components = use_signal(|| false);
+    let mut extract_assets = use_signal(|| false);
+    let mut markdown_components = use_signal(|| false);
+    let mut format = use_signal(FormatOptions::default);
+    // Files the last conversion referenced through `asset!()`
+    let mut assets = use_signal(Vec::<Asset>::new);
+    // Handlers the last conversion wrote as stubs
+    let mut handlers = use_signal(Vec::<HandlerStub>::new);
+    // Converts RSX back to HTML instead of HTML to RSX
+    let mut to_html = use_signal(|| false);
+    // The last conversion error together with the input that produced it
+    let mut conversion_error = use_signal(|| None::<(ConvertError, String)>);
+    // The result of the last round-trip check, cleared whenever the output changes
+    let mut fidelity_report = use_signal(|| None::<FidelityReport>);
+    let mut live_mode = use_signal(|| false);
+    // The pending live conversion, cancelled by the next edit
+    let mut live_task = use_signal(|| None::<Task>);
+    // The output tokens matching the input under the caret, or the last clicked output token
+    let mut output_selection = use_signal(|| None::<Range<usize>>);
+    let languages = move || {
+        if to_html() {
+            ("rsx", "html")
+        } else {
+            ("html", "rsx")
+        }
+    };
+
+    let options = move || Options {
+        input: input_format(),
+        split_styles: split_styles(),
+        handler_stubs: handler_stubs(),
+        extract_components: extract_components(),
+        icon_component: icon_components().then(|| "Icon".to_string()),
+        extract_assets: extract_assets(),
+        markdown_components: if markdown_components() {
+            MarkdownComponents {
+                heading: Some("Heading".to_string()),
+                code_block: Some("CodeBlock".to_string()),
+                link: Some("Link".to_string()),
+            }
+        } else {
+            MarkdownComponents::default()
+        },
+        format: format(),
+        ..Options::default()
+    };
+
+    // Converts the current input, keeping the previous output if the input is invalid
+    let mut run_conversion = move || {
+        let html_value = html_input();
+        let options = options();
+        fidelity_report.set(None);
+        let converted = if to_html() {
+            handlers.set(Vec::new());
+            assets.set(Vec::new());
+            rsx_to_html(&html_value)
+        } else {
+            convert_with_report(&html_value, &options).map(|conversion| {
+                let output = conversion.output();
+                handlers.set(conversion.handlers);
+                assets.set(conversion.assets);
+                output
+            })
+        };
+        match converted {
+            Ok(formatted) => {
+                rsx_output.set(formatted);
+                output_selection.set(None);
+                conversion_error.set(None);
+            }
+            Err(error) => conversion_error.set(Some((error, html_value))),
+        }
+    };
+
+    // Debounces conversions while typing in live mode
+    let mut schedule_live_conversion = move || {
+        if !live_mode() {
+            return;
+        }
+        if let Some(task) = live_task.take() {
+            task.cancel();
+        }
+        live_task.set(Some(spawn(async move {
+            sleep(LIVE_DEBOUNCE_MS).await;
+            run_conversion();
+        })));
+    };
+
+    // Selects the output matching the input token under the caret
+    let jump_to_output = move || {
+        spawn(async move {
+            let (input_language, output_language) = languages();
+            let input = html_input();
+            let Some(caret) = input_caret(&input).await else {
+                return;
+            };
+            let input_tokens = tokenize(&input, input_language);
+            let matching = token_at(&input_tokens, caret).and_then(|token| {
+                counterpart(
+                    token,
+                    &input_tokens,
+                    &tokenize(&rsx_output(), output_language),
+                )
+            });
+            output_selection.set(matching);
+        });
+    };
 
     // Add CSS for syntax highlighting and responsive layout
     let css = r#"/* Tailwind-like utility classes for syntax highlighting */
@@ -175,6 +235,7 @@ fn app() -> Element {
 .text-yellow-500 { color: #eab308; }
 .text-gray-500 { color: #9ca3af; }
 .text-white { color: #ffffff; }
+.token-selected { background-color: rgba(205, 127, 50, 0.35); border-radius: 0.125rem; }
 
 /* Code block styling */
 .language-html, .language-rsx {
@@ -279,35 +340,261 @@ body, html {
 
                         h2 { style: "color: #FFFFFF; font-family: monospace; margin-bottom: 0.75rem; display: flex; align-items: center; gap: 0.5rem;",
                             span { style: "color: #CD7F32; font-size: 1.25rem;", "📄" }
-                            "HTML Input"
+                            if to_html() {
+                                "RSX Input"
+                            } else if input_format() == InputFormat::Jsx {
+                                "JSX Input"
+                            } else if matches!(input_format(), InputFormat::Vue | InputFormat::Svelte) {
+                                "Component Input"
+                            } else if input_format() == InputFormat::Markdown {
+                                "Markdown Input"
+                            } else if input_format() != InputFormat::Html {
+                                "Template Input"
+                            } else {
+                                "HTML Input"
+                            }
+
+                            // Conversion direction toggle
+                            button {
+                                title: "Swap conversion direction",
+                                style: "margin-left: auto; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.25rem 0.5rem; font-family: monospace; font-size: 0.8rem; cursor: pointer;",
+                                onclick: move |_| {
+                                    to_html.toggle();
+                                    conversion_error.set(None);
+                                    let placeholder = if to_html() { "Your Generated HTML" } else { "Your Generated RSX" };
+                                    rsx_output.set(placeholder.to_string());
+                                    schedule_live_conversion();
+                                },
+                                if to_html() {
+                                    "RSX → HTML ⇄"
+                                } else {
+                                    "HTML → RSX ⇄"
+                                }
+                            }
+
+                            // Input format selector
+                            select {
+                                hidden: to_html(),
+                                style: "background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.25rem 0.5rem; font-family: monospace; font-size: 0.8rem;",
+                                onchange: move |e| {
+                                    let format = match e.value().as_str() {
+                                        "jsx" => InputFormat::Jsx,
+                                        "jinja" => InputFormat::Jinja,
+                                        "askama" => InputFormat::Askama,
+                                        "handlebars" => InputFormat::Handlebars,
+                                        "vue" => InputFormat::Vue,
+                                        "svelte" => InputFormat::Svelte,
+                                        "markdown" => InputFormat::Markdown,
+                                        _ => InputFormat::Html,
+                                    };
+                                    input_format.set(format);
+                                    schedule_live_conversion();
+                                },
+                                option { value: "html", selected: input_format() == InputFormat::Html, "HTML" }
+                                option { value: "jsx", selected: input_format() == InputFormat::Jsx, "JSX / TSX" }
+                                option { value: "jinja", selected: input_format() == InputFormat::Jinja, "Jinja" }
+                                option { value: "askama", selected: input_format() == InputFormat::Askama, "Askama" }
+                                option { value: "handlebars", selected: input_format() == InputFormat::Handlebars, "Handlebars" }
+                                option { value: "vue", selected: input_format() == InputFormat::Vue, "Vue SFC" }
+                                option { value: "svelte", selected: input_format() == InputFormat::Svelte, "Svelte" }
+                                option { value: "markdown", selected: input_format() == InputFormat::Markdown, "Markdown" }
+                            }
                         }
 
                         // Simple textarea for HTML input
                         textarea {
+                            id: INPUT_ID,
                             value: "{html_input}",
-                            oninput: move |e| html_input.set(e.value().clone()),
-                            placeholder: "Paste your HTML code here...",
+                            oninput: move |e| {
+                                html_input.set(e.value().clone());
+                                schedule_live_conversion();
+                            },
+                            // Highlight the output for the token under the caret
+                            onclick: move |_| jump_to_output(),
+                            onkeyup: move |_| jump_to_output(),
+                            placeholder: if to_html() { "Paste your RSX code here..." } else { "Paste your HTML code here..." },
                             style: "width: 100%; height: 60vh; padding: 0.75rem; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; font-family: monospace; resize: none; line-height: 1.5; font-size: 0.95rem; transition: border-color 0.2s ease-in-out; outline: none; box-sizing: border-box;",
                         }
 
-                        div { style: "display: flex; justify-content: center; margin-top: 0.75rem;",
+                        div { style: "display: flex; justify-content: center; align-items: center; gap: 1rem; margin-top: 0.75rem;",
+                            // Conversion settings
+                            if !to_html() {
+                                label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: split_styles(),
+                                        onchange: move |e| {
+                                            split_styles.set(e.checked());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                    "Split inline styles"
+                                }
+                                label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: handler_stubs(),
+                                        onchange: move |e| {
+                                            handler_stubs.set(e.checked());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                    "Handler stubs"
+                                }
+                                label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: extract_components(),
+                                        onchange: move |e| {
+                                            extract_components.set(e.checked());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                    "Extract components"
+                                }
+                                label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: icon_components(),
+                                        onchange: move |e| {
+                                            icon_components.set(e.checked());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                    "SVG icon components"
+                                }
+                                label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                    input {
+                                        r#type: "checkbox",
+                                        checked: extract_assets(),
+                                        onchange: move |e| {
+                                            extract_assets.set(e.checked());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                    "Extract assets"
+                                }
+                                if input_format() == InputFormat::Markdown {
+                                    label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                        input {
+                                            r#type: "checkbox",
+                                            checked: markdown_components(),
+                                            onchange: move |e| {
+                                                markdown_components.set(e.checked());
+                                                schedule_live_conversion();
+                                            },
+                                        }
+                                        "Heading, CodeBlock and Link components"
+                                    }
+                                }
+                            }
+
+                            label { style: "display: flex; align-items: center; gap: 0.4rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem; cursor: pointer;",
+                                input {
+                                    r#type: "checkbox",
+                                    checked: live_mode(),
+                                    onchange: move |e| {
+                                        live_mode.set(e.checked());
+                                        schedule_live_conversion();
+                                    },
+                                }
+                                "Live"
+                            }
+
                             button {
-                                onclick: move |_| {
-                                    spawn(async move {
-                                        let html_value = html_input();
-                                        let preprocessed_html = preprocess_html(&html_value);
-                                        let dom = Dom::parse(&preprocessed_html).unwrap();
-                                        let rsx_response_callbody = dioxus_rsx_rosetta::rsx_from_html(&dom);
-                                        let formatted = dioxus_autofmt::write_block_out(&rsx_response_callbody)
-                                            .unwrap();
-                                        rsx_output.set(formatted);
-                                    });
-                                },
+                                onclick: move |_| run_conversion(),
                                 style: "padding: 0.75rem 1.5rem; background-color: #CD7F32; color: #111111; border: none; border-radius: 0.25rem; cursor: pointer; font-weight: bold; display: flex; align-items: center; gap: 0.5rem; transition: background-color 0.2s ease-in-out; box-shadow: 0 2px 4px rgba(0, 0, 0, 0.2);",
 
                                 // Convert icon
                                 span { style: "font-size: 1.2rem;", "⟳" }
-                                "Convert to RSX"
+                                if to_html() {
+                                    "Convert to HTML"
+                                } else {
+                                    "Convert to RSX"
+                                }
+                            }
+                        }
+
+                        // Output formatting settings
+                        if !to_html() {
+                            div { style: "display: flex; justify-content: center; align-items: center; gap: 1rem; margin-top: 0.75rem; color: #D1D5DB; font-family: monospace; font-size: 0.85rem;",
+                                label { style: "display: flex; align-items: center; gap: 0.4rem;",
+                                    "Indent"
+                                    input {
+                                        r#type: "number",
+                                        min: "1",
+                                        max: "8",
+                                        value: "{format().indent_width}",
+                                        style: "width: 3.5rem; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.2rem 0.4rem; font-family: monospace;",
+                                        onchange: move |e| {
+                                            if let Ok(width) = e.value().parse() {
+                                                format.with_mut(|format| format.indent_width = width);
+                                                schedule_live_conversion();
+                                            }
+                                        },
+                                    }
+                                }
+                                label { style: "display: flex; align-items: center; gap: 0.4rem;",
+                                    "Max width"
+                                    input {
+                                        r#type: "number",
+                                        min: "20",
+                                        placeholder: "auto",
+                                        value: format().max_line_length.map(|width| width.to_string()).unwrap_or_default(),
+                                        style: "width: 4.5rem; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.2rem 0.4rem; font-family: monospace;",
+                                        onchange: move |e| {
+                                            format.with_mut(|format| format.max_line_length = e.value().parse().ok());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                }
+                                label { style: "display: flex; align-items: center; gap: 0.4rem;",
+                                    "Attributes per line"
+                                    input {
+                                        r#type: "number",
+                                        min: "0",
+                                        placeholder: "auto",
+                                        value: format().max_attributes_per_line.map(|count| count.to_string()).unwrap_or_default(),
+                                        style: "width: 4.5rem; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.2rem 0.4rem; font-family: monospace;",
+                                        onchange: move |e| {
+                                            format.with_mut(|format| format.max_attributes_per_line = e.value().parse().ok());
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                }
+                                select {
+                                    style: "background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.25rem 0.5rem; font-family: monospace; font-size: 0.8rem;",
+                                    onchange: move |e| {
+                                        let output = match e.value().as_str() {
+                                            "rsx" => OutputMode::Rsx,
+                                            "component" => OutputMode::Component,
+                                            "app" => OutputMode::App,
+                                            _ => OutputMode::Body,
+                                        };
+                                        format.with_mut(|format| format.output = output);
+                                        schedule_live_conversion();
+                                    },
+                                    option { value: "body", selected: format().output == OutputMode::Body, "Bare body" }
+                                    option { value: "rsx", selected: format().output == OutputMode::Rsx, "rsx! {{ }}" }
+                                    option { value: "component", selected: format().output == OutputMode::Component, "#[component]" }
+                                    option { value: "app", selected: format().output == OutputMode::App, "main.rs" }
+                                }
+                                if matches!(format().output, OutputMode::Component | OutputMode::App) {
+                                    input {
+                                        r#type: "text",
+                                        placeholder: "App",
+                                        title: "Component name",
+                                        value: format().component_name.unwrap_or_default(),
+                                        style: "width: 8rem; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.2rem 0.4rem; font-family: monospace;",
+                                        // Show the name as it will be written once editing is done
+                                        onchange: move |e| {
+                                            let name = e.value();
+                                            let name = (!name.trim().is_empty()).then(|| component_name(&name));
+                                            format.with_mut(|format| format.component_name = name);
+                                            schedule_live_conversion();
+                                        },
+                                    }
+                                }
                             }
                         }
                     }
@@ -321,14 +608,65 @@ body, html {
 
                         h2 { style: "color: #FFFFFF; font-family: monospace; margin-bottom: 0.75rem; display: flex; align-items: center; gap: 0.5rem;",
                             span { style: "color: #CD7F32; font-size: 1.25rem;", "📝" }
-                            "RSX Output"
+                            if to_html() {
+                                "HTML Output"
+                            } else {
+                                "RSX Output"
+
+                                // Converts to RSX and back to HTML to find anything the conversion lost
+                                button {
+                                    title: "Convert the RSX back to HTML and compare it with the input",
+                                    style: "margin-left: auto; background-color: #333333; color: #FFFFFF; border: 1px solid #444444; border-radius: 0.25rem; padding: 0.25rem 0.5rem; font-family: monospace; font-size: 0.8rem; cursor: pointer;",
+                                    onclick: move |_| {
+                                        // The check converts a bare body, so the shown output, handlers
+                                        // and assets come from a regular conversion of the same input
+                                        run_conversion();
+                                        let html_value = html_input();
+                                        match check_round_trip(&html_value, &options()) {
+                                            Ok(report) => fidelity_report.set(Some(report)),
+                                            Err(error) => {
+                                                conversion_error.set(Some((error, html_value)))
+                                            }
+                                        }
+                                    },
+                                    "Check round trip"
+                                }
+                            }
+
+                            // In live mode the last good output stays visible, so flag errors inline
+                            if live_mode() && let Some((error, _)) = conversion_error() {
+                                span {
+                                    title: "{error}",
+                                    style: "margin-left: auto; max-width: 60%; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; color: #F87171; font-size: 0.8rem; font-weight: normal;",
+                                    "⚠ {error}"
+                                }
+                            }
                         }
 
                         div { style: "width: 100%; height: 60vh; padding: 0.5rem; background-color: #1A1A1A; color: #FFFFFF; border: 1px solid #333333; border-radius: 0.25rem; overflow: auto; position: relative; box-sizing: border-box;",
-                            // Use the CodeBlock component for syntax highlighting
-                            CodeBlock {
-                                code: rsx_output().to_string(),
-                                language: "rsx".to_string(),
+                            // Show the error panel instead of stale output when a manual conversion fails
+                            if let Some((error, input)) = conversion_error().filter(|_| !live_mode()) {
+                                ErrorPanel { error, input }
+                            } else {
+                                // Use the CodeBlock component for syntax highlighting
+                                CodeBlock {
+                                    code: rsx_output().to_string(),
+                                    language: languages().1,
+                                    selected: output_selection(),
+                                    on_token_click: move |token: Token| {
+                                        let (input_language, output_language) = languages();
+                                        let input = html_input();
+                                        let matching = counterpart(
+                                            &token,
+                                            &tokenize(&rsx_output(), output_language),
+                                            &tokenize(&input, input_language),
+                                        );
+                                        if let Some(range) = matching {
+                                            select_input(&input, range);
+                                        }
+                                        output_selection.set(Some(token.span));
+                                    },
+                                }
                             }
 
                             // Copy button (positioned in the top-right corner)
@@ -366,6 +704,18 @@ body, html {
                                 }
                             }
                         }
+
+                        if !to_html() && !handlers().is_empty() {
+                            HandlersPanel { handlers: handlers() }
+                        }
+
+                        if !to_html() && !assets().is_empty() {
+                            AssetsPanel { assets: assets() }
+                        }
+
+                        if let Some(report) = fidelity_report() {
+                            FidelityPanel { report }
+                        }
                     }
                 }
             } // Close main tag
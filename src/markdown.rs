@@ -0,0 +1,261 @@
+//! Markdown input.
+//!
+//! Markdown is rendered to HTML with `pulldown-cmark` and then converted like any other HTML, so
+//! preprocessing, style splitting and the other options apply to it. Headings, fenced code blocks
+//! and links can be rendered with custom components instead of plain elements, e.g. the router's
+//! `Link` for links between documentation pages.
+
+use crate::component::component_name;
+use crate::handlers::Conversion;
+use crate::{ConvertError, Options};
+use dioxus_rsx::{
+    Attribute, AttributeName, AttributeValue, BodyNode, Component, Element, HotLiteral,
+    TemplateBody,
+};
+use proc_macro2::{Ident, Literal, Span};
+use quote::quote;
+
+/// Components to render Markdown elements with, instead of plain HTML elements. Names are turned
+/// into PascalCase identifiers like other component names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MarkdownComponents {
+    /// Renders headings as `Name { level: 2, "Title" }`.
+    pub heading: Option<String>,
+    /// Renders fenced code blocks as `Name { code: "...", language: "rust" }`. `language` is left
+    /// out for fences without one.
+    pub code_block: Option<String>,
+    /// Renders links as `Name { to: "/docs", "Docs" }`, matching the router's `Link`.
+    pub link: Option<String>,
+}
+
+/// Renders Markdown to HTML and converts it to RSX.
+pub(crate) fn convert_markdown(
+    markdown: &str,
+    options: &Options,
+) -> Result<Conversion, ConvertError> {
+    // Errors can only come from raw HTML in the Markdown, and their positions are in the rendered
+    // HTML, which the user never sees
+    crate::convert_html(&render(markdown), options, None).map_err(|error| match error {
+        ConvertError::Parse { message, .. } => ConvertError::Parse {
+            message,
+            span: None,
+        },
+        ConvertError::Unsupported { construct, .. } => ConvertError::Unsupported {
+            construct,
+            span: None,
+        },
+        ConvertError::Format => ConvertError::Format,
+    })
+}
+
+/// Renders Markdown, with the GitHub extensions documentation tends to use, to HTML.
+fn render(markdown: &str) -> String {
+    let options = pulldown_cmark::Options::ENABLE_TABLES
+        | pulldown_cmark::Options::ENABLE_STRIKETHROUGH
+        | pulldown_cmark::Options::ENABLE_TASKLISTS
+        | pulldown_cmark::Options::ENABLE_FOOTNOTES;
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+    pulldown_cmark::html::push_html(
+        &mut html,
+        pulldown_cmark::Parser::new_ext(markdown, options),
+    );
+    html
+}
+
+/// Replaces headings, code blocks and links in rosetta's output with the configured components.
+pub(crate) fn map_components(nodes: &mut [BodyNode], components: &MarkdownComponents) {
+    let heading = components.heading.as_deref().map(component_ident);
+    let code_block = components.code_block.as_deref().map(component_ident);
+    let link = components.link.as_deref().map(component_ident);
+    map_nodes(
+        nodes,
+        &Mapped {
+            heading,
+            code_block,
+            link,
+        },
+    );
+}
+
+struct Mapped {
+    heading: Option<Ident>,
+    code_block: Option<Ident>,
+    link: Option<Ident>,
+}
+
+fn component_ident(name: &str) -> Ident {
+    Ident::new(&component_name(name), Span::call_site())
+}
+
+fn map_nodes(nodes: &mut [BodyNode], mapped: &Mapped) {
+    for node in nodes {
+        let BodyNode::Element(element) = node else {
+            continue;
+        };
+        map_nodes(&mut element.children, mapped);
+
+        let name = element.name.to_string();
+        let replacement = match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => mapped.heading.as_ref().map(|ident| {
+                let level = Literal::u8_unsuffixed(name[1..].parse().unwrap_or(1));
+                invocation(ident, quote! { level: #level }, element)
+            }),
+            "pre" => mapped
+                .code_block
+                .as_ref()
+                .and_then(|ident| code_block(ident, element)),
+            "a" => mapped.link.as_ref().and_then(|ident| {
+                let href = element
+                    .raw_attributes
+                    .iter()
+                    .position(|attribute| attribute.name.to_string() == "href")?;
+                let href = element.raw_attributes.remove(href);
+                let mut component = invocation(ident, quote! {}, element);
+                component.fields.insert(
+                    0,
+                    Attribute::from_raw(
+                        AttributeName::BuiltIn(Ident::new("to", Span::call_site())),
+                        href.value,
+                    ),
+                );
+                Some(component)
+            }),
+            _ => None,
+        };
+
+        if let Some(component) = replacement {
+            *node = BodyNode::Component(component);
+        }
+    }
+}
+
+/// Builds `Name { first, ...attributes, ...children }` from an element.
+fn invocation(ident: &Ident, first: proc_macro2::TokenStream, element: &mut Element) -> Component {
+    let mut component: Component =
+        syn::parse2(quote! { #ident { #first } }).expect("component invocations are valid RSX");
+    component
+        .fields
+        .extend(std::mem::take(&mut element.raw_attributes));
+    component.children = TemplateBody::new(std::mem::take(&mut element.children));
+    component
+}
+
+/// Builds the code block component for a `pre` that holds a single `code` element, as rendered
+/// for fenced and indented code blocks.
+fn code_block(ident: &Ident, pre: &Element) -> Option<Component> {
+    let [BodyNode::Element(code)] = pre.children.as_slice() else {
+        return None;
+    };
+    if code.name != "code" {
+        return None;
+    }
+
+    // The text is already escaped for RSX, which string props need as well
+    let mut text = String::new();
+    for child in &code.children {
+        let BodyNode::Text(child) = child else {
+            return None;
+        };
+        text.push_str(&child.input.source.value());
+    }
+    let language = code.raw_attributes.iter().find_map(|attribute| {
+        if attribute.name.to_string() != "class" {
+            return None;
+        }
+        match &attribute.value {
+            AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) => value
+                .formatted_input
+                .source
+                .value()
+                .split_whitespace()
+                .find_map(|class| class.strip_prefix("language-").map(str::to_string)),
+            _ => None,
+        }
+    });
+
+    let mut component: Component =
+        syn::parse2(quote! { #ident {} }).expect("component invocations are valid RSX");
+    component.fields.push(literal_field("code", &text));
+    if let Some(language) = language {
+        component.fields.push(literal_field("language", &language));
+    }
+    Some(component)
+}
+
+fn literal_field(name: &str, text: &str) -> Attribute {
+    Attribute::from_raw(
+        AttributeName::BuiltIn(Ident::new(name, Span::call_site())),
+        AttributeValue::AttrLiteral(HotLiteral::from_raw_text(text)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{InputFormat, MarkdownComponents, Options, convert};
+
+    fn markdown_options(components: MarkdownComponents) -> Options {
+        Options {
+            input: InputFormat::Markdown,
+            markdown_components: components,
+            ..Options::default()
+        }
+    }
+
+    #[test]
+    fn markdown_renders_to_elements() {
+        let output = convert(
+            "# Hello {world}\n\nSee [the docs](/docs).\n\n```rust\nfn main() {}\n```\n",
+            &markdown_options(MarkdownComponents::default()),
+        )
+        .unwrap();
+
+        for expected in [
+            r#"h1 { "Hello {{world}}" }"#,
+            r#"a { href: "/docs", "the docs" }"#,
+            r#"code { class: "language-rust", "fn main() {{}}\n" }"#,
+        ] {
+            assert!(output.contains(expected), "missing {expected}:\n{output}");
+        }
+    }
+
+    #[test]
+    fn markdown_elements_map_to_components() {
+        let output = convert(
+            "## Install\n\nSee [the docs](/docs \"Docs\").\n\n```sh\ncargo add dioxus\n```\n\n    indented\n",
+            &markdown_options(MarkdownComponents {
+                heading: Some("heading".to_string()),
+                code_block: Some("CodeBlock".to_string()),
+                link: Some("Link".to_string()),
+            }),
+        )
+        .unwrap();
+
+        for expected in [
+            r#"Heading { level: 2, "Install" }"#,
+            r#"Link { to: "/docs", title: "Docs", "the docs" }"#,
+            r#"CodeBlock { code: "cargo add dioxus\n", language: "sh" }"#,
+            r#"CodeBlock { code: "indented\n" }"#,
+        ] {
+            assert!(output.contains(expected), "missing {expected}:\n{output}");
+        }
+    }
+
+    #[test]
+    fn task_list_checkboxes_are_booleans() {
+        let output = convert(
+            "- [x] done\n- [ ] todo\n",
+            &markdown_options(MarkdownComponents::default()),
+        )
+        .unwrap();
+
+        assert!(
+            output.contains(r#"input { checked: true, disabled: true, r#type: "checkbox" }"#),
+            "{output}"
+        );
+        assert!(
+            output.contains(r#"input { disabled: true, r#type: "checkbox" }"#),
+            "{output}"
+        );
+        assert!(!output.contains(r#": """#), "{output}");
+    }
+}
@@ -0,0 +1,307 @@
+use crate::handlers::HandlerStub;
+use crate::jsx::decode_entities;
+use crate::rules::{self, Marked, Rewrite, marker_name};
+use crate::svg;
+use crate::tokenizer::{Tag, TokenKind, tokenize};
+use crate::whitespace::{is_void_element, preserves_whitespace};
+use crate::{Options, Span};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// Preprocesses HTML to convert React-style attributes to Dioxus RSX format and normalize whitespace
+/// - Converts `className` attributes to `class` (only inside real tags, never in text or scripts)
+/// - Normalizes whitespace in text content while preserving attribute spacing
+/// - Leaves the content of `pre`, `code`, `textarea` and `white-space: pre` elements untouched
+pub fn preprocess_html(html: &str) -> Cow<'_, str> {
+    Cow::Owned(Preprocessed::new(html).text)
+}
+
+/// The output of [`preprocess_html`] along with a map back to the original input.
+///
+/// Parse errors are reported against the preprocessed text, so the offset map is used to point
+/// them at the markup the user actually wrote.
+#[derive(Debug, Clone)]
+pub(crate) struct Preprocessed {
+    pub text: String,
+    /// Byte offset in the original input for every byte of `text`, plus one entry for the end.
+    offsets: Vec<usize>,
+    /// Attributes written under a marker name by a rewrite rule, indexed by the marker.
+    pub marked: Vec<Marked>,
+    /// Event handlers among `marked`, in document order.
+    pub handlers: Vec<HandlerStub>,
+}
+
+impl Preprocessed {
+    /// Runs the preprocessing steps while tracking where every character came from.
+    pub fn new(html: &str) -> Self {
+        Self::for_options(html, &Options::default())
+    }
+
+    /// Prepares `html` for parsing the way `options` ask. Attribute rewrite rules and handler stubs
+    /// always apply; `className` renames and whitespace normalization only with
+    /// `options.preprocess`.
+    pub fn for_options(html: &str, options: &Options) -> Self {
+        let mut result = Self {
+            text: String::with_capacity(html.len()),
+            offsets: Vec::with_capacity(html.len() + 1),
+            marked: Vec::new(),
+            handlers: Vec::new(),
+        };
+        // Open elements inside the outermost whitespace-sensitive element, if any
+        let mut preformatted: Vec<String> = Vec::new();
+
+        for token in tokenize(html) {
+            match &token.kind {
+                TokenKind::StartTag(tag) => {
+                    result.push_start_tag(html, token.span.clone(), tag, options);
+                    track_start_tag(html, tag, &mut preformatted);
+                }
+                TokenKind::EndTag { name } => {
+                    result.push_str(html, token.span.clone());
+                    if let Some(index) = preformatted.iter().rposition(|open| open == name) {
+                        preformatted.truncate(index);
+                    }
+                }
+                TokenKind::Text if options.preprocess && preformatted.is_empty() => {
+                    result.push_collapsed(html, token.span.clone())
+                }
+                // Preformatted text, script and style bodies, comments, CDATA and doctypes are
+                // copied verbatim
+                _ => result.push_str(html, token.span.clone()),
+            }
+        }
+
+        result.offsets.push(html.len());
+        result
+    }
+
+    /// Copies a start tag, applying the rewrite rules, then the built-in `on*` handler rule if it is
+    /// enabled, the namespaced attribute fix-ups and the React `className` rename.
+    fn push_start_tag(&mut self, html: &str, span: Range<usize>, tag: &Tag, options: &Options) {
+        let mut pos = span.start;
+        for attribute in &tag.attributes {
+            let name = &html[attribute.name.clone()];
+            let rewrite = rules::rewrite(&options.rules, name)
+                .or_else(|| {
+                    let handler = options.handler_stubs
+                        && name.len() > 2
+                        && name
+                            .get(..2)
+                            .is_some_and(|on| on.eq_ignore_ascii_case("on"))
+                        && name.chars().all(|c| c.is_ascii_alphanumeric());
+                    handler.then(|| Rewrite::Mark(Marked::Event(name.to_ascii_lowercase())))
+                })
+                .or_else(|| svg::namespaced_attribute(html, tag, name))
+                .or_else(|| {
+                    (options.preprocess && name == "className")
+                        .then(|| Rewrite::Rename("class".to_string()))
+                });
+            let Some(rewrite) = rewrite else {
+                continue;
+            };
+
+            self.push_str(html, pos..attribute.name.start);
+            pos = attribute.name.end;
+            match rewrite {
+                Rewrite::Rename(name) => self.push_name(&name, attribute.name.start),
+                Rewrite::Drop => pos = attribute.span.end,
+                Rewrite::Mark(marked) => {
+                    if let Marked::Event(event) = &marked {
+                        let code = attribute.value.clone().map_or("", |value| &html[value]);
+                        self.handlers.push(HandlerStub {
+                            event: event.clone(),
+                            element: tag.name.clone(),
+                            code: decode_entities(code).trim().to_string(),
+                            span: Some(Span::from_offsets(
+                                html,
+                                attribute.span.start,
+                                attribute.span.end,
+                            )),
+                        });
+                    }
+                    self.push_name(&marker_name(self.marked.len()), attribute.name.start);
+                    self.marked.push(marked);
+                }
+            }
+        }
+        self.push_str(html, pos..span.end);
+    }
+
+    /// Writes a replacement attribute name, mapped to the start of the name it replaces.
+    fn push_name(&mut self, name: &str, offset: usize) {
+        for c in name.chars() {
+            self.push(c, offset);
+        }
+    }
+
+    /// Copies text content with runs of whitespace collapsed to a single space. Text that is only
+    /// whitespace, like the indentation between tags, is dropped.
+    fn push_collapsed(&mut self, html: &str, span: Range<usize>) {
+        let text = &html[span.clone()];
+        if text.trim().is_empty() {
+            return;
+        }
+
+        let mut last_was_space = false;
+        for (i, c) in text.char_indices() {
+            if c.is_whitespace() {
+                if !last_was_space {
+                    self.push(' ', span.start + i);
+                }
+                last_was_space = true;
+            } else {
+                self.push(c, span.start + i);
+                last_was_space = false;
+            }
+        }
+    }
+
+    fn push_str(&mut self, html: &str, span: Range<usize>) {
+        for (i, c) in html[span.clone()].char_indices() {
+            self.push(c, span.start + i);
+        }
+    }
+
+    /// Maps a byte offset in the preprocessed text back to the original input.
+    pub fn original_offset(&self, offset: usize) -> usize {
+        self.offsets
+            .get(offset)
+            .or(self.offsets.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn push(&mut self, c: char, offset: usize) {
+        self.text.push(c);
+        self.offsets
+            .extend(std::iter::repeat_n(offset, c.len_utf8()));
+    }
+}
+
+/// Updates the stack of open whitespace-sensitive elements after a start tag.
+///
+/// The stack is empty outside such elements. Inside one, every open element is pushed so the
+/// matching end tag can be found even when the same name is nested.
+fn track_start_tag(html: &str, tag: &Tag, open: &mut Vec<String>) {
+    if tag.self_closing || is_void_element(&tag.name) {
+        return;
+    }
+
+    if !open.is_empty() || preserves_whitespace(&tag.name, tag.attribute(html, "style")) {
+        open.push(tag.name.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preprocess(html: &str) -> String {
+        Preprocessed::new(html).text
+    }
+
+    #[test]
+    fn renames_class_name_only_in_tags() {
+        assert_eq!(
+            preprocess(r#"<p className="a">set className=foo</p><br className=b/>"#),
+            r#"<p class="a">set className=foo</p><br class=b/>"#
+        );
+    }
+
+    #[test]
+    fn less_than_in_text_is_not_a_tag() {
+        assert_eq!(
+            preprocess("<p>if a  < b and b >  c</p>"),
+            "<p>if a < b and b > c</p>"
+        );
+    }
+
+    #[test]
+    fn quotes_in_comments_do_not_leak() {
+        assert_eq!(
+            preprocess("<!-- it's \"quoted\" -->\n  <p>a   b</p>"),
+            "<!-- it's \"quoted\" --><p>a b</p>"
+        );
+    }
+
+    #[test]
+    fn script_bodies_are_copied_verbatim() {
+        let script = "<script>\n  if (a > b) {\n    x('</div>  className=y');\n  }\n</script>";
+        assert_eq!(preprocess(script), script);
+    }
+
+    #[test]
+    fn cdata_is_copied_verbatim() {
+        let html = "<svg><![CDATA[ a  <  b ]]></svg>";
+        assert_eq!(preprocess(html), html);
+    }
+
+    #[test]
+    fn quoted_greater_than_stays_in_tag() {
+        assert_eq!(
+            preprocess("<a title='a > b'  className=\"x\" >  go  </a>"),
+            "<a title='a > b'  class=\"x\" > go </a>"
+        );
+    }
+
+    #[test]
+    fn preformatted_text_is_copied_verbatim() {
+        let html = "<pre>  a\n\n  <b> b </b>  </pre>\n  <p>  c  </p>";
+        assert_eq!(
+            preprocess(html),
+            "<pre>  a\n\n  <b> b </b>  </pre><p> c </p>"
+        );
+
+        let html = "<div style=\"color: red; white-space: pre-wrap\"><div>  a  </div>  b  </div> <p>  c </p>";
+        assert_eq!(
+            preprocess(html),
+            "<div style=\"color: red; white-space: pre-wrap\"><div>  a  </div>  b  </div><p> c </p>"
+        );
+
+        // A void element inside doesn't end the preformatted text early
+        let html = "<code>a  <br>  b</code>  <i>  c</i>";
+        assert_eq!(preprocess(html), "<code>a  <br>  b</code><i> c</i>");
+    }
+
+    #[test]
+    fn offsets_point_into_the_original() {
+        let html = "<p className=\"a\">\n   x</p>";
+        let source = Preprocessed::new(html);
+        let x = source.text.find('x').unwrap();
+        assert_eq!(source.original_offset(x), html.find('x').unwrap());
+    }
+
+    #[test]
+    fn offsets_survive_renames_and_multibyte_text() {
+        let html = "<p   className=\"ü\"  id=\"x\">  naïve   café 🌍 <b>y</b></p>";
+        let source = Preprocessed::new(html);
+        assert_eq!(
+            source.text,
+            "<p   class=\"ü\"  id=\"x\"> naïve café 🌍 <b>y</b></p>"
+        );
+
+        // Every byte maps to the start of the character it belongs to in the original
+        for needle in ["\"ü\"", "naïve", "café", "🌍", "<b>", "</p>"] {
+            let start = source.text.find(needle).unwrap();
+            for (i, c) in needle.char_indices() {
+                for byte in 0..c.len_utf8() {
+                    assert_eq!(
+                        source.original_offset(start + i + byte),
+                        html.find(needle).unwrap() + i,
+                        "{needle} byte {}",
+                        i + byte
+                    );
+                }
+            }
+        }
+        // Renamed attributes map to the start of the original name
+        let class = source.text.find("class").unwrap();
+        assert_eq!(
+            source.original_offset(class + 3),
+            html.find("className").unwrap()
+        );
+        // The end of the text, and anything past it, maps to the end of the input
+        assert_eq!(source.original_offset(source.text.len()), html.len());
+        assert_eq!(source.original_offset(source.text.len() + 5), html.len());
+    }
+}
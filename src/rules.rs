@@ -0,0 +1,359 @@
+//! Attribute rewrite rules.
+//!
+//! Frameworks like Alpine.js, htmx and Vue add attributes that `html_parser` can't read
+//! (`@click`, `:class`, `x-on:click.outside`) or that rosetta mangles (it drops everything before a
+//! `:`). Rules are applied to start tags while preprocessing, before the markup is parsed:
+//!
+//! ```toml
+//! [[rule]]
+//! match = "@*"
+//! action = "event"
+//! to = "on*"
+//!
+//! [[rule]]
+//! match = "x-data"
+//! action = "custom"
+//! ```
+//!
+//! A trailing `*` in `match` matches any suffix, and a `*` in `to` is replaced by that suffix. The
+//! first matching rule wins.
+
+use crate::emit::{block_comment, parse_rsx, placeholder};
+use crate::jsx::decode_entities;
+use dioxus_rsx::{Attribute, AttributeName, AttributeValue, BodyNode, HotLiteral};
+use proc_macro2::Span;
+use serde::Deserialize;
+use std::fmt;
+use syn::LitStr;
+
+/// Prefix of the attribute names that stand in for custom attributes and event handlers until
+/// rosetta has converted the element.
+const MARKER_PREFIX: &str = "data-html2rsx-";
+
+/// A rule that rewrites attributes whose name matches `pattern`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RewriteRule {
+    /// Attribute name to match, ASCII case-insensitively. A trailing `*` matches any suffix.
+    pub pattern: String,
+    pub action: RewriteAction,
+}
+
+/// What a [`RewriteRule`] does with a matching attribute.
+///
+/// A `*` in a target name is replaced by the part of the attribute name matched by the `*` in the
+/// pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RewriteAction {
+    /// Renames the attribute before it is parsed, so `class` still becomes a typed attribute.
+    Rename(String),
+    /// Removes the attribute.
+    Drop,
+    /// Replaces the attribute with an event handler stub, e.g. `onclick: move |_| { /* ... */ }`,
+    /// keeping the value in a comment. Modifiers after a `.` (`@click.prevent`) are ignored.
+    Event(String),
+    /// Keeps the attribute as a quoted custom attribute, renamed if a name is given.
+    Custom(Option<String>),
+}
+
+/// An error in a rules config file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RulesError {
+    pub message: String,
+}
+
+impl fmt::Display for RulesError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid rewrite rules: {}", self.message)
+    }
+}
+
+impl std::error::Error for RulesError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesConfig {
+    #[serde(default)]
+    rule: Vec<RuleConfig>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    #[serde(rename = "match")]
+    pattern: String,
+    action: String,
+    to: Option<String>,
+}
+
+/// Parses rewrite rules from a TOML config file with a `[[rule]]` table per rule.
+pub fn parse_rules(config: &str) -> Result<Vec<RewriteRule>, RulesError> {
+    let config: RulesConfig = toml::from_str(config).map_err(|err| RulesError {
+        message: err.message().to_string(),
+    })?;
+
+    config
+        .rule
+        .into_iter()
+        .map(|rule| {
+            let error = |message: &str| RulesError {
+                message: format!("rule `{}`: {message}", rule.pattern),
+            };
+
+            if rule.pattern.is_empty() || rule.pattern.trim_end_matches('*').contains('*') {
+                return Err(error("`match` must be a name with at most a trailing `*`"));
+            }
+
+            let action = match (rule.action.as_str(), rule.to) {
+                ("rename", Some(to)) if !to.is_empty() => RewriteAction::Rename(to),
+                ("rename", _) => return Err(error("`rename` needs a `to` name")),
+                ("drop", None) => RewriteAction::Drop,
+                ("drop", Some(_)) => return Err(error("`drop` does not take a `to` name")),
+                ("event", Some(to)) if to.starts_with("on") => RewriteAction::Event(to),
+                ("event", _) => return Err(error("`event` needs a `to` name starting with `on`")),
+                ("custom", to) => RewriteAction::Custom(to),
+                (action, _) => {
+                    return Err(error(&format!(
+                        "unknown action `{action}`, expected rename, drop, event or custom"
+                    )));
+                }
+            };
+
+            Ok(RewriteRule {
+                pattern: rule.pattern,
+                action,
+            })
+        })
+        .collect()
+}
+
+impl RewriteRule {
+    /// Returns the part of `name` matched by the `*` in the pattern (empty for exact patterns),
+    /// or `None` if the rule doesn't apply.
+    fn matches<'a>(&self, name: &'a str) -> Option<&'a str> {
+        match self.pattern.strip_suffix('*') {
+            Some(prefix) => name
+                .get(..prefix.len())
+                .filter(|start| start.eq_ignore_ascii_case(prefix))
+                .map(|_| &name[prefix.len()..]),
+            None => name.eq_ignore_ascii_case(&self.pattern).then_some(""),
+        }
+    }
+}
+
+/// How the preprocessor should write an attribute.
+pub(crate) enum Rewrite {
+    /// Write it under a new name.
+    Rename(String),
+    Drop,
+    /// Write it under a marker name and fix it up after rosetta with [`restore_marked`].
+    Mark(Marked),
+}
+
+/// An attribute that rosetta can't produce itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Marked {
+    /// A quoted custom attribute with this exact name.
+    Custom(String),
+    /// An event handler stub for this event, e.g. `onclick`.
+    Event(String),
+}
+
+/// Finds the first rule matching the attribute `name` and works out what to write instead.
+pub(crate) fn rewrite(rules: &[RewriteRule], name: &str) -> Option<Rewrite> {
+    rules.iter().find_map(|rule| {
+        let suffix = rule.matches(name)?;
+        let target = |template: &str| template.replace('*', suffix);
+
+        Some(match &rule.action {
+            RewriteAction::Rename(to) => Rewrite::Rename(target(to)),
+            RewriteAction::Drop => Rewrite::Drop,
+            RewriteAction::Custom(to) => Rewrite::Mark(Marked::Custom(
+                to.as_deref().map_or(name.to_string(), target),
+            )),
+            RewriteAction::Event(to) => {
+                let modifiers = suffix.find('.').unwrap_or(suffix.len());
+                let event = to.replace('*', &suffix[..modifiers]).to_ascii_lowercase();
+                // Names that aren't identifiers can't be handlers, so keep the attribute instead
+                if event.starts_with("on")
+                    && event.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                {
+                    Rewrite::Mark(Marked::Event(event))
+                } else {
+                    Rewrite::Mark(Marked::Custom(name.to_string()))
+                }
+            }
+        })
+    })
+}
+
+/// The attribute name that stands in for the `index`th marked attribute.
+pub(crate) fn marker_name(index: usize) -> String {
+    format!("{MARKER_PREFIX}{index}")
+}
+
+/// Looks up the marked attribute a name stands in for.
+pub(crate) fn marked<'a>(marked: &'a [Marked], name: &str) -> Option<&'a Marked> {
+    let index = name.strip_prefix(MARKER_PREFIX)?.parse::<usize>().ok()?;
+    marked.get(index)
+}
+
+/// Replaces the marker attributes in rosetta's output with the custom attributes and event handler
+/// stubs they stand in for. Handler comments are added to `placeholders`.
+pub(crate) fn restore_marked(
+    nodes: &mut [BodyNode],
+    marked: &[Marked],
+    placeholders: &mut Vec<String>,
+) {
+    if marked.is_empty() {
+        return;
+    }
+
+    for node in nodes {
+        let element = match node {
+            BodyNode::Element(element) => element,
+            BodyNode::Component(component) => {
+                restore_marked(&mut component.children.roots, marked, placeholders);
+                continue;
+            }
+            _ => continue,
+        };
+
+        let mut changed = false;
+        for attribute in &mut element.raw_attributes {
+            let AttributeName::Custom(name) = &attribute.name else {
+                continue;
+            };
+            let Some(replacement) = self::marked(marked, &name.value()) else {
+                continue;
+            };
+
+            changed = true;
+            *attribute = match replacement {
+                Marked::Custom(name) => Attribute::from_raw(
+                    AttributeName::Custom(LitStr::new(name, Span::call_site())),
+                    attribute.value.clone(),
+                ),
+                Marked::Event(event) => {
+                    let source = match &attribute.value {
+                        AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) => {
+                            value.formatted_input.source.value()
+                        }
+                        _ => String::new(),
+                    };
+                    placeholders.push(block_comment(&decode_entities(&source)));
+                    let ident = placeholder(placeholders.len() - 1);
+                    parse_rsx(&format!("{event}: move |_| {{ {ident} }}"))
+                        .expect("event handler stubs are valid RSX")
+                }
+            };
+        }
+
+        // Keep rosetta's attribute order now that the names are final
+        if changed {
+            element
+                .raw_attributes
+                .sort_by_key(|attribute| attribute.name.to_string());
+        }
+        restore_marked(&mut element.children, marked, placeholders);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Options, convert};
+
+    const ALPINE: &str = r#"
+        [[rule]]
+        match = "@*"
+        action = "event"
+        to = "on*"
+
+        [[rule]]
+        match = "x-on:*"
+        action = "event"
+        to = "on*"
+
+        [[rule]]
+        match = ":*"
+        action = "custom"
+        to = "x-bind:*"
+
+        [[rule]]
+        match = "x-*"
+        action = "custom"
+
+        [[rule]]
+        match = "hx-*"
+        action = "custom"
+
+        [[rule]]
+        match = "v-cloak"
+        action = "drop"
+
+        [[rule]]
+        match = "data-role"
+        action = "rename"
+        to = "role"
+    "#;
+
+    fn convert_with(html: &str, config: &str) -> String {
+        let options = Options {
+            rules: parse_rules(config).unwrap(),
+            ..Options::default()
+        };
+        convert(html, &options).unwrap()
+    }
+
+    #[test]
+    fn parses_every_action() {
+        let rules = parse_rules(ALPINE).unwrap();
+        assert_eq!(rules.len(), 7);
+        assert_eq!(rules[0].action, RewriteAction::Event("on*".to_string()));
+        assert_eq!(rules[3].action, RewriteAction::Custom(None));
+        assert_eq!(rules[5].action, RewriteAction::Drop);
+        assert_eq!(rules[6].action, RewriteAction::Rename("role".to_string()));
+    }
+
+    #[test]
+    fn rejects_bad_rules() {
+        for config in [
+            "[[rule]]\nmatch = \"a\"\naction = \"rename\"",
+            "[[rule]]\nmatch = \"a\"\naction = \"event\"\nto = \"click\"",
+            "[[rule]]\nmatch = \"*a\"\naction = \"drop\"",
+            "[[rule]]\nmatch = \"a\"\naction = \"explode\"",
+            "[[rule]]\nmatch = \"a\"\naction = \"drop\"\ncolor = \"red\"",
+        ] {
+            assert!(parse_rules(config).is_err(), "{config}");
+        }
+    }
+
+    #[test]
+    fn rewrites_alpine_attributes() {
+        let rsx = convert_with(
+            r#"<div x-data="{ open: false }" v-cloak data-role="menu"><button @click.prevent="open = !open" :class="{ active: open }">Toggle</button></div>"#,
+            ALPINE,
+        );
+        assert!(rsx.contains(r#""x-data": "{{ open: false }}""#), "{rsx}");
+        assert!(rsx.contains(r#"role: "menu""#), "{rsx}");
+        assert!(!rsx.contains("cloak"), "{rsx}");
+        assert!(
+            rsx.contains("onclick: move |_| { /* open = !open */ }"),
+            "{rsx}"
+        );
+        assert!(
+            rsx.contains(r#""x-bind:class": "{{ active: open }}""#),
+            "{rsx}"
+        );
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rsx = convert_with(
+            r#"<button hx-get="/items" x-on:click="load()">Load</button>"#,
+            ALPINE,
+        );
+        assert!(rsx.contains(r#""hx-get": "/items""#), "{rsx}");
+        assert!(rsx.contains("onclick: move |_|"), "{rsx}");
+    }
+}
@@ -0,0 +1,1199 @@
+//! Vue and Svelte single-file component input.
+//!
+//! Only the markup is converted: the `<template>` block of a `.vue` file, or everything outside
+//! `<script>` and `<style>` in a `.svelte` file. It is parsed with the JSX parser, since Svelte
+//! already writes expressions as `{expr}` and Vue's `{{ expr }}` reads as a braced expression, and
+//! Vue directives and Svelte blocks are then rewritten into the loops, `if` chains, attributes and
+//! handlers the JSX writer knows. The props declared in the script (`defineProps`, `export let`)
+//! become the signature of the `#[component]` the body is wrapped in.
+
+use crate::Options;
+use crate::component::{component_fn, component_name};
+use crate::emit::todo_expr;
+use crate::error::ConvertError;
+use crate::handlers::Conversion;
+use crate::js::{expr_to_rust, path_to_rust, snake_case};
+use crate::jsx::{
+    JsxAttribute, JsxElement, JsxNode, JsxValue, matching_brace, parse_html, split_top_level,
+    string_literal, strip_parens,
+};
+
+/// The `#[component]` a single-file component is converted into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ComponentSignature {
+    /// The component name: the `name` option of a Vue component, or `App`.
+    pub name: String,
+    /// The props inferred from the script, as a comma-separated parameter list such as
+    /// `title: String, #[props(default = 0.0)] count: f64`.
+    pub props: String,
+}
+
+/// A single-file component framework.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Framework {
+    Vue,
+    Svelte,
+}
+
+/// Converts a `.vue` or `.svelte` file to an RSX body and the signature of its component.
+pub(crate) fn convert_sfc(
+    source: &str,
+    framework: Framework,
+    options: &Options,
+) -> Result<Conversion, ConvertError> {
+    let blocks = top_level_blocks(source);
+    let script: String = blocks
+        .iter()
+        .filter(|block| block.tag == "script")
+        .map(|block| &source[block.content.clone()])
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    // Everything but the markup is blanked out, so parse errors still point into the file
+    let markup = match framework {
+        Framework::Vue => {
+            let template = blocks
+                .iter()
+                .find(|block| block.tag == "template")
+                .ok_or_else(|| ConvertError::Parse {
+                    message: "no <template> block found".to_string(),
+                    span: None,
+                })?;
+            blank(source, |offset| !template.content.contains(&offset))
+        }
+        Framework::Svelte => blank(source, |offset| {
+            blocks
+                .iter()
+                .any(|block| block.tag != "template" && block.outer.contains(&offset))
+        }),
+    };
+
+    let mut rewriter = Rewriter { slot: false };
+    let nodes = parse_html(&markup)?;
+    let nodes = match framework {
+        Framework::Vue => rewriter.vue_nodes(nodes)?,
+        Framework::Svelte => rewriter.svelte_nodes(nodes)?,
+    };
+    let mut conversion = crate::jsx::write_nodes(&nodes, options)?;
+
+    let mut props: Vec<String> = match framework {
+        Framework::Vue => vue_props(&script),
+        Framework::Svelte => svelte_props(&script),
+    }
+    .into_iter()
+    .map(|mut prop| {
+        if prop.ty.is_none() {
+            prop.ty = usage_type(&nodes, &prop.name);
+        }
+        prop.render()
+    })
+    .collect();
+    if rewriter.slot {
+        props.push("children: Element".to_string());
+    }
+    let name = match framework {
+        Framework::Vue => vue_name(&script),
+        Framework::Svelte => None,
+    };
+    let signature = ComponentSignature {
+        name: component_name(name.as_deref().unwrap_or("App")),
+        props: props.join(", "),
+    };
+
+    // Make sure the wrapped component formats before handing it out
+    component_fn(&signature.name, &signature.props, &conversion.rsx)?;
+    conversion.component = Some(signature);
+    Ok(conversion)
+}
+
+/// A top-level `<script>`, `<style>` or `<template>` block.
+struct Block {
+    tag: &'static str,
+    /// From the opening `<` to the end of the closing tag.
+    outer: std::ops::Range<usize>,
+    content: std::ops::Range<usize>,
+}
+
+/// Finds the top-level `<script>`, `<style>` and `<template>` blocks of a single-file component.
+fn top_level_blocks(source: &str) -> Vec<Block> {
+    let lower = source.to_ascii_lowercase();
+    let mut blocks = Vec::new();
+    let mut pos = 0;
+    while let Some(found) = lower[pos..].find('<') {
+        let start = pos + found;
+        let rest = &lower[start..];
+        if rest.starts_with("<!--") {
+            pos = rest.find("-->").map_or(source.len(), |end| start + end + 3);
+            continue;
+        }
+
+        let Some(tag) = ["script", "style", "template"].into_iter().find(|tag| {
+            rest[1..].starts_with(tag)
+                && rest[1 + tag.len()..].starts_with(|c: char| c == '>' || c.is_whitespace())
+        }) else {
+            pos = start + 1;
+            continue;
+        };
+        let Some(open_end) = rest.find('>').map(|end| start + end + 1) else {
+            break;
+        };
+
+        // Templates can nest, scripts and styles can't
+        let close = format!("</{tag}");
+        let mut depth = 1;
+        let mut cursor = open_end;
+        let content_end = loop {
+            let next_open = lower[cursor..].find(&format!("<{tag}")).map(|i| cursor + i);
+            let Some(next_close) = lower[cursor..].find(&close).map(|i| cursor + i) else {
+                break source.len();
+            };
+            match next_open {
+                Some(open) if tag == "template" && open < next_close => {
+                    depth += 1;
+                    cursor = open + 1;
+                }
+                _ => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break next_close;
+                    }
+                    cursor = next_close + 1;
+                }
+            }
+        };
+        let end = lower[content_end..]
+            .find('>')
+            .map_or(source.len(), |end| content_end + end + 1);
+
+        blocks.push(Block {
+            tag,
+            outer: start..end,
+            content: open_end..content_end,
+        });
+        pos = end;
+    }
+    blocks
+}
+
+/// Replaces every character at an offset for which `hide` holds with a space, keeping newlines so
+/// line numbers stay the same.
+fn blank(source: &str, hide: impl Fn(usize) -> bool) -> String {
+    source
+        .char_indices()
+        .map(|(offset, c)| match c {
+            '\n' => '\n',
+            _ if hide(offset) => ' ',
+            c => c,
+        })
+        .collect()
+}
+
+/// Whether an element of a Vue `v-if` chain starts, continues or ends the chain.
+enum Branch {
+    If(String),
+    ElseIf(String),
+    Else,
+}
+
+struct Rewriter {
+    /// Whether the markup has a `<slot>`, which becomes the `children` prop.
+    slot: bool,
+}
+
+impl Rewriter {
+    fn vue_nodes(&mut self, nodes: Vec<JsxNode>) -> Result<Vec<JsxNode>, ConvertError> {
+        let mut out: Vec<JsxNode> = Vec::new();
+        for node in nodes {
+            match node {
+                JsxNode::Element(element) => {
+                    let (node, branch) = self.vue_element(element)?;
+                    let (cond, node) = match branch {
+                        None => {
+                            out.push(node);
+                            continue;
+                        }
+                        Some(Branch::If(cond)) => {
+                            out.push(JsxNode::If(vec![(Some(cond), vec![node])]));
+                            continue;
+                        }
+                        Some(Branch::ElseIf(cond)) => (Some(cond), node),
+                        Some(Branch::Else) => (None, node),
+                    };
+
+                    // `v-else` belongs to the `v-if` chain of the previous element
+                    while matches!(out.last(), Some(JsxNode::Text(text)) if text.trim().is_empty())
+                    {
+                        out.pop();
+                    }
+                    match out.last_mut() {
+                        Some(JsxNode::If(branches))
+                            if branches.last().is_some_and(|(cond, _)| cond.is_some()) =>
+                        {
+                            branches.push((cond, vec![node]));
+                        }
+                        _ => {
+                            return Err(ConvertError::Parse {
+                                message: "`v-else` without a `v-if` before it".to_string(),
+                                span: None,
+                            });
+                        }
+                    }
+                }
+                // `{{ expr }}` is read as a braced `{ expr }`
+                JsxNode::Expr(expr) => match expr
+                    .trim()
+                    .strip_prefix('{')
+                    .and_then(|expr| expr.strip_suffix('}'))
+                {
+                    Some(expr) => out.push(JsxNode::Expr(expr.trim().to_string())),
+                    None => out.push(JsxNode::Text(format!("{{{expr}}}"))),
+                },
+                JsxNode::Fragment(children) => {
+                    out.push(JsxNode::Fragment(self.vue_nodes(children)?))
+                }
+                other => out.push(other),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Rewrites the directives of an element, returning it (wrapped in a loop for `v-for`) and its
+    /// place in a `v-if` chain.
+    fn vue_element(
+        &mut self,
+        element: JsxElement,
+    ) -> Result<(JsxNode, Option<Branch>), ConvertError> {
+        let mut children = self.vue_nodes(element.children)?;
+        let mut attributes = Vec::new();
+        let mut branch = None;
+        let mut each = None;
+
+        for attribute in element.attributes {
+            let JsxAttribute::Named { name, value } = attribute else {
+                attributes.push(attribute);
+                continue;
+            };
+            let code = value_source(&value);
+
+            let (directive, argument) = match name.chars().next() {
+                Some(':') => ("v-bind", Some(&name[1..])),
+                Some('@') => ("v-on", Some(&name[1..])),
+                Some('#') => ("v-slot", Some(&name[1..])),
+                _ if name.starts_with("v-") => match name.split_once(':') {
+                    Some((directive, argument)) => (directive, Some(argument)),
+                    None => (name.as_str(), None),
+                },
+                _ => {
+                    attributes.push(JsxAttribute::Named { name, value });
+                    continue;
+                }
+            };
+            // Modifiers like `@click.prevent` and `v-model.trim` have no RSX equivalent
+            let directive = directive.split('.').next().unwrap_or_default();
+            let argument = argument.and_then(|argument| argument.split('.').next());
+
+            match (directive, argument) {
+                ("v-if", _) => branch = Some(Branch::If(condition(&code))),
+                ("v-else-if", _) => branch = Some(Branch::ElseIf(condition(&code))),
+                ("v-else", _) => branch = Some(Branch::Else),
+                ("v-for", _) => each = Some(code),
+                ("v-show", _) => attributes.push(named("hidden", format!("!({code})"))),
+                ("v-html", _) => attributes.push(named(
+                    "dangerouslySetInnerHTML",
+                    format!("{{ __html: {code} }}"),
+                )),
+                ("v-text", _) => children = vec![JsxNode::Expr(code)],
+                ("v-model", argument) => attributes.extend(two_way_binding(
+                    argument.unwrap_or("value"),
+                    &code,
+                    "$event",
+                )),
+                ("v-bind", Some(argument)) => attributes.push(named(argument, code)),
+                ("v-bind", None) => attributes.push(JsxAttribute::Spread(code)),
+                ("v-on", Some(event)) => attributes.push(named(&event_prop(event), code)),
+                // Slots, `v-once`, `v-pre`, `v-cloak` and custom directives
+                _ => {}
+            }
+        }
+
+        let node = match element.name.as_str() {
+            "template" => JsxNode::Fragment(children),
+            "slot" => {
+                self.slot = true;
+                JsxNode::Expr("children".to_string())
+            }
+            _ => JsxNode::Element(JsxElement {
+                name: element.name,
+                attributes,
+                children,
+            }),
+        };
+        let node = match each {
+            Some(each) => vue_for(&each, node),
+            None => node,
+        };
+        Ok((node, branch))
+    }
+
+    fn svelte_nodes(&mut self, nodes: Vec<JsxNode>) -> Result<Vec<JsxNode>, ConvertError> {
+        let mut nodes = nodes.into_iter();
+        let (out, end) = self.svelte_block(&mut nodes)?;
+        match end {
+            Some(tag) => Err(unexpected(&tag)),
+            None => Ok(out),
+        }
+    }
+
+    /// Rewrites nodes up to the next `{:...}` or `{/...}` tag, which is returned with them.
+    fn svelte_block(
+        &mut self,
+        nodes: &mut impl Iterator<Item = JsxNode>,
+    ) -> Result<(Vec<JsxNode>, Option<String>), ConvertError> {
+        let mut out = Vec::new();
+        while let Some(node) = nodes.next() {
+            let tag = match node {
+                JsxNode::Expr(expr) => expr.trim().to_string(),
+                JsxNode::Element(element) => {
+                    out.push(self.svelte_element(element)?);
+                    continue;
+                }
+                JsxNode::Fragment(children) => {
+                    out.push(JsxNode::Fragment(self.svelte_nodes(children)?));
+                    continue;
+                }
+                other => {
+                    out.push(other);
+                    continue;
+                }
+            };
+
+            if tag.starts_with([':', '/']) {
+                return Ok((out, Some(tag)));
+            }
+            let (keyword, header) = tag
+                .split_once(char::is_whitespace)
+                .map_or((tag.as_str(), ""), |(keyword, header)| {
+                    (keyword, header.trim())
+                });
+
+            match keyword {
+                "#if" => out.push(self.svelte_if(header, nodes)?),
+                "#each" => out.extend(self.svelte_each(header, nodes)?),
+                "#key" => {
+                    let (body, end) = self.svelte_block(nodes)?;
+                    expect_end(end, "/key")?;
+                    out.extend(body);
+                }
+                _ if keyword.starts_with('#') => {
+                    // `{#await}` and `{#snippet}` keep their content with the tags as comments
+                    let closing = format!("/{}", &keyword[1..]);
+                    out.push(JsxNode::Comment(format!("{{{tag}}}")));
+                    loop {
+                        let (body, end) = self.svelte_block(nodes)?;
+                        out.extend(body);
+                        let end = end.ok_or_else(|| unclosed(&tag))?;
+                        out.push(JsxNode::Comment(format!("{{{end}}}")));
+                        if end == closing {
+                            break;
+                        }
+                    }
+                }
+                "@html" => out.push(JsxNode::Element(raw_html(header))),
+                _ if keyword.starts_with('@') => out.push(JsxNode::Comment(format!("{{{tag}}}"))),
+                _ => out.push(JsxNode::Expr(tag)),
+            }
+        }
+        Ok((out, None))
+    }
+
+    fn svelte_if(
+        &mut self,
+        cond: &str,
+        nodes: &mut impl Iterator<Item = JsxNode>,
+    ) -> Result<JsxNode, ConvertError> {
+        let mut branches = Vec::new();
+        let mut cond = Some(condition(cond));
+        loop {
+            let (body, end) = self.svelte_block(nodes)?;
+            branches.push((cond.take(), body));
+            let end = end.ok_or_else(|| unclosed("#if"))?;
+            match end.strip_prefix(":else") {
+                _ if end == "/if" => break,
+                Some(rest) if branches.last().is_some_and(|(cond, _)| cond.is_some()) => {
+                    match rest.trim().strip_prefix("if ") {
+                        Some(next) => cond = Some(condition(next)),
+                        None => cond = None,
+                    }
+                }
+                _ => return Err(unexpected(&end)),
+            }
+        }
+        Ok(JsxNode::If(branches))
+    }
+
+    /// Rewrites `{#each items as item, index (key)}`, with an `{:else}` branch becoming an `if`
+    /// after the loop.
+    fn svelte_each(
+        &mut self,
+        header: &str,
+        nodes: &mut impl Iterator<Item = JsxNode>,
+    ) -> Result<Vec<JsxNode>, ConvertError> {
+        let (items, binding) = header.split_once(" as ").unwrap_or((header, "item"));
+        let (binding, key) = match binding.trim().strip_suffix(')') {
+            Some(keyed) => match keyed.rsplit_once('(') {
+                Some((binding, key)) => (binding.trim(), Some(key.trim())),
+                None => (binding.trim(), None),
+            },
+            None => (binding.trim(), None),
+        };
+        let params: Vec<&str> = binding.split(',').map(str::trim).collect();
+
+        let (mut body, end) = self.svelte_block(nodes)?;
+        if let Some(key) = key {
+            for node in &mut body {
+                if let JsxNode::Element(element) = node {
+                    element.attributes.push(named("key", key.to_string()));
+                }
+            }
+        }
+        let mut out = vec![for_loop(items, &params, body)];
+
+        let end = end.ok_or_else(|| unclosed("#each"))?;
+        if end == ":else" {
+            let (empty, end) = self.svelte_block(nodes)?;
+            expect_end(end, "/each")?;
+            let cond = match path_to_rust(items) {
+                Some(items) => format!("{items}.is_empty()"),
+                None => todo_expr(items),
+            };
+            out.push(JsxNode::If(vec![(Some(cond), empty)]));
+        } else if end != "/each" {
+            return Err(unexpected(&end));
+        }
+        Ok(out)
+    }
+
+    fn svelte_element(&mut self, element: JsxElement) -> Result<JsxNode, ConvertError> {
+        let mut children = self.svelte_nodes(element.children)?;
+        let mut attributes = Vec::new();
+
+        // `<div>{@html markup}</div>` sets the inner HTML of the div instead of a wrapper
+        let meaningful: Vec<&JsxNode> = children
+            .iter()
+            .filter(|child| !matches!(child, JsxNode::Text(text) if text.trim().is_empty()))
+            .collect();
+        if let [JsxNode::Element(wrapper)] = meaningful.as_slice()
+            && wrapper.name == RAW_HTML_WRAPPER
+            && let [_, inner_html] = wrapper.attributes.as_slice()
+        {
+            attributes.push(inner_html.clone());
+            children.clear();
+        }
+
+        for attribute in element.attributes {
+            let JsxAttribute::Named { name, value } = attribute else {
+                attributes.push(attribute);
+                continue;
+            };
+            let Some((directive, argument)) = name.split_once(':') else {
+                // `class="btn {variant}"` interpolates like a template literal
+                match value {
+                    JsxValue::Str(text) if text.contains('{') => {
+                        attributes.push(named(&name, template_literal(&text)));
+                    }
+                    value => attributes.push(JsxAttribute::Named { name, value }),
+                }
+                continue;
+            };
+            let argument = argument.split('|').next().unwrap_or_default();
+            // `class:active` is short for `class:active={active}`
+            let code = match &value {
+                JsxValue::True => argument.to_string(),
+                value => value_source(value),
+            };
+
+            match directive {
+                "on" => attributes.push(named(&event_prop(argument), code)),
+                "bind" if argument == "this" => {}
+                "bind" => attributes.extend(two_way_binding(argument, &code, "event")),
+                "class" => attributes.push(named("class", format!("{code} ? '{argument}' : ''"))),
+                "style" => attributes.push(named(
+                    "style",
+                    template_literal(&format!(
+                        "{argument}: {}",
+                        match &value {
+                            JsxValue::Str(text) => text.clone(),
+                            _ => format!("{{{code}}}"),
+                        }
+                    )),
+                )),
+                // Actions, transitions and animations
+                "use" | "transition" | "in" | "out" | "animate" => {}
+                _ => attributes.push(JsxAttribute::Named { name, value }),
+            }
+        }
+
+        Ok(match element.name.as_str() {
+            "slot" => {
+                self.slot = true;
+                JsxNode::Expr("children".to_string())
+            }
+            "svelte:fragment" => JsxNode::Fragment(children),
+            name if name.starts_with("svelte:") => {
+                JsxNode::Comment(format!("<{name}> has no RSX equivalent"))
+            }
+            _ => JsxNode::Element(JsxElement {
+                name: element.name,
+                attributes,
+                children,
+            }),
+        })
+    }
+}
+
+/// The element `{@html markup}` is written as unless it is the only child of an element.
+const RAW_HTML_WRAPPER: &str = "span";
+
+/// Wraps `{@html markup}` in an element that sets its inner HTML, without a box of its own.
+fn raw_html(markup: &str) -> JsxElement {
+    JsxElement {
+        name: RAW_HTML_WRAPPER.to_string(),
+        attributes: vec![
+            JsxAttribute::Named {
+                name: "style".to_string(),
+                value: JsxValue::Str("display: contents".to_string()),
+            },
+            named("dangerouslySetInnerHTML", format!("{{ __html: {markup} }}")),
+        ],
+        children: Vec::new(),
+    }
+}
+
+fn named(name: &str, expr: String) -> JsxAttribute {
+    JsxAttribute::Named {
+        name: name.to_string(),
+        value: JsxValue::Expr(expr),
+    }
+}
+
+fn value_source(value: &JsxValue) -> String {
+    match value {
+        JsxValue::True => String::new(),
+        JsxValue::Str(text) | JsxValue::Expr(text) => text.trim().to_string(),
+    }
+}
+
+/// The JSX prop for an event, e.g. `click` -> `onClick`.
+fn event_prop(event: &str) -> String {
+    let mut chars = event.chars();
+    match chars.next() {
+        Some(first) => format!("on{}{}", first.to_ascii_uppercase(), chars.as_str()),
+        None => "on".to_string(),
+    }
+}
+
+/// `v-model` and `bind:value` set the attribute and write it back on input. The write-back can't
+/// be translated mechanically, so it becomes a handler stub.
+fn two_way_binding(property: &str, code: &str, event: &str) -> [JsxAttribute; 2] {
+    let handler = match property {
+        "checked" => format!("{code} = {event}.target.checked"),
+        _ => format!("{code} = {event}.target.value"),
+    };
+    [
+        named(property, code.to_string()),
+        JsxAttribute::Named {
+            name: "onInput".to_string(),
+            value: JsxValue::Str(handler),
+        },
+    ]
+}
+
+/// Turns Svelte's `text {expr}` attribute interpolation into a JavaScript template literal, which
+/// the JSX writer knows how to format.
+fn template_literal(text: &str) -> String {
+    format!("`{}`", text.replace('`', "\\`").replace('{', "${"))
+}
+
+fn condition(code: &str) -> String {
+    expr_to_rust(code).unwrap_or_else(|| todo_expr(code))
+}
+
+/// Wraps a node in the loop of a `v-for="(item, index) in items"`.
+fn vue_for(each: &str, node: JsxNode) -> JsxNode {
+    let (params, items) = each
+        .split_once(" in ")
+        .or_else(|| each.split_once(" of "))
+        .unwrap_or(("item", each));
+    let params: Vec<&str> = strip_parens(params.trim())
+        .split(',')
+        .map(str::trim)
+        .collect();
+    for_loop(items, &params, vec![node])
+}
+
+/// Builds a loop over `items` with one or two (item and index) parameters.
+fn for_loop(items: &str, params: &[&str], body: Vec<JsxNode>) -> JsxNode {
+    let items = items.trim();
+    let params: Vec<String> = params
+        .iter()
+        .map(|param| path_to_rust(param).unwrap_or_else(|| "item".to_string()))
+        .collect();
+
+    // `v-for="n in 10"` counts from 1
+    let (iter, pattern) = if let Ok(count) = items.parse::<u32>() {
+        (format!("1..={count}"), params[0].clone())
+    } else {
+        let items = path_to_rust(items)
+            .map(|items| format!("{items}.iter()"))
+            .unwrap_or_else(|| todo_expr(items));
+        match params.as_slice() {
+            [item, index, ..] => (format!("{items}.enumerate()"), format!("({index}, {item})")),
+            _ => (items, params[0].clone()),
+        }
+    };
+
+    JsxNode::For {
+        pattern,
+        iter,
+        body,
+    }
+}
+
+fn expect_end(end: Option<String>, expected: &str) -> Result<(), ConvertError> {
+    match end {
+        Some(end) if end == expected => Ok(()),
+        Some(end) => Err(unexpected(&end)),
+        None => Err(unclosed(expected)),
+    }
+}
+
+fn unexpected(tag: &str) -> ConvertError {
+    ConvertError::Parse {
+        message: format!("unexpected `{{{tag}}}`"),
+        span: None,
+    }
+}
+
+fn unclosed(tag: &str) -> ConvertError {
+    ConvertError::Parse {
+        message: format!("`{{{tag}}}` block is never closed"),
+        span: None,
+    }
+}
+
+/// Written for props whose type neither the script nor the markup gives away, so the guess is
+/// flagged for review.
+const UNKNOWN_TYPE: &str = "/* TODO: type */ String";
+
+/// Written for props the markup loops over without saying what they contain.
+const UNKNOWN_LIST_TYPE: &str = "Vec</* TODO: item type */ String>";
+
+/// Works out the type of an undeclared prop from how the markup uses it: looping over it makes it a
+/// list, and using it as a condition on its own makes it a `bool`.
+fn usage_type(nodes: &[JsxNode], name: &str) -> Option<String> {
+    nodes.iter().find_map(|node| match node {
+        JsxNode::Element(element) => usage_type(&element.children, name),
+        JsxNode::Fragment(children) => usage_type(children, name),
+        JsxNode::For { iter, body, .. } => {
+            if iter
+                .strip_prefix(name)
+                .is_some_and(|rest| rest.starts_with(".iter()"))
+            {
+                Some(UNKNOWN_LIST_TYPE.to_string())
+            } else {
+                usage_type(body, name)
+            }
+        }
+        JsxNode::If(branches) => branches.iter().find_map(|(condition, body)| {
+            let condition = condition.as_deref().map(str::trim);
+            if condition.is_some_and(|condition| condition.trim_start_matches('!') == name) {
+                Some("bool".to_string())
+            } else {
+                usage_type(body, name)
+            }
+        }),
+        JsxNode::Text(_) | JsxNode::Expr(_) | JsxNode::Comment(_) => None,
+    })
+}
+
+/// A prop declared in a component script.
+struct Prop {
+    name: String,
+    /// The Rust type, or `None` if it isn't declared and can't be inferred.
+    ty: Option<String>,
+    /// The default in Rust, when it is a literal.
+    default: Option<String>,
+    /// Whether the prop can be left out, because it has a default or is marked optional.
+    optional: bool,
+}
+
+impl Prop {
+    fn new(name: &str) -> Self {
+        Self {
+            name: snake_case(name.trim().trim_matches(['"', '\''])),
+            ty: None,
+            default: None,
+            optional: false,
+        }
+    }
+
+    /// Sets the default from its JavaScript source.
+    fn default_from(&mut self, js: &str) {
+        let js = js.trim();
+        self.optional = true;
+        if self.ty.is_none() {
+            self.ty = infer_type(js);
+        }
+        self.default = match (self.ty.as_deref(), js) {
+            (_, "true" | "false") => Some(js.to_string()),
+            (Some("f64"), number) if number.parse::<f64>().is_ok() => {
+                Some(if number.contains('.') {
+                    number.to_string()
+                } else {
+                    format!("{number}.0")
+                })
+            }
+            (Some("String"), _) => string_literal(js).map(|text| format!("{text:?}.to_string()")),
+            _ => None,
+        };
+    }
+
+    fn render(&self) -> String {
+        let ty = self.ty.as_deref().unwrap_or(UNKNOWN_TYPE);
+        // Types the script declares itself may not implement `Default`
+        let defaultable =
+            matches!(ty, "String" | "f64" | "bool" | UNKNOWN_TYPE) || ty.starts_with("Vec<");
+        match (&self.default, self.optional) {
+            (Some(default), _) => format!("#[props(default = {default})] {}: {ty}", self.name),
+            (None, true) if defaultable => format!("#[props(default)] {}: {ty}", self.name),
+            (None, true) => format!("{}: Option<{ty}>", self.name),
+            (None, false) => format!("{}: {ty}", self.name),
+        }
+    }
+}
+
+/// Maps a TypeScript type or Vue prop constructor to a Rust type.
+fn rust_type(ts: &str) -> Option<String> {
+    let ts = ts.trim();
+    if let Some(item) = ts.strip_suffix("[]").or_else(|| {
+        ts.strip_prefix("Array<")
+            .and_then(|ts| ts.strip_suffix('>'))
+    }) {
+        return Some(format!("Vec<{}>", rust_type(item)?));
+    }
+    Some(match ts {
+        "string" | "String" => "String".to_string(),
+        "number" | "Number" => "f64".to_string(),
+        "boolean" | "Boolean" => "bool".to_string(),
+        "Array" => "Vec<String>".to_string(),
+        "Function" => "EventHandler".to_string(),
+        "Object" => return None,
+        _ if ts.contains("=>") => "EventHandler".to_string(),
+        // Unions of string literals
+        _ if ts.split('|').all(|part| string_literal(part).is_some()) => "String".to_string(),
+        // Interfaces and other named types are expected to be ported with the same name
+        _ if ts.starts_with(|c: char| c.is_ascii_uppercase())
+            && ts.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+        {
+            ts.to_string()
+        }
+        _ => return None,
+    })
+}
+
+/// Infers a Rust type from a default value.
+fn infer_type(js: &str) -> Option<String> {
+    let js = js.trim();
+    if js == "true" || js == "false" {
+        Some("bool".to_string())
+    } else if js.parse::<f64>().is_ok() {
+        Some("f64".to_string())
+    } else if string_literal(js).is_some() || js.starts_with('`') {
+        Some("String".to_string())
+    } else if js.starts_with('[') {
+        Some("Vec<String>".to_string())
+    } else {
+        None
+    }
+}
+
+/// Splits the inside of an object literal or TypeScript type into its entries.
+fn entries(inner: &str) -> Vec<&str> {
+    split_top_level(inner, ',')
+        .into_iter()
+        .flat_map(|entry| split_top_level(entry, ';'))
+        .flat_map(|entry| entry.lines())
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty() && !entry.starts_with("//"))
+        .collect()
+}
+
+/// Returns the contents of the `{...}` starting at or after `from`.
+fn braced_after(script: &str, from: usize) -> Option<&str> {
+    let open = script[from..].find('{')? + from;
+    let close = matching_brace(script, open)?;
+    Some(&script[open + 1..close])
+}
+
+/// Reads the props of a Vue component from `defineProps` (with `withDefaults`), or from the
+/// `props` option of the Options API.
+fn vue_props(script: &str) -> Vec<Prop> {
+    let mut props = match script.find("defineProps") {
+        Some(define) => {
+            let rest = script[define + "defineProps".len()..].trim_start();
+            let call = script.len() - rest.len();
+            match rest.chars().next() {
+                // `defineProps<{ title: string }>()` or `defineProps<Props>()`
+                Some('<') => {
+                    let generic = &rest[1..rest.find('>').unwrap_or(1)];
+                    let inner = if generic.trim_start().starts_with('{') {
+                        braced_after(script, call)
+                    } else {
+                        type_declaration(script, generic.trim())
+                    };
+                    inner.map(typed_props).unwrap_or_default()
+                }
+                _ => runtime_props(&rest[1..]),
+            }
+        }
+        None => match script.find("props:") {
+            Some(option) => runtime_props(script[option + "props:".len()..].trim_start()),
+            None => Vec::new(),
+        },
+    };
+
+    // `withDefaults(defineProps<...>(), { count: 0 })`
+    if let Some(with) = script.find("withDefaults") {
+        let call = &script[with..];
+        if let Some(after_props) = call.find(">()").or_else(|| call.find(')'))
+            && let Some(defaults) = braced_after(script, with + after_props)
+        {
+            for entry in entries(defaults) {
+                let Some((name, value)) = entry.split_once(':') else {
+                    continue;
+                };
+                let name = snake_case(name.trim());
+                if let Some(prop) = props.iter_mut().find(|prop| prop.name == name) {
+                    prop.default_from(value);
+                }
+            }
+        }
+    }
+
+    props
+}
+
+/// Finds the body of `interface Name { ... }` or `type Name = { ... }`.
+fn type_declaration<'a>(script: &'a str, name: &str) -> Option<&'a str> {
+    let declaration = [format!("interface {name}"), format!("type {name}")]
+        .iter()
+        .find_map(|declaration| script.find(declaration.as_str()))?;
+    braced_after(script, declaration)
+}
+
+/// Reads TypeScript prop declarations like `title: string; count?: number`.
+fn typed_props(inner: &str) -> Vec<Prop> {
+    entries(inner)
+        .into_iter()
+        .filter_map(|entry| {
+            let (name, ty) = entry.split_once(':')?;
+            let optional = name.trim().ends_with('?');
+            let mut prop = Prop::new(name.trim().trim_end_matches('?'));
+            prop.ty = rust_type(ty);
+            prop.optional = optional;
+            Some(prop)
+        })
+        .collect()
+}
+
+/// Reads runtime prop declarations: `['title']` or `{ title: String, count: { type: Number,
+/// default: 0 } }`.
+fn runtime_props(argument: &str) -> Vec<Prop> {
+    let argument = argument.trim_start();
+    if let Some(list) = argument.strip_prefix('[') {
+        let list = &list[..list.find(']').unwrap_or(list.len())];
+        return list
+            .split(',')
+            .filter_map(string_literal)
+            .map(|name| Prop::new(&name))
+            .collect();
+    }
+    let Some(inner) = braced_after(argument, 0) else {
+        return Vec::new();
+    };
+
+    entries(inner)
+        .into_iter()
+        .filter_map(|entry| {
+            let (name, definition) = entry.split_once(':')?;
+            let mut prop = Prop::new(name);
+            let definition = definition.trim();
+            let Some(options) = definition
+                .strip_prefix('{')
+                .and_then(|options| options.strip_suffix('}'))
+            else {
+                // Props without `required: true` are optional in Vue
+                prop.ty = rust_type(definition);
+                prop.optional = true;
+                return Some(prop);
+            };
+
+            for option in entries(options) {
+                match option.split_once(':') {
+                    Some(("type", ty)) => prop.ty = rust_type(ty),
+                    Some(("required", required)) => prop.optional = required.trim() != "true",
+                    Some(("default", default)) => prop.default_from(default),
+                    _ => {}
+                }
+            }
+            if !options.contains("required") {
+                prop.optional = true;
+            }
+            Some(prop)
+        })
+        .collect()
+}
+
+/// Reads the name of a Vue component from `defineOptions({ name })` or the `name` option.
+fn vue_name(script: &str) -> Option<String> {
+    let options = ["defineOptions", "export default"]
+        .iter()
+        .find_map(|call| braced_after(script, script.find(call)?))?;
+    entries(options).into_iter().find_map(|entry| {
+        let (key, value) = entry.split_once(':')?;
+        (key.trim() == "name").then(|| string_literal(value))?
+    })
+}
+
+/// Reads Svelte props: `export let title: string;` and `let { title, count = 0 } = $props();`.
+fn svelte_props(script: &str) -> Vec<Prop> {
+    let mut props = Vec::new();
+
+    for statement in script.split([';', '\n']).map(str::trim) {
+        let Some(declaration) = statement.strip_prefix("export let ") else {
+            continue;
+        };
+        let (declaration, default) = match declaration.split_once('=') {
+            Some((declaration, default)) => (declaration, Some(default)),
+            None => (declaration, None),
+        };
+        let (name, ty) = match declaration.split_once(':') {
+            Some((name, ty)) => (name, rust_type(ty)),
+            None => (declaration, None),
+        };
+        let mut prop = Prop::new(name);
+        prop.ty = ty;
+        if let Some(default) = default {
+            prop.default_from(default);
+        }
+        props.push(prop);
+    }
+
+    // Svelte 5 runes
+    if let Some(runes) = script.find("$props()") {
+        let declaration = &script[..runes];
+        if let Some(open) = declaration.rfind("let {") {
+            for entry in braced_after(declaration, open)
+                .map(entries)
+                .unwrap_or_default()
+            {
+                let (name, default) = match entry.split_once('=') {
+                    Some((name, default)) => (name, Some(default)),
+                    None => (entry, None),
+                };
+                if name.trim().starts_with("...") {
+                    continue;
+                }
+                let mut prop = Prop::new(name);
+                if let Some(default) = default {
+                    prop.default_from(default);
+                }
+                props.push(prop);
+            }
+        }
+    }
+
+    props
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Conversion, ConvertError, InputFormat, Options, convert_with_report};
+
+    fn convert_as(input: InputFormat, source: &str) -> Result<Conversion, ConvertError> {
+        let options = Options {
+            input,
+            ..Options::default()
+        };
+        convert_with_report(source, &options)
+    }
+
+    #[test]
+    fn vue_directives_become_control_flow() {
+        let conversion = convert_as(
+            InputFormat::Vue,
+            r#"<script setup lang="ts">
+const props = withDefaults(defineProps<{
+  title: string
+  items: string[]
+  size?: number
+}>(), { size: 2 })
+</script>
+
+<template>
+  <h2 v-if="title">{{ title }}</h2>
+  <p v-else>Untitled</p>
+  <ul>
+    <li v-for="(item, index) in items" :key="item" @click="select(item)">{{ index }}: {{ item }}</li>
+  </ul>
+  <input v-model="query">
+  <button @click.prevent="save">Save</button>
+</template>"#,
+        )
+        .unwrap();
+
+        let output = conversion.output();
+        for expected in [
+            "pub fn App(title: String, items: Vec<String>, #[props(default = 2.0)] size: f64) -> Element {",
+            "if title {",
+            r#"h2 { "{title}" }"#,
+            "} else {",
+            "for (index, item) in items.iter().enumerate() {",
+            r#"key: "{item}","#,
+            "onclick: move |_| { /* select(item) */ },",
+            "oninput: move |_| { /* query = $event.target.value */ }",
+            "button { onclick: move |evt| save(evt), \"Save\" }",
+        ] {
+            assert!(output.contains(expected), "missing {expected}:\n{output}");
+        }
+        assert_eq!(conversion.handlers.len(), 2);
+    }
+
+    #[test]
+    fn svelte_blocks_become_control_flow() {
+        let conversion = convert_as(
+            InputFormat::Svelte,
+            r#"<script>
+  export let todos = [];
+  export let title: string;
+</script>
+
+<h1 class="title {size}">{title}</h1>
+<ul>
+  {#each todos as todo (todo)}
+    <li class:done>{todo}</li>
+  {:else}
+    <li>Nothing to do</li>
+  {/each}
+</ul>
+{#if todos.length > 3}<p>Busy</p>{:else if todos.length}<p>Some</p>{/if}
+<slot />
+
+<style>h1 { color: red; }</style>"#,
+        )
+        .unwrap();
+
+        let output = conversion.output();
+        for expected in [
+            "pub fn App(#[props(default)] todos: Vec<String>, title: String, children: Element)",
+            r#"h1 { class: "title {size}", "{title}" }"#,
+            "for todo in todos.iter() {",
+            r#"class: if done { "done" } else { "" }"#,
+            r#"key: "{todo}""#,
+            "if todos.is_empty() {",
+            "if todos.len() > 3 {",
+            "} else if todos.len() {",
+            "{children}",
+        ] {
+            assert!(output.contains(expected), "missing {expected}:\n{output}");
+        }
+        assert!(!output.contains("color: red"), "{output}");
+    }
+
+    #[test]
+    fn untyped_props_are_inferred_from_usage() {
+        let conversion = convert_as(
+            InputFormat::Svelte,
+            r#"<script>
+  export let items;
+  export let open;
+  export let label;
+</script>
+
+{#if open}
+  <ul>{#each items as item}<li>{item}</li>{/each}</ul>
+{/if}
+<p>{label}</p>"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            conversion.component.as_ref().unwrap().props,
+            "items: Vec</* TODO: item type */ String>, open: bool, label: /* TODO: type */ String"
+        );
+        let output = conversion.output();
+        assert!(output.contains("for item in items.iter() {"), "{output}");
+        assert!(output.contains("if open {"), "{output}");
+    }
+
+    #[test]
+    fn svelte_raw_html_sets_inner_html() {
+        let output = convert_as(
+            InputFormat::Svelte,
+            "<article>{@html body}</article>\n<p>Intro {@html note} <br> end</p>",
+        )
+        .unwrap()
+        .output();
+        for expected in [
+            "article { dangerous_inner_html: body }",
+            r#"span { style: "display: contents", dangerous_inner_html: note }"#,
+            "br {}",
+        ] {
+            assert!(output.contains(expected), "missing {expected}:\n{output}");
+        }
+    }
+
+    #[test]
+    fn vue_options_api_props_and_name() {
+        let conversion = convert_as(
+            InputFormat::Vue,
+            r#"<template><span>{{ label }}</span></template>
+<script>
+export default {
+  name: 'user-badge',
+  props: {
+    label: String,
+    size: { type: Number, default: 1 },
+    active: { type: Boolean, required: true },
+    owner: Object,
+  },
+}
+</script>"#,
+        )
+        .unwrap();
+
+        let component = conversion.component.unwrap();
+        assert_eq!(component.name, "UserBadge");
+        assert_eq!(
+            component.props,
+            "#[props(default)] label: String, #[props(default = 1.0)] size: f64, active: bool, #[props(default)] owner: /* TODO: type */ String"
+        );
+    }
+
+    #[test]
+    fn malformed_components_are_reported() {
+        let error = convert_as(InputFormat::Vue, "<script>export default {}</script>").unwrap_err();
+        assert!(error.to_string().contains("no <template> block"), "{error}");
+
+        let error =
+            convert_as(InputFormat::Vue, "<template><p v-else>x</p></template>").unwrap_err();
+        assert!(error.to_string().contains("v-else"), "{error}");
+
+        let error =
+            convert_as(InputFormat::Svelte, "{#each items as item}<p>{item}</p>").unwrap_err();
+        assert!(error.to_string().contains("never closed"), "{error}");
+
+        let error = convert_as(InputFormat::Svelte, "<div>\n  <p>x</div>").unwrap_err();
+        assert_eq!(error.span().unwrap().start.line, 2);
+    }
+}
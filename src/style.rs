@@ -0,0 +1,179 @@
+//! Splitting inline `style` strings into individual RSX style attributes.
+
+use dioxus_core_types::HotReloadingContext;
+use dioxus_html::{HtmlCtx, map_html_attribute_to_rsx, map_html_element_to_rsx};
+use dioxus_rsx_rosetta::Node;
+
+/// Splits a CSS declaration block (`color: red; margin-top: 4px`) into `(property, value)` pairs.
+///
+/// Semicolons inside parentheses or quotes (`url(data:image/png;base64,...)`) do not split.
+pub(crate) fn parse_declarations(css: &str) -> Vec<(String, String)> {
+    let mut declarations = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in css.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth = depth.saturating_sub(1),
+            (None, ';') if depth == 0 => {
+                declarations.extend(parse_declaration(&css[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    declarations.extend(parse_declaration(&css[start..]));
+
+    declarations
+}
+
+fn parse_declaration(declaration: &str) -> Option<(String, String)> {
+    let (property, value) = declaration.split_once(':')?;
+    let property = property.trim();
+    let value = value.trim();
+    (!property.is_empty() && !value.is_empty()).then(|| (property.to_string(), value.to_string()))
+}
+
+/// Returns the RSX attribute for a CSS property on the given RSX element, if Dioxus exposes it as a
+/// style attribute there.
+///
+/// Properties that collide with a real attribute of the element (`width` on `img`, `fill` on
+/// `path`), custom properties and `!important` values have no typed form and return `None`.
+pub(crate) fn style_attribute(element: &str, property: &str, value: &str) -> Option<&'static str> {
+    if value.contains("!important") {
+        return None;
+    }
+
+    let rsx = map_html_attribute_to_rsx(&property.to_ascii_lowercase())?;
+    match HtmlCtx::map_attribute(element, rsx) {
+        Some((_, Some("style"))) => Some(rsx),
+        _ => None,
+    }
+}
+
+/// Joins declarations back into an inline style string.
+pub(crate) fn join_declarations<'a>(
+    declarations: impl IntoIterator<Item = &'a (String, String)>,
+) -> String {
+    declarations
+        .into_iter()
+        .map(|(property, value)| format!("{property}: {value}"))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Moves style declarations Dioxus knows about out of each element's `style` attribute and into
+/// attributes of their own, so rosetta emits `margin_top: "4px"` instead of one style string.
+///
+/// Declarations whose property the element already has an attribute for stay in the style string,
+/// so neither value is lost.
+pub(crate) fn split_dom_styles(nodes: &mut [Node]) {
+    for node in nodes {
+        let Node::Element(el) = node else {
+            continue;
+        };
+        split_dom_styles(&mut el.children);
+
+        let Some(element) = map_html_element_to_rsx(&el.name) else {
+            continue;
+        };
+        let Some(Some(css)) = el.attributes.remove("style") else {
+            continue;
+        };
+
+        let mut fallback = Vec::new();
+        for (property, value) in parse_declarations(&css) {
+            let name = property.to_ascii_lowercase();
+            match style_attribute(element, &property, &value) {
+                Some(_) if !el.attributes.contains_key(&name) => {
+                    el.attributes.insert(name, Some(value));
+                }
+                _ => fallback.push((property, value)),
+            }
+        }
+
+        if !fallback.is_empty() {
+            el.attributes
+                .insert("style".to_string(), Some(join_declarations(&fallback)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Options, convert};
+
+    fn split(html: &str) -> String {
+        let options = Options {
+            split_styles: true,
+            ..Options::default()
+        };
+        convert(html, &options).unwrap()
+    }
+
+    #[test]
+    fn declarations_split_outside_parentheses_and_quotes() {
+        assert_eq!(
+            parse_declarations(
+                "background: url(data:image/png;base64,AAA=) no-repeat; content: 'a;b'; font-family: \"x;y\", serif;; color :red ;"
+            ),
+            [
+                (
+                    "background".to_string(),
+                    "url(data:image/png;base64,AAA=) no-repeat".to_string()
+                ),
+                ("content".to_string(), "'a;b'".to_string()),
+                ("font-family".to_string(), "\"x;y\", serif".to_string()),
+                ("color".to_string(), "red".to_string()),
+            ]
+        );
+        assert!(parse_declarations("color; : red;").is_empty());
+    }
+
+    #[test]
+    fn known_properties_become_attributes() {
+        let rsx = split(r#"<p style="margin-top: 4px; color: red">x</p>"#);
+        assert!(rsx.contains(r#"color: "red""#), "{rsx}");
+        assert!(rsx.contains(r#"margin_top: "4px""#), "{rsx}");
+        assert!(!rsx.contains("style:"), "{rsx}");
+    }
+
+    #[test]
+    fn untyped_declarations_stay_in_the_style_string() {
+        let rsx = split(
+            r#"<div style="color: red !important; --gap: 4px; margin: 0"><img src="a.png" style="width: 10px; border: 0"></div>"#,
+        );
+        assert!(
+            rsx.contains(r#"style: "color: red !important; --gap: 4px""#),
+            "{rsx}"
+        );
+        assert!(rsx.contains(r#"margin: "0""#), "{rsx}");
+        assert!(rsx.contains(r#"style: "width: 10px""#), "{rsx}");
+        assert!(rsx.contains(r#"border: "0""#), "{rsx}");
+    }
+
+    #[test]
+    fn entities_are_decoded_before_splitting() {
+        let rsx =
+            split(r#"<p style="font-family: &quot;Inter&quot;, sans-serif; color: red">x</p>"#);
+        assert!(
+            rsx.contains(r#"font_family: "\"Inter\", sans-serif""#),
+            "{rsx}"
+        );
+        assert!(rsx.contains(r#"color: "red""#), "{rsx}");
+    }
+
+    #[test]
+    fn existing_attributes_are_not_overwritten() {
+        let rsx = split(r#"<div style="color: red; margin: 0" color="x">y</div>"#);
+        assert!(rsx.contains(r#"color: "x""#), "{rsx}");
+        assert!(rsx.contains(r#"style: "color: red""#), "{rsx}");
+        assert!(rsx.contains(r#"margin: "0""#), "{rsx}");
+    }
+}
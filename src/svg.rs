@@ -0,0 +1,325 @@
+//! SVG-specific conversion.
+//!
+//! Rosetta already maps SVG's camelCase and kebab-case attributes (`viewBox`, `stroke-width`,
+//! `fill-rule`) to their RSX names, but it drops the prefix of namespaced attributes, so
+//! `xlink:href` became a second `href` and `xmlns:xlink` became `xlink`. [`namespaced_attribute`]
+//! keeps them intact while preprocessing, and [`extract_icons`] turns `svg` elements into icon
+//! components with `class` and `size` props, passed the values from the markup at each call site.
+
+use crate::ConvertError;
+use crate::component::{component_fn, component_name};
+use crate::emit::{parse_rsx, quote_text};
+use crate::extract::unique_name;
+use crate::rules::{Marked, Rewrite};
+use crate::tokenizer::Tag;
+use dioxus_rsx::{
+    Attribute, AttributeName, AttributeValue, BodyNode, CallBody, Element, HotLiteral, TemplateBody,
+};
+use proc_macro2::{Ident, Span};
+
+/// Works out how to write a namespaced attribute like `xlink:href` or `xml:space`, which Dioxus has
+/// no typed attribute for. Returns `None` for attributes without a prefix.
+pub(crate) fn namespaced_attribute(html: &str, tag: &Tag, name: &str) -> Option<Rewrite> {
+    let (prefix, local) = name.split_once(':')?;
+
+    // SVG 2 reads plain `href`, which is also the only spelling Dioxus has an attribute for
+    if prefix.eq_ignore_ascii_case("xlink")
+        && local.eq_ignore_ascii_case("href")
+        && !tag
+            .attributes
+            .iter()
+            .any(|attribute| html[attribute.name.clone()].eq_ignore_ascii_case("href"))
+    {
+        return Some(Rewrite::Rename("href".to_string()));
+    }
+
+    Some(Rewrite::Mark(Marked::Custom(name.to_string())))
+}
+
+/// Replaces every `svg` element in `nodes` with an invocation of a generated icon component named
+/// after `name`, and returns the source of the components. Identical icons share a component.
+///
+/// The component takes a `class` prop and a `size` prop that sets both `width` and `height`, or
+/// separate `width` and `height` props for icons that aren't square. The values in the markup are
+/// passed at the call site, so icons that only differ in them share a component.
+pub(crate) fn extract_icons(
+    nodes: &mut [BodyNode],
+    name: &str,
+    names: &mut Vec<String>,
+) -> Result<Vec<String>, ConvertError> {
+    let mut icons = Icons {
+        base: component_name(name),
+        names,
+        generated: Vec::new(),
+        components: Vec::new(),
+    };
+    icons.extract(nodes)?;
+    Ok(icons.components)
+}
+
+struct Icons<'a> {
+    base: String,
+    names: &'a mut Vec<String>,
+    /// The props and body of every component generated so far, with its name.
+    generated: Vec<(String, String, String)>,
+    components: Vec<String>,
+}
+
+impl Icons<'_> {
+    fn extract(&mut self, nodes: &mut [BodyNode]) -> Result<(), ConvertError> {
+        for node in nodes {
+            let BodyNode::Element(element) = node else {
+                continue;
+            };
+            if element.name != "svg" {
+                self.extract(&mut element.children)?;
+                continue;
+            }
+
+            let Icon { props, rsx, call } = icon(element)?;
+            let existing = self
+                .generated
+                .iter()
+                .find(|(other_props, other_rsx, _)| *other_props == props && *other_rsx == rsx);
+            let name = match existing {
+                Some((_, _, name)) => name.clone(),
+                None => {
+                    let name = unique_name(&self.base, self.names);
+                    self.components.push(component_fn(&name, &props, &rsx)?);
+                    self.generated.push((props, rsx, name.clone()));
+                    name
+                }
+            };
+
+            *node =
+                parse_rsx(&format!("{name} {{ {call} }}")).expect("icon invocations are valid RSX");
+        }
+        Ok(())
+    }
+}
+
+/// The parts of an icon component generated for an `svg` element.
+struct Icon {
+    props: String,
+    /// The formatted RSX body.
+    rsx: String,
+    /// The props to invoke the component with, e.g. `class: "size-6", size: "24"`.
+    call: String,
+}
+
+/// Builds the icon component for an `svg` element, and the props to invoke it with.
+fn icon(svg: &Element) -> Result<Icon, ConvertError> {
+    let literal = |name: &str| {
+        svg.raw_attributes
+            .iter()
+            .find(|attribute| attribute.name.to_string() == name)
+            .and_then(|attribute| match &attribute.value {
+                AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) => {
+                    // Undo the brace escaping, since the value is quoted again for the call site
+                    Some(
+                        value
+                            .formatted_input
+                            .source
+                            .value()
+                            .replace("{{", "{")
+                            .replace("}}", "}"),
+                    )
+                }
+                _ => None,
+            })
+    };
+    let class = literal("class");
+    let width = literal("width");
+    let height = literal("height");
+
+    // A single `size` only stands in for both dimensions when they agree
+    let dimensions: Vec<(&str, Option<String>)> = match (width, height) {
+        (Some(width), Some(height)) if width != height => {
+            vec![("width", Some(width)), ("height", Some(height))]
+        }
+        (width, height) => vec![("size", width.or(height))],
+    };
+
+    let mut template = svg.clone();
+    template.raw_attributes.retain(|attribute| {
+        !matches!(
+            attribute.name.to_string().as_str(),
+            "class" | "width" | "height"
+        )
+    });
+    template.raw_attributes.push(Attribute::from_raw(
+        AttributeName::BuiltIn(Ident::new("class", Span::call_site())),
+        AttributeValue::AttrLiteral(HotLiteral::from_raw_text("{class}")),
+    ));
+    for dimension in ["width", "height"] {
+        let prop = match dimensions.as_slice() {
+            [("size", _)] => "size",
+            _ => dimension,
+        };
+        template.raw_attributes.push(
+            parse_rsx(&format!("{dimension}: {prop}.clone()"))
+                .expect("size attributes are valid RSX"),
+        );
+    }
+    template
+        .raw_attributes
+        .sort_by_key(|attribute| attribute.name.to_string());
+
+    let rsx = dioxus_autofmt::write_block_out(&CallBody::new(TemplateBody::new(vec![
+        BodyNode::Element(template),
+    ])))
+    .ok_or(ConvertError::Format)?;
+
+    let mut props = vec!["#[props(default)] class: String".to_string()];
+    let mut call = Vec::new();
+    if let Some(class) = class {
+        call.push(format!("class: {}", quote_text(&class)));
+    }
+    for (prop, value) in dimensions {
+        props.push(format!("{prop}: Option<String>"));
+        if let Some(value) = value {
+            call.push(format!("{prop}: {}", quote_text(&value)));
+        }
+    }
+    Ok(Icon {
+        props: props.join(", "),
+        rsx,
+        call: call.join(", "),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Conversion, Options, convert, convert_with_report};
+
+    /// Heroicons' outline `home` icon.
+    const HEROICON: &str = r#"<svg xmlns="http://www.w3.org/2000/svg" fill="none" viewBox="0 0 24 24" stroke-width="1.5" stroke="currentColor" class="size-6">
+  <path stroke-linecap="round" stroke-linejoin="round" d="m2.25 12 8.954-8.955c.44-.439 1.152-.439 1.591 0L21.75 12" />
+</svg>"#;
+
+    fn icons(html: &str) -> Conversion {
+        let options = Options {
+            icon_component: Some("home icon".to_string()),
+            ..Options::default()
+        };
+        convert_with_report(html, &options).unwrap()
+    }
+
+    #[test]
+    fn namespaced_attributes_keep_their_prefix() {
+        let rsx = convert(
+            r##"<svg xmlns:xlink="http://www.w3.org/1999/xlink" xml:space="preserve"><use xlink:href="#a" /><use xlink:href="#b" href="#c" /></svg>"##,
+            &Options::default(),
+        )
+        .unwrap();
+
+        assert!(
+            rsx.contains(r#""xmlns:xlink": "http://www.w3.org/1999/xlink""#),
+            "{rsx}"
+        );
+        assert!(rsx.contains(r#""xml:space": "preserve""#), "{rsx}");
+        assert!(rsx.contains(r##"r#use { href: "#a" }"##), "{rsx}");
+        assert!(
+            rsx.contains(r##"r#use { href: "#c", "xlink:href": "#b" }"##),
+            "{rsx}"
+        );
+    }
+
+    #[test]
+    fn camel_case_svg_names_are_mapped() {
+        let rsx = convert(
+            r##"<svg viewBox="0 0 24 24" preserveAspectRatio="none"><linearGradient gradientUnits="userSpaceOnUse"><stop stop-color="#fff" /></linearGradient><path fill-rule="evenodd" clip-rule="evenodd" d="M0 0" /></svg>"##,
+            &Options::default(),
+        )
+        .unwrap();
+
+        for expected in [
+            r#"view_box: "0 0 24 24""#,
+            r#"preserve_aspect_ratio: "none""#,
+            r#"linearGradient { gradient_units: "userSpaceOnUse""#,
+            r##"stop { stop_color: "#fff" }"##,
+            r#"fill_rule: "evenodd""#,
+            r#"clip_rule: "evenodd""#,
+        ] {
+            assert!(rsx.contains(expected), "missing {expected}:\n{rsx}");
+        }
+    }
+
+    #[test]
+    fn icon_component_takes_class_and_size() {
+        let conversion = icons(HEROICON);
+
+        assert_eq!(conversion.rsx.trim(), r#"HomeIcon { class: "size-6" }"#);
+        assert_eq!(conversion.components.len(), 1);
+        let component = &conversion.components[0];
+        for expected in [
+            "pub fn HomeIcon(#[props(default)] class: String, size: Option<String>) -> Element {",
+            r#"class: "{class}""#,
+            "width: size.clone()",
+            "height: size.clone()",
+            r#"stroke_width: "1.5""#,
+        ] {
+            assert!(
+                component.contains(expected),
+                "missing {expected}:\n{component}"
+            );
+        }
+    }
+
+    #[test]
+    fn identical_icons_share_a_component() {
+        let lucide = |class: &str, size: &str| {
+            format!(
+                r#"<svg width="{size}" height="{size}" viewBox="0 0 24 24" class="{class}"><path d="M18 6 6 18" /></svg>"#
+            )
+        };
+        let html = format!(
+            "<button>{}</button><button>{}</button>{HEROICON}",
+            lucide("w-6 h-6", "24"),
+            lucide("w-4 h-4", "16")
+        );
+        let conversion = icons(&html);
+
+        assert_eq!(
+            conversion.components.len(),
+            2,
+            "{:?}",
+            conversion.components
+        );
+        for expected in [
+            r#"HomeIcon { class: "w-6 h-6", size: "24" }"#,
+            r#"HomeIcon { class: "w-4 h-4", size: "16" }"#,
+            r#"HomeIcon2 { class: "size-6" }"#,
+        ] {
+            assert!(
+                conversion.rsx.contains(expected),
+                "missing {expected}:\n{}",
+                conversion.rsx
+            );
+        }
+    }
+
+    #[test]
+    fn icons_that_are_not_square_keep_both_dimensions() {
+        let conversion =
+            icons(r#"<svg width="24" height="16" viewBox="0 0 24 16"><path d="M0 0" /></svg>"#);
+
+        assert_eq!(
+            conversion.rsx.trim(),
+            r#"HomeIcon { width: "24", height: "16" }"#
+        );
+        let component = &conversion.components[0];
+        for expected in [
+            "width: Option<String>",
+            "height: Option<String>",
+            "width: width.clone()",
+            "height: height.clone()",
+        ] {
+            assert!(
+                component.contains(expected),
+                "missing {expected}:\n{component}"
+            );
+        }
+        assert!(!component.contains("size"), "{component}");
+    }
+}
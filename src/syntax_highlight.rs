@@ -1,48 +1,307 @@
 use dioxus::prelude::*;
+use dioxus_html_rsx::Position;
+use std::ops::Range;
+
+/// The syntactic category of a highlighted token, which decides its color.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Tag names and angle brackets in HTML, element names in RSX.
+    Tag,
+    /// Attribute names.
+    Attribute,
+    /// Quoted strings and attribute values.
+    String,
+    /// `<!-- -->` and `//` comments.
+    Comment,
+    /// Text content between HTML tags.
+    Text,
+    /// `{` and `}` in RSX.
+    Brace,
+    /// Numeric literals.
+    Number,
+    /// Whitespace, punctuation and anything else that isn't colored.
+    Plain,
+}
+
+impl TokenKind {
+    /// Tailwind classes used to color this kind of token.
+    fn class(self) -> &'static str {
+        match self {
+            TokenKind::Tag => "text-blue-400",
+            TokenKind::Attribute => "text-purple-400",
+            TokenKind::String => "text-green-400",
+            TokenKind::Comment => "text-gray-500",
+            TokenKind::Text => "text-white",
+            TokenKind::Brace => "text-yellow-500",
+            TokenKind::Number => "text-orange-400",
+            TokenKind::Plain => "",
+        }
+    }
+
+    /// Human readable name shown in the token tooltip.
+    fn label(self) -> Option<&'static str> {
+        match self {
+            TokenKind::Tag => Some("tag"),
+            TokenKind::Attribute => Some("attribute"),
+            TokenKind::String => Some("string"),
+            TokenKind::Comment => Some("comment"),
+            TokenKind::Text => Some("text"),
+            TokenKind::Brace => Some("brace"),
+            TokenKind::Number => Some("number"),
+            TokenKind::Plain => None,
+        }
+    }
+}
+
+/// A highlighted slice of source code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    /// Byte range of `text` in the highlighted source.
+    pub span: Range<usize>,
+}
+
+/// What a token's [`Token::key`] names, so a tag name never matches a string with the same text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyKind {
+    Name,
+    Content,
+    Comment,
+}
+
+impl Token {
+    /// The part of the token that carries over between HTML and RSX, normalized so both spellings
+    /// compare equal: names without case, `-`, `_` or `r#`, and strings, text and comments without
+    /// their delimiters and with whitespace collapsed. `None` for punctuation, whitespace and
+    /// closing tags, which have no counterpart in RSX.
+    fn key(&self) -> Option<(KeyKind, String)> {
+        let collapse = |text: &str| text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let (kind, key) = match self.kind {
+            TokenKind::Tag | TokenKind::Attribute | TokenKind::Plain => {
+                let name = self.text.trim();
+                if name.starts_with('/') {
+                    return None;
+                }
+                let name = name.trim_end_matches('/');
+                let name = name.strip_prefix("r#").unwrap_or(name);
+                if !name.chars().any(char::is_alphanumeric)
+                    || !name
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | ':'))
+                {
+                    return None;
+                }
+                let name: String = name
+                    .chars()
+                    .filter(|c| !matches!(c, '-' | '_'))
+                    .flat_map(char::to_lowercase)
+                    .collect();
+                (KeyKind::Name, name)
+            }
+            TokenKind::String => {
+                let quoted = &self.text[1..];
+                let content = quoted
+                    .strip_suffix(&self.text[..1])
+                    .unwrap_or(quoted)
+                    .replace("\\\"", "\"")
+                    .replace("{{", "{")
+                    .replace("}}", "}");
+                (KeyKind::Content, collapse(&content))
+            }
+            TokenKind::Text => (KeyKind::Content, collapse(&self.text)),
+            TokenKind::Comment => {
+                let comment = self.text.trim();
+                let comment = comment.strip_prefix("<!--").unwrap_or(comment);
+                let comment = comment.strip_suffix("-->").unwrap_or(comment);
+                (KeyKind::Comment, collapse(comment.trim_start_matches('/')))
+            }
+            TokenKind::Brace | TokenKind::Number => return None,
+        };
+        (!key.is_empty()).then_some((kind, key))
+    }
+}
+
+/// Splits `code` into tokens for `language`, "html" or "rsx". Other languages become one plain
+/// token.
+pub fn tokenize(code: &str, language: &str) -> Vec<Token> {
+    match language.to_lowercase().as_str() {
+        "html" => tokenize_html(code),
+        "rsx" => tokenize_rsx(code),
+        _ => vec![Token {
+            kind: TokenKind::Plain,
+            text: code.to_string(),
+            span: 0..code.len(),
+        }],
+    }
+}
+
+/// Finds the token at byte `offset`, preferring one with a counterpart in the other language when
+/// the offset falls between two tokens, like a caret right after a tag name.
+pub fn token_at(tokens: &[Token], offset: usize) -> Option<&Token> {
+    let mut candidates = tokens
+        .iter()
+        .filter(|token| token.span.contains(&offset) || token.span.end == offset);
+    let first = candidates.next()?;
+    match candidates.next() {
+        Some(second) if first.key().is_none() || second.key().is_some() => Some(second),
+        _ => Some(first),
+    }
+}
+
+/// Finds the span in `target` that corresponds to `token` from `source`, where one is the HTML and
+/// the other the RSX for the same markup.
+///
+/// Tokens are matched by their [`Token::key`] and by how many tokens with that key come before
+/// them, so the third `li` in the HTML maps to the third `li` in the RSX.
+pub fn counterpart(token: &Token, source: &[Token], target: &[Token]) -> Option<Range<usize>> {
+    let key = token.key()?;
+    let occurrence = source
+        .iter()
+        .take_while(|other| other.span.start < token.span.start)
+        .filter(|other| other.key().as_ref() == Some(&key))
+        .count();
+    target
+        .iter()
+        .filter(|other| other.key().as_ref() == Some(&key))
+        .nth(occurrence)
+        .map(|other| other.span.clone())
+}
+
+/// Returns the position each token starts at, walking the tokens once instead of rescanning the
+/// code for every token.
+fn token_positions(tokens: &[Token]) -> Vec<Position> {
+    let mut position = Position { line: 1, column: 1 };
+    tokens
+        .iter()
+        .map(|token| {
+            let start = position;
+            match token.text.rsplit_once('\n') {
+                Some((before, after)) => {
+                    position.line += before.matches('\n').count() + 1;
+                    position.column = after.chars().count() + 1;
+                }
+                None => position.column += token.text.chars().count(),
+            }
+            start
+        })
+        .collect()
+}
+
+/// Collects tokens for a piece of source.
+struct Tokens<'a> {
+    code: &'a str,
+    tokens: Vec<Token>,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(code: &'a str) -> Self {
+        Self {
+            code,
+            tokens: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, kind: TokenKind, span: Range<usize>) {
+        if span.is_empty() {
+            return;
+        }
+        self.tokens.push(Token {
+            kind,
+            text: self.code[span.clone()].to_string(),
+            span,
+        });
+    }
 
-/// Highlights HTML source code by applying spans for syntax elements such as tags, attributes, and content.
+    /// Pushes text content, only coloring it if it isn't pure whitespace.
+    fn text(&mut self, span: Range<usize>) {
+        let kind = if self.code[span.clone()].trim().is_empty() {
+            TokenKind::Plain
+        } else {
+            TokenKind::Text
+        };
+        self.push(kind, span);
+    }
+
+    /// Splits the unquoted part of an HTML tag into the tag name and attribute names.
+    ///
+    /// `name_pending` is true until the tag name has been seen, since the content of a tag can
+    /// be split over several calls by quoted attribute values.
+    fn tag_content(&mut self, span: Range<usize>, name_pending: &mut bool) {
+        let content = &self.code[span.clone()];
+        let mut run_start = 0;
+
+        // Alternate between runs of whitespace and words
+        while run_start < content.len() {
+            let rest = &content[run_start..];
+            let in_whitespace = rest.starts_with(char::is_whitespace);
+            let run_len = rest
+                .find(|c: char| c.is_whitespace() != in_whitespace)
+                .unwrap_or(rest.len());
+            let run = span.start + run_start..span.start + run_start + run_len;
+            if in_whitespace {
+                self.push(TokenKind::Plain, run);
+            } else {
+                self.tag_word(run, name_pending);
+            }
+            run_start += run_len;
+        }
+    }
+
+    fn tag_word(&mut self, span: Range<usize>, name_pending: &mut bool) {
+        if std::mem::take(name_pending) {
+            self.push(TokenKind::Tag, span);
+            return;
+        }
+
+        // Attribute, optionally followed by `=` and an unquoted value
+        match self.code[span.clone()].find('=') {
+            Some(equals) => {
+                let equals = span.start + equals;
+                self.push(TokenKind::Attribute, span.start..equals);
+                self.push(TokenKind::Plain, equals..span.end);
+            }
+            None => self.push(TokenKind::Attribute, span),
+        }
+    }
+
+    fn finish(self) -> Vec<Token> {
+        self.tokens
+    }
+}
+
+/// Splits HTML source into tokens for tags, attributes, attribute values, comments and content.
 ///
-/// This function processes the provided HTML code, identifying tags, attributes, and content.
-/// It wraps detected elements in appropriately colored spans for syntax highlighting.
-fn highlight_html_syntax(code: &str) -> String {
-    let mut result = String::new();
+/// The returned tokens cover the whole input, so concatenating their text gives back `code`.
+pub fn tokenize_html(code: &str) -> Vec<Token> {
+    let mut tokens = Tokens::new(code);
     let mut in_tag = false;
     let mut in_attr_value = false;
     let mut in_comment = false;
+    let mut name_pending = false;
+    // Byte offset of the first character not yet turned into a token
     let mut token_start = 0;
-    let chars: Vec<char> = code.chars().collect();
     let mut quote_char = '"'; // Default quote character
 
-    for i in 0..chars.len() {
+    for (i, c) in code.char_indices() {
+        // Skip characters already consumed as part of a multi-character token
+        if i < token_start {
+            continue;
+        }
+        let rest = &code[i..];
+
         // Handle HTML comments
-        if !in_tag
-            && !in_comment
-            && i + 3 < chars.len()
-            && chars[i] == '<'
-            && chars[i + 1] == '!'
-            && chars[i + 2] == '-'
-            && chars[i + 3] == '-'
-        {
-            // Start of comment
-            if token_start < i {
-                result.push_str(&code[token_start..i]);
-            }
-            result.push_str("<span class='text-gray-500'>");
+        if !in_tag && !in_comment && rest.starts_with("<!--") {
+            tokens.text(token_start..i);
             token_start = i;
             in_comment = true;
             continue;
         }
 
-        if in_comment
-            && i + 2 < chars.len()
-            && chars[i] == '-'
-            && chars[i + 1] == '-'
-            && chars[i + 2] == '>'
-        {
-            // End of comment
-            result.push_str(&code[token_start..i + 3]);
-            result.push_str("</span>");
-            token_start = i + 3;
+        if in_comment && rest.starts_with("-->") && i >= token_start + "<!--".len() {
+            let end = i + "-->".len();
+            tokens.push(TokenKind::Comment, token_start..end);
+            token_start = end;
             in_comment = false;
             continue;
         }
@@ -52,281 +311,137 @@ fn highlight_html_syntax(code: &str) -> String {
         }
 
         // Handle tag opening
-        if !in_tag && chars[i] == '<' && i + 1 < chars.len() && chars[i + 1] != '!' {
-            if token_start < i {
-                // Text content before tag
-                let content = &code[token_start..i];
-                if !content.trim().is_empty() {
-                    result.push_str("<span class='text-white'>");
-                    result.push_str(content);
-                    result.push_str("</span>");
-                } else {
-                    result.push_str(content);
-                }
-            }
-            result.push_str("<span class='text-blue-400'>&lt;");
+        if !in_tag && c == '<' && rest[1..].chars().next().is_some_and(|next| next != '!') {
+            tokens.text(token_start..i);
+            tokens.push(TokenKind::Tag, i..i + 1);
             in_tag = true;
+            name_pending = true;
             token_start = i + 1;
             continue;
         }
 
         // Handle tag closing
-        if in_tag && chars[i] == '>' {
-            if token_start < i {
-                let tag_content = &code[token_start..i];
-                result.push_str(&highlight_tag_content(tag_content));
-            }
-            result.push_str("&gt;</span>");
+        if in_tag && !in_attr_value && c == '>' {
+            tokens.tag_content(token_start..i, &mut name_pending);
+            tokens.push(TokenKind::Tag, i..i + 1);
             in_tag = false;
-            in_attr_value = false;
             token_start = i + 1;
             continue;
         }
 
-        // Handle attribute values
-        if in_tag && (chars[i] == '"' || chars[i] == '\'') {
+        // Handle attribute values, including their quotes
+        if in_tag && (c == '"' || c == '\'') {
             if !in_attr_value {
-                // Start of attribute value
-                if token_start < i {
-                    let attr_name = &code[token_start..i];
-                    result.push_str(&highlight_tag_content(attr_name));
-                }
-                quote_char = chars[i];
-                result.push_str("<span class='text-green-400'>");
-                result.push(chars[i]);
+                tokens.tag_content(token_start..i, &mut name_pending);
+                quote_char = c;
                 in_attr_value = true;
-                token_start = i + 1;
-            } else if chars[i] == quote_char {
-                // End of attribute value
-                result.push_str(&code[token_start..i]);
-                result.push(chars[i]);
-                result.push_str("</span>");
+                token_start = i;
+            } else if c == quote_char {
+                tokens.push(TokenKind::String, token_start..i + 1);
                 in_attr_value = false;
                 token_start = i + 1;
             }
-            continue;
-        }
-
-        // Handle whitespace in tags (not in attribute values)
-        if in_tag && !in_attr_value && chars[i].is_whitespace() {
-            if token_start < i {
-                let tag_name = &code[token_start..i];
-                if tag_name.starts_with('/') {
-                    // Closing tag
-                    result.push_str("<span class='text-blue-400'>");
-                    result.push_str(tag_name);
-                    result.push_str("</span>");
-                } else {
-                    // Opening tag
-                    result.push_str("<span class='text-blue-400'>");
-                    result.push_str(tag_name);
-                    result.push_str("</span>");
-                }
-            }
-            result.push(chars[i]);
-            token_start = i + 1;
         }
     }
 
     // Add any remaining part
-    if token_start < chars.len() {
-        let remaining = &code[token_start..];
-        if in_tag {
-            result.push_str(&highlight_tag_content(remaining));
-        } else if in_comment {
-            result.push_str(remaining);
-            result.push_str("</span>");
-        } else {
-            // Text content
-            if !remaining.trim().is_empty() {
-                result.push_str("<span class='text-white'>");
-                result.push_str(remaining);
-                result.push_str("</span>");
-            } else {
-                result.push_str(remaining);
-            }
-        }
-    }
-
-    result
-}
-
-/// Highlights the content inside an HTML tag, such as tag names and attributes.
-fn highlight_tag_content(content: &str) -> String {
-    let mut result = String::new();
-    let parts: Vec<&str> = content.split_whitespace().collect();
-
-    if parts.is_empty() {
-        return content.to_string();
-    }
-
-    // First part is the tag name
-    let tag_name = parts[0];
-    if tag_name.starts_with('/') {
-        // Closing tag
-        result.push_str(&format!("<span class='text-blue-400'>{}</span>", tag_name));
+    let remaining = token_start..code.len();
+    if in_attr_value {
+        tokens.push(TokenKind::String, remaining);
+    } else if in_tag {
+        tokens.tag_content(remaining, &mut name_pending);
+    } else if in_comment {
+        tokens.push(TokenKind::Comment, remaining);
     } else {
-        // Opening tag
-        result.push_str(&format!("<span class='text-blue-400'>{}</span>", tag_name));
+        tokens.text(remaining);
     }
 
-    // Rest are attributes
-    let mut current_pos = tag_name.len();
-    for i in 1..parts.len() {
-        // Find the position of this part in the original content
-        let part_pos = content[current_pos..].find(parts[i]).unwrap() + current_pos;
-
-        // Add any content between the last part and this one
-        result.push_str(&content[current_pos..part_pos]);
-        current_pos = part_pos + parts[i].len();
-
-        // Highlight attribute name
-        if parts[i].contains('=') {
-            let attr_parts: Vec<&str> = parts[i].split('=').collect();
-            result.push_str(&format!(
-                "<span class='text-purple-400'>{}</span>=",
-                attr_parts[0]
-            ));
-
-            // If there's a value part after the equals
-            if attr_parts.len() > 1 {
-                result.push_str(attr_parts[1]);
-            }
-        } else {
-            result.push_str(&format!(
-                "<span class='text-purple-400'>{}</span>",
-                parts[i]
-            ));
-        }
-    }
-
-    // Add any remaining content
-    if current_pos < content.len() {
-        result.push_str(&content[current_pos..]);
-    }
-
-    result
+    tokens.finish()
 }
 
-/// Highlights RSX syntax by applying spans for syntax elements.
-fn highlight_rsx_syntax(code: &str) -> String {
-    let mut result = String::new();
+/// Splits RSX source into tokens for element names, attributes, strings, braces and comments.
+///
+/// The returned tokens cover the whole input, so concatenating their text gives back `code`.
+pub fn tokenize_rsx(code: &str) -> Vec<Token> {
+    let mut tokens = Tokens::new(code);
     let mut in_string = false;
     let mut in_comment = false;
+    // Byte offset of the first character not yet turned into a token
     let mut token_start = 0;
-    let chars: Vec<char> = code.chars().collect();
+    let mut escaped = false;
 
-    for i in 0..chars.len() {
-        // Handle comments first
-        if !in_string && i + 1 < chars.len() && chars[i] == '/' && chars[i + 1] == '/' {
-            // Add any accumulated token before the comment
-            if token_start < i {
-                let token = &code[token_start..i];
-                result.push_str(&highlight_rsx_token(token, false));
-            }
-
-            // Start the comment span
-            result.push_str("<span class='text-gray-500'>");
-            token_start = i;
-            in_comment = true;
+    for (i, c) in code.char_indices() {
+        if i < token_start {
             continue;
         }
 
-        // If we're in a comment and hit a newline, close the comment span
-        if in_comment && chars[i] == '\n' {
-            result.push_str(&code[token_start..=i]);
-            result.push_str("</span>");
-            token_start = i + 1;
-            in_comment = false;
+        // Handle comments first
+        if !in_string && !in_comment && code[i..].starts_with("//") {
+            push_rsx_word(&mut tokens, token_start..i);
+            token_start = i;
+            in_comment = true;
             continue;
         }
 
-        // If we're in a comment, continue to next character
+        // A comment runs up to and including the end of the line
         if in_comment {
+            if c == '\n' {
+                tokens.push(TokenKind::Comment, token_start..i + 1);
+                token_start = i + 1;
+                in_comment = false;
+            }
             continue;
         }
 
-        // Handle string literals
-        if chars[i] == '"' && (i == 0 || chars[i - 1] != '\\') {
+        // Handle string literals, including their quotes
+        if c == '"' && !escaped {
             if !in_string {
-                // Start of string
-                if token_start < i {
-                    let token = &code[token_start..i];
-                    result.push_str(&highlight_rsx_token(token, false));
-                }
-                result.push_str("<span class='text-green-400'>\"");
-                token_start = i + 1;
+                push_rsx_word(&mut tokens, token_start..i);
+                token_start = i;
                 in_string = true;
             } else {
-                // End of string
-                result.push_str(&code[token_start..i]);
-                result.push_str("\"</span>");
+                tokens.push(TokenKind::String, token_start..i + 1);
                 token_start = i + 1;
                 in_string = false;
             }
             continue;
         }
 
-        // If we're in a string, continue to next character
         if in_string {
+            // A backslash escapes the next character, unless it is itself escaped
+            escaped = c == '\\' && !escaped;
             continue;
         }
 
         // Handle whitespace and separators
-        if chars[i].is_whitespace()
-            || chars[i] == '{'
-            || chars[i] == '}'
-            || chars[i] == '('
-            || chars[i] == ')'
-            || chars[i] == ':'
-            || chars[i] == ','
-        {
-            if token_start < i {
-                let token = &code[token_start..i];
-                result.push_str(&highlight_rsx_token(token, false));
-            }
-
-            // Add the separator character with special coloring for braces
-            if chars[i] == '{' || chars[i] == '}' {
-                result.push_str(&format!(
-                    "<span class='text-yellow-500'>{}</span>",
-                    chars[i]
-                ));
+        if c.is_whitespace() || matches!(c, '{' | '}' | '(' | ')' | ':' | ',') {
+            push_rsx_word(&mut tokens, token_start..i);
+            let kind = if c == '{' || c == '}' {
+                TokenKind::Brace
             } else {
-                result.push(chars[i]);
-            }
-            token_start = i + 1;
+                TokenKind::Plain
+            };
+            tokens.push(kind, i..i + c.len_utf8());
+            token_start = i + c.len_utf8();
         }
     }
 
     // Add any remaining part
-    if token_start < chars.len() {
-        let token = &code[token_start..];
-        if in_string {
-            result.push_str(token);
-        } else if in_comment {
-            result.push_str(token);
-            result.push_str("</span>");
-        } else {
-            result.push_str(&highlight_rsx_token(token, false));
-        }
-    }
-
-    result
-}
-
-/// Highlights an RSX token by applying appropriate styling.
-fn highlight_rsx_token(token: &str, in_string: bool) -> String {
+    let remaining = token_start..code.len();
     if in_string {
-        return token.to_string();
+        tokens.push(TokenKind::String, remaining);
+    } else if in_comment {
+        tokens.push(TokenKind::Comment, remaining);
+    } else {
+        push_rsx_word(&mut tokens, remaining);
     }
 
-    // Clean the token
-    let clean_token = token.trim();
+    tokens.finish()
+}
 
-    if clean_token.is_empty() {
-        return token.to_string();
-    }
+/// Classifies a word between RSX separators.
+fn push_rsx_word(tokens: &mut Tokens, span: Range<usize>) {
+    let word = &tokens.code[span.clone()];
 
     // Handle RSX keywords
     let keywords = [
@@ -334,44 +449,43 @@ fn highlight_rsx_token(token: &str, in_string: bool) -> String {
         "textarea", "form", "img", "nav", "footer", "header", "main", "section", "article",
     ];
 
-    if keywords.contains(&clean_token) {
-        return format!("<span class='text-blue-400'>{}</span>", token);
-    }
-
-    // Handle attributes
-    if clean_token.ends_with(':') {
-        return format!("<span class='text-purple-400'>{}</span>", token);
-    }
+    // A word directly followed by a single `:` is an attribute name; `::` is a path
+    let rest = &tokens.code[span.end..];
+    let is_attribute = rest.starts_with(':') && !rest.starts_with("::");
 
-    // Handle class and style attributes
-    if clean_token == "class" || clean_token == "style" {
-        return format!("<span class='text-purple-400'>{}</span>", token);
-    }
-
-    // Handle numbers
-    if clean_token.chars().all(|c| c.is_ascii_digit() || c == '.') {
-        return format!("<span class='text-orange-400'>{}</span>", token);
-    }
-
-    token.to_string()
+    let kind = if is_attribute || word == "class" || word == "style" {
+        TokenKind::Attribute
+    } else if keywords.contains(&word) {
+        TokenKind::Tag
+    } else if !word.is_empty() && word.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        TokenKind::Number
+    } else {
+        TokenKind::Plain
+    };
+    tokens.push(kind, span);
 }
 
 #[component]
 /// Renders a syntax-highlighted code block as a Dioxus component.
 ///
-/// This component applies syntax highlighting to the provided code snippet based on the specified language.
+/// Each token is rendered as its own `span`, with a tooltip naming the token kind and the line and
+/// column it starts at. Clicking a token passes it to `on_token_click`, if set.
 /// Currently supports "html" and "rsx" languages.
 ///
 /// # Arguments
 ///
 /// * `code` - The code snippet to highlight.
 /// * `language` - The language identifier (e.g., "html", "rsx"). This value is case-insensitive.
-pub fn CodeBlock(code: String, language: String) -> Element {
-    let highlighted = match language.to_lowercase().as_str() {
-        "html" => highlight_html_syntax(&code),
-        "rsx" => highlight_rsx_syntax(&code),
-        _ => code.clone(),
-    };
+/// * `on_token_click` - Called with the token under the cursor when it is clicked.
+/// * `selected` - A byte range of `code` whose tokens are highlighted.
+pub fn CodeBlock(
+    code: String,
+    language: String,
+    on_token_click: Option<EventHandler<Token>>,
+    selected: Option<Range<usize>>,
+) -> Element {
+    let tokens = tokenize(&code, &language);
+    let positions = token_positions(&tokens);
 
     rsx! {
         pre {
@@ -380,7 +494,227 @@ pub fn CodeBlock(code: String, language: String) -> Element {
                 language,
             ),
             style: "white-space: pre;", // Ensure whitespace is preserved
-            dangerous_inner_html: "{highlighted}",
+            for (token, position) in tokens.into_iter().zip(positions) {
+                span {
+                    key: "{token.span.start}",
+                    class: if selected
+                        .as_ref()
+                        .is_some_and(|selected| {
+                            selected.start < token.span.end && token.span.start < selected.end
+                        })
+                    {
+                        format!("{} token-selected", token.kind.class())
+                    } else {
+                        token.kind.class().to_string()
+                    },
+                    title: token
+                        .kind
+                        .label()
+                        .map(|label| format!("{label} at {position}")),
+                    onclick: {
+                        let token = token.clone();
+                        move |_| {
+                            if let Some(handler) = on_token_click {
+                                handler.call(token.clone());
+                            }
+                        }
+                    },
+                    "{token.text}"
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that the tokens cover `code` without gaps and that each span matches its text.
+    fn assert_covers(code: &str, tokens: &[Token]) {
+        let joined: String = tokens.iter().map(|token| token.text.as_str()).collect();
+        assert_eq!(joined, code);
+        let mut offset = 0;
+        for token in tokens {
+            assert_eq!(token.span.start, offset);
+            assert_eq!(&code[token.span.clone()], token.text);
+            offset = token.span.end;
+        }
+    }
+
+    fn find<'a>(tokens: &'a [Token], text: &str) -> &'a Token {
+        tokens
+            .iter()
+            .find(|token| token.text == text)
+            .unwrap_or_else(|| panic!("no token {text:?} in {tokens:#?}"))
+    }
+
+    #[test]
+    fn html_with_multibyte_text_round_trips() {
+        let code = "<p class=\"intro\">こんにちは 🌍 — © 2024</p>\n<span title='naïve café'>Ünïcødé</span>";
+        let tokens = tokenize_html(code);
+        assert_covers(code, &tokens);
+        assert_eq!(
+            find(&tokens, "こんにちは 🌍 — © 2024").kind,
+            TokenKind::Text
+        );
+        assert_eq!(find(&tokens, "'naïve café'").kind, TokenKind::String);
+        assert_eq!(find(&tokens, "class").kind, TokenKind::Attribute);
+        assert_eq!(find(&tokens, "span").kind, TokenKind::Tag);
+    }
+
+    #[test]
+    fn html_with_multibyte_comment_round_trips() {
+        let code = "<!-- 注释 ✨ --><div>😀</div><!-- unterminated ©";
+        let tokens = tokenize_html(code);
+        assert_covers(code, &tokens);
+        assert_eq!(find(&tokens, "<!-- 注释 ✨ -->").kind, TokenKind::Comment);
+        assert_eq!(
+            find(&tokens, "<!-- unterminated ©").kind,
+            TokenKind::Comment
+        );
+    }
+
+    #[test]
+    fn html_markup_in_text_stays_text() {
+        let code = "<p>&amp; &lt;b&gt; «ok»</p>";
+        let tokens = tokenize_html(code);
+        assert_covers(code, &tokens);
+        assert_eq!(find(&tokens, "&amp; &lt;b&gt; «ok»").kind, TokenKind::Text);
+    }
+
+    #[test]
+    fn html_attribute_after_quoted_value_is_not_a_tag() {
+        let code = "<a href=\"/\" title=\"home\">";
+        let tokens = tokenize_html(code);
+        assert_covers(code, &tokens);
+        assert_eq!(find(&tokens, "a").kind, TokenKind::Tag);
+        assert_eq!(find(&tokens, "title").kind, TokenKind::Attribute);
+    }
+
+    #[test]
+    fn rsx_with_multibyte_strings_round_trips() {
+        let code = "div { class: \"ß\", \"日本語 🎉 \\\"quoted\\\" <b>\" }\n// コメント 🚀\nspan {\u{3000}\"©\" }";
+        let tokens = tokenize_rsx(code);
+        assert_covers(code, &tokens);
+        assert_eq!(
+            find(&tokens, "\"日本語 🎉 \\\"quoted\\\" <b>\"").kind,
+            TokenKind::String
+        );
+        assert_eq!(find(&tokens, "// コメント 🚀\n").kind, TokenKind::Comment);
+        assert_eq!(find(&tokens, "\"©\"").kind, TokenKind::String);
+    }
+
+    #[test]
+    fn rsx_with_trailing_backslash_in_string() {
+        let code = "p { \"C:\\\\\" }";
+        let tokens = tokenize_rsx(code);
+        assert_covers(code, &tokens);
+        assert_eq!(find(&tokens, "\"C:\\\\\"").kind, TokenKind::String);
+        assert_eq!(tokens.last().unwrap().kind, TokenKind::Brace);
+    }
+
+    #[test]
+    fn rsx_with_multibyte_identifiers() {
+        let code = "Größe { wert: 1.5, Icons::Star {} }";
+        let tokens = tokenize_rsx(code);
+        assert_covers(code, &tokens);
+        assert_eq!(find(&tokens, "wert").kind, TokenKind::Attribute);
+        assert_eq!(find(&tokens, "1.5").kind, TokenKind::Number);
+        assert_eq!(find(&tokens, "Icons").kind, TokenKind::Plain);
+    }
+
+    /// The text of the token in `target` that `text` in `source` jumps to.
+    fn jump<'a>(
+        source: &str,
+        source_language: &str,
+        text: &str,
+        occurrence: usize,
+        target: &'a str,
+        target_language: &str,
+    ) -> Option<&'a str> {
+        let source_tokens = tokenize(source, source_language);
+        let token = source_tokens
+            .iter()
+            .filter(|token| token.text == text)
+            .nth(occurrence)
+            .unwrap_or_else(|| panic!("no token {text:?} in {source_tokens:#?}"));
+        counterpart(token, &source_tokens, &tokenize(target, target_language))
+            .map(|span| &target[span])
+    }
+
+    #[test]
+    fn tokens_jump_to_the_same_occurrence_in_the_other_language() {
+        let html = "<ul class=\"list\">\n  <li>One</li>\n  <li data-id=\"2\">Two  {x}</li>\n  <!-- done -->\n</ul>";
+        let rsx = "ul { class: \"list\",\n    li { \"One\" }\n    li { \"data-id\": \"2\", \"Two {{x}}\" }\n    // done\n}";
+
+        assert_eq!(jump(html, "html", "li", 1, rsx, "rsx"), Some("li"));
+        let second_li = rsx.match_indices("li {").nth(1).unwrap().0;
+        let source_tokens = tokenize(html, "html");
+        let li = source_tokens
+            .iter()
+            .filter(|token| token.text == "li")
+            .nth(1)
+            .unwrap();
+        assert_eq!(
+            counterpart(li, &source_tokens, &tokenize(rsx, "rsx")),
+            Some(second_li..second_li + 2)
+        );
+
+        assert_eq!(
+            jump(html, "html", "Two  {x}", 0, rsx, "rsx"),
+            Some("\"Two {{x}}\"")
+        );
+        assert_eq!(
+            jump(html, "html", "\"list\"", 0, rsx, "rsx"),
+            Some("\"list\"")
+        );
+        assert_eq!(
+            jump(html, "html", "<!-- done -->", 0, rsx, "rsx"),
+            Some("// done\n")
+        );
+        assert_eq!(jump(rsx, "rsx", "\"One\"", 0, html, "html"), Some("One"));
+        assert_eq!(jump(rsx, "rsx", "class", 0, html, "html"), Some("class"));
+
+        // Closing tags and punctuation have no counterpart
+        assert_eq!(jump(html, "html", "/li", 0, rsx, "rsx"), None);
+        assert_eq!(jump(rsx, "rsx", "{", 0, html, "html"), None);
+    }
+
+    #[test]
+    fn names_match_across_spellings() {
+        let html = "<svg viewBox=\"0 0 1 1\"><input type=\"text\"></svg>";
+        let rsx = "svg { view_box: \"0 0 1 1\", input { r#type: \"text\" } }";
+        assert_eq!(
+            jump(html, "html", "viewBox", 0, rsx, "rsx"),
+            Some("view_box")
+        );
+        assert_eq!(jump(html, "html", "type", 0, rsx, "rsx"), Some("r#type"));
+    }
+
+    #[test]
+    fn token_at_prefers_tokens_with_a_counterpart() {
+        let code = "<p class=\"a\">";
+        let tokens = tokenize_html(code);
+        assert_eq!(token_at(&tokens, 1).unwrap().text, "p");
+        // Right after `p`, between it and the following space
+        assert_eq!(token_at(&tokens, 2).unwrap().text, "p");
+        assert_eq!(token_at(&tokens, 4).unwrap().text, "class");
+        assert_eq!(token_at(&tokens, code.len()).unwrap().text, ">");
+        assert!(token_at(&tokens, code.len() + 1).is_none());
+    }
+
+    #[test]
+    fn token_positions_match_offsets() {
+        let code = "<p>\n  «é» <b>\r\n\n</b></p>\n<!-- a\nb -->x";
+        let tokens = tokenize_html(code);
+        let positions = token_positions(&tokens);
+        for (token, position) in tokens.iter().zip(positions) {
+            assert_eq!(
+                position,
+                Position::from_offset(code, token.span.start),
+                "{token:?}"
+            );
         }
     }
 }
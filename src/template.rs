@@ -0,0 +1,1044 @@
+//! Server template input (Jinja, Askama and Handlebars).
+//!
+//! Templates are HTML with `{{ expressions }}` and `{% statements %}` mixed in, which `Dom::parse`
+//! would treat as text. They are lowered to plain HTML first: blocks become marker elements around
+//! their content, interpolations become marker words, and comments and unknown statements become
+//! empty marker elements. The HTML then goes through the usual pipeline, and the markers in
+//! rosetta's output are swapped for RSX `for` loops, `if` chains, `"{expr}"` segments and
+//! `// comments`.
+//!
+//! Expressions are translated to Rust where that is mechanical (`and` to `&&`, `name|upper` to
+//! `name.to_uppercase()`, `this.title` to `item.title`). Conditions and loop sources that can't be
+//! translated become `todo!("...")` placeholders, and so do interpolations. `{% raw %}` blocks are
+//! kept as text.
+
+use crate::emit::{placeholder, rust_tokens, todo_expr};
+use crate::handlers::Conversion;
+use crate::{ConvertError, Options, Span};
+use dioxus_rsx::{AttributeValue, BodyNode, ForLoop, HotLiteral, IfChain, TemplateBody, TextNode};
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
+use syn::Expr;
+
+/// Name prefix of the elements that stand in for blocks until rosetta is done.
+const BLOCK_PREFIX: &str = "html2rsx-template-";
+
+/// Prefix of the words that stand in for interpolations until rosetta is done.
+const EXPR_PREFIX: &str = "__html2rsx_expr_";
+
+/// A server template language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Dialect {
+    Jinja,
+    Askama,
+    Handlebars,
+}
+
+/// A template converted to HTML with markers, and what the markers stand for.
+pub(crate) struct Lowered {
+    pub html: String,
+    /// Byte offset in the template for every byte of `html`, plus one entry for the end.
+    offsets: Vec<usize>,
+    blocks: Vec<Block>,
+    /// The `"{...}"` segment or escaped text each interpolation marker is replaced with.
+    interpolations: Vec<String>,
+}
+
+enum Block {
+    For {
+        pat: TokenStream,
+        expr: TokenStream,
+    },
+    /// An `if` chain, whose children are all [`Block::Branch`] markers.
+    If,
+    /// A branch of an `if` chain; `None` for the final `else`.
+    Branch(Option<TokenStream>),
+    /// A template comment or a statement with no RSX equivalent, kept as a `//` comment.
+    Comment(String),
+}
+
+/// Converts a template in `dialect` to RSX.
+pub(crate) fn convert_template(
+    source: &str,
+    dialect: Dialect,
+    options: &Options,
+) -> Result<Conversion, ConvertError> {
+    let lowered = lower(source, dialect)?;
+    crate::convert_html(&lowered.html, options, Some(&lowered)).map_err(|error| {
+        // Spans point into the lowered HTML, so move them back onto the template
+        let remap = |span: Option<Span>| {
+            span.map(|span| {
+                let start = lowered.original_offset(span.start.to_offset(&lowered.html));
+                let end = lowered.original_offset(span.end.to_offset(&lowered.html));
+                Span::from_offsets(source, start, end)
+            })
+        };
+        match error {
+            ConvertError::Parse { message, span } => ConvertError::Parse {
+                message,
+                span: remap(span),
+            },
+            ConvertError::Unsupported { construct, span } => ConvertError::Unsupported {
+                construct,
+                span: remap(span),
+            },
+            ConvertError::Format => ConvertError::Format,
+        }
+    })
+}
+
+/// An open block while lowering.
+struct Open {
+    kind: OpenKind,
+    /// Marker of the block element, and of the current branch for `if` chains.
+    markers: Vec<usize>,
+    /// Where the opening tag is, for errors about unclosed blocks.
+    start: usize,
+}
+
+enum OpenKind {
+    /// A loop, with the source of its iterator for a trailing `else` branch.
+    For {
+        expr: TokenStream,
+    },
+    If,
+    /// The `else` branch of a loop, which is an `if` on the iterator being empty.
+    ForElse,
+    /// A Handlebars `{{#each}}` that uses `@index`, so it needs `.enumerate()`.
+    Each {
+        pat: String,
+        expr: String,
+        index: bool,
+    },
+}
+
+struct Lowerer<'a> {
+    source: &'a str,
+    dialect: Dialect,
+    lowered: Lowered,
+    open: Vec<Open>,
+    /// Quote character of the attribute value being written, or `'<'` inside a tag but outside
+    /// any value, or `None` in content.
+    tag_state: Option<char>,
+}
+
+/// Converts the template into HTML with markers.
+fn lower(source: &str, dialect: Dialect) -> Result<Lowered, ConvertError> {
+    let mut lowerer = Lowerer {
+        source,
+        dialect,
+        lowered: Lowered {
+            html: String::with_capacity(source.len()),
+            offsets: Vec::with_capacity(source.len() + 1),
+            blocks: Vec::new(),
+            interpolations: Vec::new(),
+        },
+        open: Vec::new(),
+        tag_state: None,
+    };
+
+    let mut pos = 0;
+    while let Some(found) = source[pos..].find('{') {
+        let start = pos + found;
+        let Some((tag, end)) = template_tag(source, start, dialect)? else {
+            lowerer.push_html(pos..start + 1);
+            pos = start + 1;
+            continue;
+        };
+        lowerer.push_html(pos..start);
+        if let TemplateTag::Statement("raw") = tag {
+            // Everything up to `{% endraw %}` is text, template syntax included
+            let (text_end, raw_end) =
+                raw_block_end(source, end).ok_or_else(|| ConvertError::Parse {
+                    message: "raw block is never closed with `{% endraw %}`".to_string(),
+                    span: Some(Span::from_offsets(source, start, end)),
+                })?;
+            lowerer.push_html(end..text_end);
+            pos = raw_end;
+            continue;
+        }
+        lowerer.tag(tag, start, end)?;
+        pos = end;
+    }
+    lowerer.push_html(pos..source.len());
+
+    if let Some(open) = lowerer.open.last() {
+        return Err(ConvertError::Parse {
+            message: "template block is never closed".to_string(),
+            span: Some(Span::from_offsets(source, open.start, open.start)),
+        });
+    }
+    lowerer.lowered.offsets.push(source.len());
+    Ok(lowerer.lowered)
+}
+
+/// Finds the `{% endraw %}` after `from`, returning where it starts and ends.
+fn raw_block_end(source: &str, from: usize) -> Option<(usize, usize)> {
+    let mut pos = from;
+    while let Some(found) = source[pos..].find("{%") {
+        let start = pos + found;
+        let length = source[start..].find("%}")?;
+        let end = start + length + "%}".len();
+        let inner = source[start + "{%".len()..start + length]
+            .trim_matches(['-', '+', '~'])
+            .trim();
+        if inner == "endraw" {
+            return Some((start, end));
+        }
+        pos = end;
+    }
+    None
+}
+
+/// A template tag with its delimiters and whitespace control characters removed.
+enum TemplateTag<'a> {
+    /// `{{ ... }}`
+    Expression(&'a str),
+    /// `{% ... %}`, or a Handlebars `{{#...}}`, `{{/...}}`, `{{else}}` or `{{> ...}}`
+    Statement(&'a str),
+    /// `{# ... #}` or `{{! ... }}`
+    Comment(&'a str),
+}
+
+/// Reads the template tag starting with the `{` at `start`, returning it with its end offset, or
+/// `None` if the `{` is just text.
+fn template_tag(
+    source: &str,
+    start: usize,
+    dialect: Dialect,
+) -> Result<Option<(TemplateTag<'_>, usize)>, ConvertError> {
+    let rest = &source[start..];
+    let (open, close) = match dialect {
+        Dialect::Handlebars if rest.starts_with("{{!--") => ("{{!--", "--}}"),
+        Dialect::Handlebars if rest.starts_with("{{{") => ("{{{", "}}}"),
+        _ if rest.starts_with("{{") => ("{{", "}}"),
+        Dialect::Jinja | Dialect::Askama if rest.starts_with("{%") => ("{%", "%}"),
+        Dialect::Jinja | Dialect::Askama if rest.starts_with("{#") => ("{#", "#}"),
+        _ => return Ok(None),
+    };
+
+    let Some(length) = rest[open.len()..].find(close) else {
+        return Err(ConvertError::Parse {
+            message: format!("template tag `{open}` is never closed with `{close}`"),
+            span: Some(Span::from_offsets(source, start, start + open.len())),
+        });
+    };
+    let end = start + open.len() + length + close.len();
+    // `{%-`, `-%}`, `{{~` and friends only control whitespace, which RSX doesn't keep anyway
+    let inner = rest[open.len()..open.len() + length]
+        .trim_start_matches(['-', '+', '~'])
+        .trim_end_matches(['-', '+', '~'])
+        .trim();
+
+    let tag = match (dialect, open) {
+        (_, "{#" | "{{!--") => TemplateTag::Comment(inner),
+        (_, "{%") => TemplateTag::Statement(inner),
+        (Dialect::Handlebars, "{{") => match inner.chars().next() {
+            Some('!') => TemplateTag::Comment(inner[1..].trim()),
+            Some('#' | '/' | '^' | '>') => TemplateTag::Statement(inner),
+            _ if inner == "else" || inner.starts_with("else ") => TemplateTag::Statement(inner),
+            _ => TemplateTag::Expression(inner),
+        },
+        _ => TemplateTag::Expression(inner),
+    };
+    Ok(Some((tag, end)))
+}
+
+impl Lowerer<'_> {
+    fn tag(&mut self, tag: TemplateTag, start: usize, end: usize) -> Result<(), ConvertError> {
+        match tag {
+            TemplateTag::Expression(expr) => {
+                if self.tag_state == Some('<') {
+                    return Err(self.unsupported(
+                        "template expressions between attributes",
+                        start,
+                        end,
+                    ));
+                }
+                let translated = match (self.dialect, expr) {
+                    (Dialect::Handlebars, "@index") => self.loop_index(),
+                    _ => self.expression(expr),
+                };
+                // Braces in the message would end the interpolation early
+                let replacement = match translated {
+                    Some(expr) => format!("{{{expr}}}"),
+                    None => format!("{{{}}}", todo_expr(&expr.replace(['{', '}'], ""))),
+                };
+                let marker = format!("{EXPR_PREFIX}{}__", self.lowered.interpolations.len());
+                self.lowered.interpolations.push(replacement);
+                self.push_marker(&marker, start);
+            }
+            // Comments inside a tag have nowhere to go
+            TemplateTag::Comment(_) if self.tag_state.is_some() => {}
+            TemplateTag::Comment(text) => {
+                let marker = self.block(Block::Comment(text.to_string()));
+                self.push_element(marker, start, true);
+                self.push_element(marker, start, false);
+            }
+            TemplateTag::Statement(_) if self.tag_state.is_some() => {
+                return Err(self.unsupported("template statements inside an HTML tag", start, end));
+            }
+            TemplateTag::Statement(statement) => self.statement(statement, start, end)?,
+        }
+        Ok(())
+    }
+
+    /// Handles a `{% ... %}` statement, or a Handlebars block tag.
+    fn statement(&mut self, statement: &str, start: usize, end: usize) -> Result<(), ConvertError> {
+        let (keyword, rest) = statement
+            .split_once(char::is_whitespace)
+            .map_or((statement, ""), |(keyword, rest)| (keyword, rest.trim()));
+
+        match (self.dialect, keyword) {
+            (Dialect::Jinja | Dialect::Askama, "for") | (Dialect::Handlebars, "#each") => {
+                self.open_for(rest, start)
+            }
+            (Dialect::Jinja | Dialect::Askama, "if") | (Dialect::Handlebars, "#if") => {
+                let cond = self.condition(rest);
+                self.open_if(cond, start);
+            }
+            (Dialect::Handlebars, "#unless") => {
+                let cond = self.condition(rest);
+                let cond = receiver(cond);
+                self.open_if(quote! { !#cond }, start);
+            }
+            (Dialect::Jinja, "elif") | (Dialect::Askama, "elif") => {
+                let cond = self.condition(rest);
+                self.branch(Some(cond), start, end)?;
+            }
+            (_, "else") => match rest.strip_prefix("if").map(str::trim) {
+                Some(cond) if rest.starts_with("if ") => {
+                    let cond = self.condition(cond);
+                    self.branch(Some(cond), start, end)?;
+                }
+                _ => self.branch(None, start, end)?,
+            },
+            (Dialect::Jinja | Dialect::Askama, "endfor") | (Dialect::Handlebars, "/each") => {
+                self.close(true, start, end)?
+            }
+            (Dialect::Jinja | Dialect::Askama, "endif")
+            | (Dialect::Handlebars, "/if" | "/unless") => self.close(false, start, end)?,
+            _ => {
+                let marker = self.block(Block::Comment(self.source[start..end].to_string()));
+                self.push_element(marker, start, true);
+                self.push_element(marker, start, false);
+            }
+        }
+        Ok(())
+    }
+
+    fn open_for(&mut self, header: &str, start: usize) {
+        let (kind, block) = match self.dialect {
+            Dialect::Handlebars => {
+                // `items as |item index|`; the loop is filled in when it closes, since `@index`
+                // in the body makes it enumerate
+                let (expr, params) = header.split_once(" as ").unwrap_or((header, ""));
+                let mut params = params.trim().trim_matches('|').split_whitespace();
+                let pat = params.next().unwrap_or("item").to_string();
+                let index = params.next();
+                let kind = OpenKind::Each {
+                    expr: handlebars_path(expr, &self.open).unwrap_or_else(|| todo_expr(expr)),
+                    index: index.is_some(),
+                    pat: match index {
+                        Some(index) => format!("({index}, {pat})"),
+                        None => pat,
+                    },
+                };
+                let block = Block::For {
+                    pat: TokenStream::new(),
+                    expr: TokenStream::new(),
+                };
+                (kind, block)
+            }
+            Dialect::Jinja | Dialect::Askama => {
+                let (pat, expr) = self.for_header(header);
+                (
+                    OpenKind::For { expr: expr.clone() },
+                    Block::For { pat, expr },
+                )
+            }
+        };
+
+        let marker = self.block(block);
+        self.open.push(Open {
+            kind,
+            markers: vec![marker],
+            start,
+        });
+        self.push_element(marker, start, true);
+    }
+
+    /// Splits a Jinja or Askama `for` header into the loop pattern and iterator.
+    fn for_header(&self, header: &str) -> (TokenStream, TokenStream) {
+        let (pat, expr) = header.split_once(" in ").unwrap_or(("item", header));
+        let pat = pat.trim();
+        // Jinja unpacks `for key, value in ...` without parentheses
+        let pat = if pat.contains(',') && !pat.starts_with('(') {
+            format!("({pat})")
+        } else {
+            pat.to_string()
+        };
+        let pat = rust_tokens(&pat)
+            .filter(|tokens| {
+                syn::parse::Parser::parse2(syn::Pat::parse_single, tokens.clone()).is_ok()
+            })
+            .unwrap_or_else(|| quote! { item });
+        let expr = self
+            .expression(expr.trim())
+            .and_then(|expr| rust_tokens(&expr))
+            .unwrap_or_else(|| todo_tokens(expr));
+        (pat, expr)
+    }
+
+    fn open_if(&mut self, cond: TokenStream, start: usize) {
+        let chain = self.block(Block::If);
+        let branch = self.block(Block::Branch(Some(cond)));
+        self.push_element(chain, start, true);
+        self.push_element(branch, start, true);
+        self.open.push(Open {
+            kind: OpenKind::If,
+            markers: vec![chain, branch],
+            start,
+        });
+    }
+
+    /// Starts an `elif`/`else` branch of the innermost `if`, or the `else` of a loop.
+    fn branch(
+        &mut self,
+        cond: Option<TokenStream>,
+        start: usize,
+        end: usize,
+    ) -> Result<(), ConvertError> {
+        let Some(open) = self.open.last() else {
+            return Err(self.unexpected(start, end));
+        };
+
+        match &open.kind {
+            OpenKind::If => {
+                let previous = *open.markers.last().expect("if chains have a branch");
+                let branch = self.block(Block::Branch(cond));
+                self.push_element(previous, start, false);
+                self.push_element(branch, start, true);
+                self.open
+                    .last_mut()
+                    .expect("checked above")
+                    .markers
+                    .push(branch);
+            }
+            // The `else` of a loop runs when there was nothing to loop over
+            OpenKind::For { .. } | OpenKind::Each { .. } if cond.is_none() => {
+                let open = self.open.pop().expect("checked above");
+                let items = receiver(self.finish_loop(&open));
+                // The loop borrows the items, so they can still be checked for emptiness after it
+                let enumerate = matches!(open.kind, OpenKind::Each { index: true, .. });
+                if let Block::For { expr, .. } = &mut self.lowered.blocks[open.markers[0]] {
+                    *expr = if enumerate {
+                        quote! { #items.iter().enumerate() }
+                    } else {
+                        quote! { #items.iter() }
+                    };
+                }
+                self.push_element(open.markers[0], start, false);
+                let chain = self.block(Block::If);
+                let branch = self.block(Block::Branch(Some(quote! { #items.is_empty() })));
+                self.push_element(chain, start, true);
+                self.push_element(branch, start, true);
+                self.open.push(Open {
+                    kind: OpenKind::ForElse,
+                    markers: vec![chain, branch],
+                    start: open.start,
+                });
+            }
+            _ => return Err(self.unexpected(start, end)),
+        }
+        Ok(())
+    }
+
+    /// Closes the innermost block, which must be a loop if `is_loop` and an `if` otherwise.
+    fn close(&mut self, is_loop: bool, start: usize, end: usize) -> Result<(), ConvertError> {
+        let matches = match self.open.last().map(|open| &open.kind) {
+            Some(OpenKind::For { .. } | OpenKind::Each { .. } | OpenKind::ForElse) => is_loop,
+            Some(OpenKind::If) => !is_loop,
+            None => false,
+        };
+        if !matches {
+            return Err(self.unexpected(start, end));
+        }
+
+        let open = self.open.pop().expect("checked above");
+        if matches!(open.kind, OpenKind::Each { .. }) {
+            self.finish_loop(&open);
+        }
+        for marker in open.markers.iter().rev() {
+            self.push_element(*marker, start, false);
+        }
+        Ok(())
+    }
+
+    /// Fills in the `for` block of a Handlebars loop now that its body is known, and returns the
+    /// iterator.
+    fn finish_loop(&mut self, open: &Open) -> TokenStream {
+        match &open.kind {
+            OpenKind::For { expr } => expr.clone(),
+            OpenKind::Each { pat, expr, index } => {
+                let iter = if *index {
+                    format!("{expr}.iter().enumerate()")
+                } else {
+                    expr.clone()
+                };
+                let expr = rust_tokens(expr).unwrap_or_else(|| todo_tokens(expr));
+                self.lowered.blocks[open.markers[0]] = Block::For {
+                    pat: rust_tokens(pat).unwrap_or_else(|| quote! { item }),
+                    expr: rust_tokens(&iter).unwrap_or_else(|| todo_tokens(&iter)),
+                };
+                expr
+            }
+            OpenKind::If | OpenKind::ForElse => unreachable!("only loops are finished"),
+        }
+    }
+
+    /// Translates a condition, falling back to `todo!()`.
+    fn condition(&self, cond: &str) -> TokenStream {
+        // Askama's `if let Some(x) = y` is already a Rust condition
+        if self.dialect == Dialect::Askama && cond.starts_with("let ") {
+            return rust_tokens(cond).unwrap_or_else(|| todo_tokens(cond));
+        }
+        self.expression(cond)
+            .and_then(|expr| rust_tokens(&expr))
+            .unwrap_or_else(|| todo_tokens(cond))
+    }
+
+    /// Translates a template expression into Rust source, or returns `None` if it can't be.
+    fn expression(&self, source: &str) -> Option<String> {
+        let expr = match self.dialect {
+            Dialect::Jinja | Dialect::Askama => {
+                let mut parts = split_filters(source).into_iter();
+                let base = parts.next()?;
+                let mut expr = match self.dialect {
+                    Dialect::Jinja => jinja_operators(base),
+                    _ => base.trim().to_string(),
+                };
+                for filter in parts {
+                    expr = apply_filter(&expr, filter)?;
+                }
+                expr
+            }
+            Dialect::Handlebars => handlebars_path(source, &self.open)?,
+        };
+        syn::parse_str::<Expr>(&expr).ok()?;
+        Some(expr)
+    }
+
+    /// Makes the innermost Handlebars loop enumerate its items, returning the index variable.
+    fn loop_index(&mut self) -> Option<String> {
+        self.open
+            .iter_mut()
+            .rev()
+            .find_map(|open| match &mut open.kind {
+                OpenKind::Each { pat, index, .. } => {
+                    if !*index {
+                        *pat = format!("(index, {pat})");
+                        *index = true;
+                    }
+                    pat.trim_start_matches('(')
+                        .split(',')
+                        .next()
+                        .map(|index| index.trim().to_string())
+                }
+                _ => None,
+            })
+    }
+
+    fn block(&mut self, block: Block) -> usize {
+        self.lowered.blocks.push(block);
+        self.lowered.blocks.len() - 1
+    }
+
+    fn push_element(&mut self, marker: usize, offset: usize, open: bool) {
+        let tag = if open {
+            format!("<{BLOCK_PREFIX}{marker}>")
+        } else {
+            format!("</{BLOCK_PREFIX}{marker}>")
+        };
+        self.push_marker(&tag, offset);
+    }
+
+    fn push_marker(&mut self, text: &str, offset: usize) {
+        self.lowered.html.push_str(text);
+        self.lowered
+            .offsets
+            .extend(std::iter::repeat_n(offset, text.len()));
+    }
+
+    /// Copies HTML from the template, keeping track of whether it ends inside a tag.
+    fn push_html(&mut self, range: std::ops::Range<usize>) {
+        let text = &self.source[range.clone()];
+        let mut chars = text.char_indices().peekable();
+        while let Some((_, c)) = chars.next() {
+            self.tag_state = match (self.tag_state, c) {
+                (None, '<') => match chars.peek() {
+                    Some((_, next)) if next.is_ascii_alphabetic() || *next == '/' => Some('<'),
+                    _ => None,
+                },
+                (Some('<'), '>') => None,
+                (Some('<'), '"' | '\'') => Some(c),
+                (Some(quote), c) if quote != '<' && c == quote => Some('<'),
+                (state, _) => state,
+            };
+        }
+        self.lowered.html.push_str(text);
+        self.lowered.offsets.extend(range);
+    }
+
+    fn unsupported(&self, construct: &str, start: usize, end: usize) -> ConvertError {
+        ConvertError::Unsupported {
+            construct: construct.to_string(),
+            span: Some(Span::from_offsets(self.source, start, end)),
+        }
+    }
+
+    fn unexpected(&self, start: usize, end: usize) -> ConvertError {
+        ConvertError::Parse {
+            message: format!("unexpected `{}`", &self.source[start..end]),
+            span: Some(Span::from_offsets(self.source, start, end)),
+        }
+    }
+}
+
+impl Lowered {
+    fn original_offset(&self, offset: usize) -> usize {
+        self.offsets
+            .get(offset)
+            .or(self.offsets.last())
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Replaces the block markers in rosetta's output with loops, `if` chains and comments.
+    /// Comment placeholders are added to `placeholders`.
+    pub(crate) fn restore_blocks(&self, nodes: &mut [BodyNode], placeholders: &mut Vec<String>) {
+        for node in nodes.iter_mut() {
+            let BodyNode::Element(element) = node else {
+                continue;
+            };
+            self.restore_blocks(&mut element.children, placeholders);
+
+            // Branches are taken apart by the `if` chain around them
+            let Some(block) = self.block_of(&element.name.to_string()) else {
+                continue;
+            };
+            if let Block::Branch(_) = block {
+                continue;
+            }
+            let children = std::mem::take(&mut element.children);
+
+            *node = match block {
+                Block::For { pat, expr } => {
+                    let mut for_loop: ForLoop = syn::parse2(quote! { for #pat in #expr {} })
+                        .expect("template loops are valid RSX");
+                    for_loop.body = TemplateBody::new(children);
+                    BodyNode::ForLoop(for_loop)
+                }
+                Block::If => match self.if_chain(children) {
+                    Some(chain) => BodyNode::IfChain(chain),
+                    None => continue,
+                },
+                Block::Branch(_) => unreachable!("branches are skipped above"),
+                Block::Comment(text) => {
+                    placeholders.push(line_comment(text));
+                    let ident = Ident::new(
+                        &placeholder(placeholders.len() - 1),
+                        proc_macro2::Span::call_site(),
+                    );
+                    syn::parse2(quote! { {#ident} }).expect("comment placeholders are valid RSX")
+                }
+            };
+        }
+    }
+
+    /// Builds an `if` chain from its branch markers.
+    fn if_chain(&self, branches: Vec<BodyNode>) -> Option<IfChain> {
+        let mut chain: Option<IfChain> = None;
+        let mut otherwise = None;
+        for branch in branches.into_iter().rev() {
+            let BodyNode::Element(element) = branch else {
+                continue;
+            };
+            let Some(Block::Branch(cond)) = self.block_of(&element.name.to_string()) else {
+                continue;
+            };
+            let body = TemplateBody::new(element.children);
+            let Some(cond) = cond else {
+                otherwise = Some(body);
+                continue;
+            };
+
+            let mut branch: IfChain = match (&chain, &otherwise) {
+                (None, Some(_)) => syn::parse2(quote! { if #cond {} else {} }),
+                _ => syn::parse2(quote! { if #cond {} }),
+            }
+            .expect("template conditions are valid RSX");
+            branch.then_branch = body;
+            match chain.take() {
+                Some(next) => branch.else_if_branch = Some(Box::new(next)),
+                None => branch.else_branch = otherwise.take(),
+            }
+            chain = Some(branch);
+        }
+        chain
+    }
+
+    /// The block a marker element stands for.
+    fn block_of(&self, name: &str) -> Option<&Block> {
+        name.strip_prefix(BLOCK_PREFIX)
+            .and_then(|index| index.parse::<usize>().ok())
+            .and_then(|index| self.blocks.get(index))
+    }
+
+    /// Replaces interpolation markers in text and attribute values with their `{expr}` segments.
+    /// Runs after the pipeline has escaped braces, so the segments stay interpolations.
+    pub(crate) fn restore_interpolations(&self, nodes: &mut [BodyNode]) {
+        if self.interpolations.is_empty() {
+            return;
+        }
+
+        for node in nodes {
+            match node {
+                BodyNode::Text(text) => {
+                    let source = text.input.source.value();
+                    if source.contains(EXPR_PREFIX) {
+                        *text = TextNode::from_text(&self.fill(&source));
+                    }
+                }
+                BodyNode::Element(element) => {
+                    for attribute in &mut element.raw_attributes {
+                        if let AttributeValue::AttrLiteral(HotLiteral::Fmted(value)) =
+                            &mut attribute.value
+                        {
+                            let source = value.formatted_input.source.value();
+                            if source.contains(EXPR_PREFIX)
+                                && let HotLiteral::Fmted(filled) =
+                                    HotLiteral::from_raw_text(&self.fill(&source))
+                            {
+                                *value = filled;
+                            }
+                        }
+                    }
+                    self.restore_interpolations(&mut element.children);
+                }
+                BodyNode::ForLoop(for_loop) => {
+                    self.restore_interpolations(&mut for_loop.body.roots)
+                }
+                BodyNode::IfChain(chain) => {
+                    let mut chain = Some(chain);
+                    while let Some(branch) = chain {
+                        self.restore_interpolations(&mut branch.then_branch.roots);
+                        if let Some(body) = &mut branch.else_branch {
+                            self.restore_interpolations(&mut body.roots);
+                        }
+                        chain = branch.else_if_branch.as_deref_mut();
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn fill(&self, text: &str) -> String {
+        let mut filled = text.to_string();
+        // Replace from the highest index down so `_1__` never matches inside `_11__`
+        for (index, replacement) in self.interpolations.iter().enumerate().rev() {
+            filled = filled.replace(&format!("{EXPR_PREFIX}{index}__"), replacement);
+        }
+        filled
+    }
+}
+
+/// Formats a comment as `//` lines.
+fn line_comment(text: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| format!("// {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Splits a Jinja expression at the `|` of each filter, ignoring `||` and anything quoted or in
+/// brackets.
+fn split_filters(source: &str) -> Vec<&str> {
+    let bytes = source.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut start = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        match (quote, byte) {
+            (Some(q), b) if b == q => quote = None,
+            (Some(_), _) => {}
+            (None, b'"' | b'\'') => quote = Some(byte),
+            (None, b'(' | b'[' | b'{') => depth += 1,
+            (None, b')' | b']' | b'}') => depth -= 1,
+            (None, b'|')
+                if depth == 0
+                    && bytes.get(i + 1) != Some(&b'|')
+                    && (i == 0 || bytes[i - 1] != b'|') =>
+            {
+                parts.push(source[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(source[start..].trim());
+    parts
+}
+
+/// Applies a Jinja/Askama filter to a translated expression, or returns `None` for filters with no
+/// obvious Rust equivalent.
+fn apply_filter(expr: &str, filter: &str) -> Option<String> {
+    let (name, args) = match filter.split_once('(') {
+        Some((name, args)) => (name.trim(), Some(args.strip_suffix(')')?.trim())),
+        None => (filter.trim(), None),
+    };
+    let receiver = if expr
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '.' | '(' | ')' | '[' | ']'))
+    {
+        expr.to_string()
+    } else {
+        format!("({expr})")
+    };
+
+    Some(match (name, args) {
+        // Dioxus escapes text itself
+        ("safe" | "e" | "escape", None) => expr.to_string(),
+        ("upper", None) => format!("{receiver}.to_uppercase()"),
+        ("lower", None) => format!("{receiver}.to_lowercase()"),
+        ("trim", None) => format!("{receiver}.trim()"),
+        ("length" | "count", None) => format!("{receiver}.len()"),
+        ("join", Some(separator)) => format!("{receiver}.join({})", jinja_operators(separator)),
+        _ => return None,
+    })
+}
+
+/// Rewrites Jinja's Python-style operators, literals and single-quoted strings as Rust.
+fn jinja_operators(source: &str) -> String {
+    let mut out = String::with_capacity(source.len());
+    let mut chars = source.trim().chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' | '"' => {
+                out.push('"');
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            out.push('\\');
+                            if let Some(escaped) = chars.next() {
+                                out.push(escaped);
+                            }
+                        }
+                        next if next == c => break,
+                        '"' => out.push_str("\\\""),
+                        next => out.push(next),
+                    }
+                }
+                out.push('"');
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                // Field names after a `.` are never keywords
+                let member = out.ends_with('.');
+                out.push_str(match word.as_str() {
+                    _ if member => &word,
+                    "and" => "&&",
+                    "or" => "||",
+                    "not" => "!",
+                    "True" | "true" => "true",
+                    "False" | "false" => "false",
+                    "None" | "none" => "None",
+                    _ => &word,
+                });
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Translates a Handlebars path (`title`, `this.title`, `../title`, `@index`) into a Rust
+/// expression, or returns `None` for helper calls and other expressions with arguments.
+fn handlebars_path(source: &str, open: &[Open]) -> Option<String> {
+    let source = source.trim();
+    if source.contains(char::is_whitespace) {
+        return None;
+    }
+    let item = open.iter().rev().find_map(|open| match &open.kind {
+        OpenKind::Each { pat, .. } => Some(
+            pat.trim_end_matches(')')
+                .rsplit([',', '('])
+                .next()
+                .unwrap_or("item")
+                .trim()
+                .to_string(),
+        ),
+        _ => None,
+    });
+
+    let mut path = source;
+    while let Some(rest) = path.strip_prefix("../") {
+        path = rest;
+    }
+    let path = path.replace('/', ".");
+    let path = match (path.as_str(), &item) {
+        ("this" | ".", Some(item)) => item.clone(),
+        (_, Some(item)) if path.starts_with("this.") => format!("{item}{}", &path[4..]),
+        ("@index", _) => "index".to_string(),
+        _ => path,
+    };
+    syn::parse_str::<Expr>(&path).ok()?;
+    Some(path)
+}
+
+/// Wraps an expression in parentheses unless it can take a method call or `!` as it is.
+fn receiver(expr: TokenStream) -> TokenStream {
+    match syn::parse2::<Expr>(expr.clone()) {
+        Ok(
+            Expr::Path(_)
+            | Expr::Field(_)
+            | Expr::MethodCall(_)
+            | Expr::Call(_)
+            | Expr::Index(_)
+            | Expr::Macro(_)
+            | Expr::Paren(_),
+        ) => expr,
+        _ => quote! { (#expr) },
+    }
+}
+
+fn todo_tokens(source: &str) -> TokenStream {
+    rust_tokens(&todo_expr(source)).expect("todo!() placeholders are valid Rust")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ConvertError, InputFormat, Options, convert};
+
+    fn convert_as(input: InputFormat, template: &str) -> Result<String, ConvertError> {
+        let options = Options {
+            input,
+            ..Options::default()
+        };
+        convert(template, &options)
+    }
+
+    #[test]
+    fn jinja_blocks_become_control_flow() {
+        let rsx = convert_as(
+            InputFormat::Jinja,
+            r#"<ul class="{{ list_class }}">
+  {% for user in users %}
+    <li title="{{ user.name|upper }}">{{ user.name }}</li>
+  {% else %}
+    <li>No users</li>
+  {% endfor %}
+</ul>
+{% if admin and not readonly %}<button>Edit</button>{% elif guest %}<a href="/login">Log in</a>{% else %}<p>Read only</p>{% endif %}"#,
+        )
+        .unwrap();
+
+        for expected in [
+            r#"ul { class: "{list_class}","#,
+            "for user in users.iter() {",
+            r#"li { title: "{user.name.to_uppercase()}", "{user.name}" }"#,
+            "if users.is_empty() {",
+            "if admin && !readonly {",
+            "} else if guest {",
+            "} else {",
+        ] {
+            assert!(rsx.contains(expected), "missing {expected}:\n{rsx}");
+        }
+    }
+
+    #[test]
+    fn handlebars_paths_are_translated() {
+        let rsx = convert_as(
+            InputFormat::Handlebars,
+            "<ol>{{#each items}}<li>{{@index}}. {{this.title}} in {{../name}}</li>{{/each}}</ol>{{#unless done}}<b>Pending</b>{{/unless}}",
+        )
+        .unwrap();
+
+        for expected in [
+            "for (index, item) in items.iter().enumerate() {",
+            r#"li { "{index}. {item.title} in {name}" }"#,
+            "if !done {",
+        ] {
+            assert!(rsx.contains(expected), "missing {expected}:\n{rsx}");
+        }
+    }
+
+    #[test]
+    fn unknown_constructs_are_kept_as_comments() {
+        let rsx = convert_as(
+            InputFormat::Jinja,
+            "<footer>{# site footer #}{% include 'footer.html' %}<p>Hi {{ body|markdown }}</p></footer>",
+        )
+        .unwrap();
+
+        assert!(rsx.contains("// site footer"), "{rsx}");
+        assert!(rsx.contains("// {% include 'footer.html' %}"), "{rsx}");
+        assert!(
+            rsx.contains(r#"p { "Hi {todo!(\"body|markdown\")}" }"#),
+            "{rsx}"
+        );
+    }
+
+    #[test]
+    fn loop_patterns_and_raw_blocks() {
+        let rsx = convert_as(
+            InputFormat::Jinja,
+            "<dl>{% for key, value in pairs %}<dt>{{ key }}</dt>{% endfor %}</dl>\
+             <pre>{% raw %}{{ not_an_expression }} {% if %}{% endraw %}</pre>",
+        )
+        .unwrap();
+
+        assert!(rsx.contains("for (key, value) in pairs {"), "{rsx}");
+        assert!(
+            rsx.contains(r#"pre { "{{{{ not_an_expression }}}} {{% if %}}" }"#),
+            "{rsx}"
+        );
+        assert!(convert_as(InputFormat::Jinja, "{% raw %}<p>{{ x }}</p>").is_err());
+    }
+
+    #[test]
+    fn askama_keeps_rust_conditions() {
+        let rsx = convert_as(
+            InputFormat::Askama,
+            "{% if let Some(user) = user %}<p>{{ user.name }}</p>{% endif %}",
+        )
+        .unwrap();
+
+        assert!(rsx.contains("if let Some(user) = user {"), "{rsx}");
+    }
+
+    #[test]
+    fn errors_point_into_the_template() {
+        let error = convert_as(InputFormat::Jinja, "<div>\n  {% endfor %}\n</div>").unwrap_err();
+        let span = error.span().unwrap();
+        assert_eq!((span.start.line, span.start.column), (2, 3));
+
+        let error = convert_as(
+            InputFormat::Jinja,
+            r#"<div {% if x %}hidden{% endif %}></div>"#,
+        )
+        .unwrap_err();
+        assert!(matches!(error, ConvertError::Unsupported { .. }), "{error}");
+    }
+}
@@ -0,0 +1,470 @@
+//! Reverse conversion: serializes an RSX body back into formatted HTML.
+//!
+//! Static markup round-trips exactly. Rust-only constructs (components, loops, conditionals and
+//! expressions) have no HTML equivalent, so they are written as comments, and interpolations in
+//! strings are kept as `{expr}` text.
+
+use crate::whitespace::preserves_whitespace;
+use crate::{ConvertError, Position, Span, is_boolean_attribute};
+use dioxus_core_types::HotReloadingContext;
+use dioxus_html::HtmlCtx;
+use dioxus_rsx::{
+    AttributeName, AttributeValue, BodyNode, CallBody, ElementName, HotLiteral, IfChain, IfmtInput,
+    TemplateBody,
+};
+use quote::ToTokens;
+use syn::spanned::Spanned;
+
+/// Elements that never have children or a closing tag.
+pub(crate) const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+/// Dioxus attributes that set the initial state of a form control, with the HTML attribute that
+/// does the same.
+const INITIAL_ATTRIBUTES: [(&str, &str); 3] = [
+    ("initial_value", "value"),
+    ("initial_checked", "checked"),
+    ("initial_selected", "selected"),
+];
+
+/// Indentation used for nested elements.
+const INDENT: &str = "    ";
+
+/// Converts an RSX body, optionally wrapped in `rsx! { ... }`, into formatted HTML.
+///
+/// The input is parsed as the same `CallBody` that [`convert`](crate::convert) produces, so
+/// parse errors carry spans into `rsx`.
+pub fn rsx_to_html(rsx: &str) -> Result<String, ConvertError> {
+    let body = parse_body(rsx)?;
+    let mut writer = HtmlWriter::default();
+    writer.write_nodes(&body.body.roots, 0);
+    Ok(writer.out.trim().to_string())
+}
+
+fn parse_body(rsx: &str) -> Result<CallBody, ConvertError> {
+    let parsed = if rsx.trim_start().starts_with("rsx!") {
+        syn::parse_str::<syn::Macro>(rsx).and_then(|mac| mac.parse_body::<CallBody>())
+    } else {
+        syn::parse_str::<CallBody>(rsx)
+    };
+
+    parsed.map_err(|err| {
+        let (start, end) = (err.span().start(), err.span().end());
+        // proc-macro2 columns are 0-based, ours are 1-based
+        let position = |line: usize, column: usize| Position {
+            line,
+            column: column + 1,
+        };
+        ConvertError::Parse {
+            message: err.to_string(),
+            span: (start.line > 0).then(|| Span {
+                start: position(start.line, start.column),
+                end: position(end.line, end.column),
+            }),
+        }
+    })
+}
+
+#[derive(Default)]
+struct HtmlWriter {
+    out: String,
+}
+
+impl HtmlWriter {
+    fn line(&mut self, depth: usize, text: &str) {
+        for _ in 0..depth {
+            self.out.push_str(INDENT);
+        }
+        self.out.push_str(text);
+        self.out.push('\n');
+    }
+
+    fn comment(&mut self, depth: usize, text: &str) {
+        self.line(depth, &format!("<!-- {} -->", text.replace("--", "- -")));
+    }
+
+    fn write_nodes(&mut self, nodes: &[BodyNode], depth: usize) {
+        for node in nodes {
+            self.write_node(node, depth);
+        }
+    }
+
+    fn write_node(&mut self, node: &BodyNode, depth: usize) {
+        match node {
+            BodyNode::Element(element) => self.write_element(element, depth),
+            BodyNode::Text(text) => {
+                let text = escape_text(&ifmt_text(&text.input.formatted_input));
+                for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                    self.line(depth, line.trim());
+                }
+            }
+            BodyNode::Component(component) => {
+                let name = tokens_text(&component.name);
+                self.comment(depth, &format!("{name} component"));
+                self.write_template(&component.children, depth);
+            }
+            BodyNode::RawExpr(expr) => self.comment(depth, &tokens_text(&expr.expr)),
+            BodyNode::ForLoop(for_loop) => {
+                self.comment(
+                    depth,
+                    &format!(
+                        "for {} in {}",
+                        tokens_text(&for_loop.pat),
+                        tokens_text(&for_loop.expr)
+                    ),
+                );
+                self.write_template(&for_loop.body, depth);
+                self.comment(depth, "end for");
+            }
+            BodyNode::IfChain(chain) => {
+                self.write_if_chain(chain, depth, "if");
+                self.comment(depth, "end if");
+            }
+        }
+    }
+
+    fn write_template(&mut self, body: &TemplateBody, depth: usize) {
+        self.write_nodes(&body.roots, depth);
+    }
+
+    fn write_if_chain(&mut self, chain: &IfChain, depth: usize, keyword: &str) {
+        self.comment(depth, &format!("{keyword} {}", tokens_text(&chain.cond)));
+        self.write_template(&chain.then_branch, depth);
+
+        if let Some(else_if) = &chain.else_if_branch {
+            self.write_if_chain(else_if, depth, "else if");
+        } else if let Some(else_branch) = &chain.else_branch {
+            self.comment(depth, "else");
+            self.write_template(else_branch, depth);
+        }
+    }
+
+    fn write_element(&mut self, element: &dioxus_rsx::Element, depth: usize) {
+        let tag = StartTag::new(element);
+
+        if tag.is_void() {
+            self.line(depth, &tag.html);
+            return;
+        }
+
+        let close_tag = format!("</{}>", tag.name);
+        if let Some(inner_html) = &tag.inner_html {
+            self.line(depth, &format!("{}{inner_html}{close_tag}", tag.html));
+            return;
+        }
+
+        // Indenting would change the content of whitespace-sensitive elements
+        if tag.preserves_whitespace {
+            let content = inline_nodes(&element.children);
+            // A newline right after the opening tag of `pre` and `textarea` is dropped by parsers
+            let newline =
+                if matches!(tag.name.as_str(), "pre" | "textarea") && content.starts_with('\n') {
+                    "\n"
+                } else {
+                    ""
+                };
+            self.line(depth, &format!("{}{newline}{content}{close_tag}", tag.html));
+            return;
+        }
+
+        // Elements whose content is a single line of text stay on one line
+        match element.children.as_slice() {
+            [] => self.line(depth, &format!("{}{close_tag}", tag.html)),
+            [BodyNode::Text(text)] if !ifmt_text(&text.input.formatted_input).contains('\n') => {
+                let text = escape_text(&ifmt_text(&text.input.formatted_input));
+                self.line(depth, &format!("{}{text}{close_tag}", tag.html));
+            }
+            children => {
+                self.line(depth, &tag.html);
+                self.write_nodes(children, depth + 1);
+                self.line(depth, &close_tag);
+            }
+        }
+    }
+}
+
+/// The HTML opening tag of an RSX element.
+struct StartTag {
+    name: String,
+    html: String,
+    /// Raw markup from `dangerous_inner_html`.
+    inner_html: Option<String>,
+    preserves_whitespace: bool,
+}
+
+impl StartTag {
+    fn new(element: &dioxus_rsx::Element) -> Self {
+        let rsx_name = match &element.name {
+            ElementName::Ident(ident) => ident.to_string(),
+            ElementName::Custom(name) => name.value(),
+        };
+        let name = match &element.name {
+            ElementName::Ident(_) => HtmlCtx::map_element(&rsx_name)
+                .map_or(rsx_name.as_str(), |(name, _)| name)
+                .trim_start_matches("r#")
+                .to_string(),
+            ElementName::Custom(_) => rsx_name.clone(),
+        };
+
+        let mut attributes: Vec<(String, Option<String>)> = Vec::new();
+        let mut styles = Vec::new();
+        let mut inner_html = None;
+
+        for attribute in &element.raw_attributes {
+            let rsx_attr = match &attribute.name {
+                AttributeName::BuiltIn(ident) => ident.to_string(),
+                AttributeName::Custom(name) => name.value(),
+                AttributeName::Spread(_) => continue,
+            };
+
+            // Event handlers and keys only mean something to Dioxus
+            if matches!(attribute.value, AttributeValue::EventTokens(_)) || rsx_attr == "key" {
+                continue;
+            }
+
+            let value = match &attribute.value {
+                AttributeValue::AttrLiteral(HotLiteral::Bool(value)) if !value.value => continue,
+                AttributeValue::AttrLiteral(HotLiteral::Bool(_)) => None,
+                AttributeValue::AttrLiteral(HotLiteral::Fmted(text)) => {
+                    Some(ifmt_text(&text.formatted_input))
+                }
+                AttributeValue::AttrLiteral(HotLiteral::Int(int)) => Some(int.to_string()),
+                AttributeValue::AttrLiteral(HotLiteral::Float(float)) => Some(float.to_string()),
+                value => Some(format!("{{{}}}", tokens_text(value))),
+            };
+
+            if rsx_attr == "dangerous_inner_html" {
+                inner_html = value;
+                continue;
+            }
+
+            let initial = INITIAL_ATTRIBUTES
+                .iter()
+                .find(|(initial, _)| *initial == rsx_attr);
+            let html_attr = match &attribute.name {
+                AttributeName::BuiltIn(_) if let Some((_, html_attr)) = initial => {
+                    html_attr.to_string()
+                }
+                AttributeName::BuiltIn(_) => match HtmlCtx::map_attribute(&rsx_name, &rsx_attr) {
+                    Some((property, Some("style"))) => {
+                        styles.push(format!("{property}: {}", value.unwrap_or_default()));
+                        continue;
+                    }
+                    Some((html_attr, _)) => html_attr.to_string(),
+                    None => rsx_attr.trim_start_matches("r#").replace('_', "-"),
+                },
+                _ => rsx_attr,
+            };
+            // Dioxus only turns a boolean attribute on for `true` or `"true"`, so `checked: "checked"`
+            // renders unchecked
+            if is_boolean_attribute(&html_attr)
+                && let AttributeValue::AttrLiteral(HotLiteral::Fmted(text)) = &attribute.value
+                && text.formatted_input.is_static()
+            {
+                if value.as_deref() == Some("true") {
+                    attributes.push((html_attr, None));
+                }
+                continue;
+            }
+            attributes.push((html_attr, value));
+        }
+
+        // Individual style attributes are merged into the `style` string
+        if !styles.is_empty() {
+            let declarations = styles.join("; ");
+            match attributes.iter_mut().find(|(name, _)| name == "style") {
+                Some((_, Some(style))) => {
+                    *style = format!("{}; {declarations}", style.trim_end_matches([';', ' ']));
+                }
+                _ => attributes.push(("style".to_string(), Some(declarations))),
+            }
+        }
+
+        let style = attributes
+            .iter()
+            .find(|(name, _)| name == "style")
+            .and_then(|(_, value)| value.as_deref());
+        let preserves_whitespace = preserves_whitespace(&name, style);
+
+        let mut open_tag = format!("<{name}");
+        for (attr, value) in &attributes {
+            match value {
+                Some(value) => {
+                    open_tag.push_str(&format!(" {attr}=\"{}\"", escape_attribute(value)))
+                }
+                None => open_tag.push_str(&format!(" {attr}")),
+            }
+        }
+        open_tag.push('>');
+
+        Self {
+            name,
+            html: open_tag,
+            inner_html,
+            preserves_whitespace,
+        }
+    }
+
+    fn is_void(&self) -> bool {
+        VOID_ELEMENTS.contains(&self.name.as_str())
+    }
+}
+
+/// Serializes nodes without adding any whitespace, for the content of whitespace-sensitive
+/// elements.
+fn inline_nodes(nodes: &[BodyNode]) -> String {
+    let mut out = String::new();
+    for node in nodes {
+        match node {
+            BodyNode::Text(text) => {
+                out.push_str(&escape_text(&ifmt_text(&text.input.formatted_input)))
+            }
+            BodyNode::Element(element) => {
+                let tag = StartTag::new(element);
+                out.push_str(&tag.html);
+                if tag.is_void() {
+                    continue;
+                }
+                match &tag.inner_html {
+                    Some(inner_html) => out.push_str(inner_html),
+                    None => out.push_str(&inline_nodes(&element.children)),
+                }
+                out.push_str(&format!("</{}>", tag.name));
+            }
+            // Comments for Rust-only constructs can't be kept inline, so write them as usual
+            node => {
+                let mut writer = HtmlWriter::default();
+                writer.write_node(node, 0);
+                out.push_str(writer.out.trim_end());
+            }
+        }
+    }
+    out
+}
+
+/// Renders a formatted string, keeping interpolations as `{expr}`.
+fn ifmt_text(input: &IfmtInput) -> String {
+    if let Some(text) = input.to_static() {
+        return text;
+    }
+
+    // Interpolations are re-parsed from inside the literal and carry no source text, so undo
+    // the `{{`/`}}` escapes on the literal value instead of printing each segment
+    let source = input.source.value();
+    let mut text = String::with_capacity(source.len());
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        text.push(c);
+        if matches!(c, '{' | '}') {
+            chars.next_if_eq(&c);
+        }
+    }
+    text
+}
+
+/// Renders Rust syntax back to source text, preferring the original spelling from the input.
+fn tokens_text(tokens: &impl ToTokens) -> String {
+    tokens
+        .span()
+        .source_text()
+        .unwrap_or_else(|| tokens.to_token_stream().to_string())
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Options, convert};
+
+    /// Converts `html` to RSX and back.
+    fn round_trip(html: &str) -> String {
+        let rsx = convert(html, &Options::default()).unwrap();
+        rsx_to_html(&rsx).unwrap()
+    }
+
+    #[test]
+    fn void_elements_have_no_closing_tag() {
+        assert_eq!(
+            round_trip(r#"<div><img src="a.png" alt="A"><br/><hr></div>"#),
+            "<div>\n    <img alt=\"A\" src=\"a.png\">\n    <br>\n    <hr>\n</div>"
+        );
+        assert_eq!(
+            rsx_to_html(r#"source { src: "a.webm" } wbr {}"#).unwrap(),
+            "<source src=\"a.webm\">\n<wbr>"
+        );
+    }
+
+    #[test]
+    fn boolean_attributes_are_valueless() {
+        assert_eq!(
+            round_trip(r#"<input type="checkbox" checked disabled><button hidden>x</button>"#),
+            "<input checked disabled type=\"checkbox\">\n<button hidden>x</button>"
+        );
+        assert_eq!(
+            rsx_to_html(r#"input { disabled: false, checked: true, r#type: "text" }"#).unwrap(),
+            "<input checked type=\"text\">"
+        );
+        // Strings are rendered the way Dioxus sets them: only "true" turns the attribute on
+        assert_eq!(
+            rsx_to_html(r#"input { checked: "checked", disabled: "", required: "true" }"#).unwrap(),
+            "<input required>"
+        );
+    }
+
+    #[test]
+    fn initial_values_become_html_attributes() {
+        assert_eq!(
+            rsx_to_html(
+                r#"input { initial_value: "y" } input { r#type: "checkbox", initial_checked: true } option { initial_selected: true, "A" }"#
+            )
+            .unwrap(),
+            "<input value=\"y\">\n<input type=\"checkbox\" checked>\n<option selected>A</option>"
+        );
+
+        let options = Options {
+            input: crate::InputFormat::Jsx,
+            ..Options::default()
+        };
+        let rsx = convert(
+            r#"<form><input defaultValue="y" /><input type="checkbox" defaultChecked /></form>"#,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            rsx_to_html(&rsx).unwrap(),
+            "<form>\n    <input value=\"y\">\n    <input type=\"checkbox\" checked>\n</form>"
+        );
+    }
+
+    #[test]
+    fn text_and_attributes_are_escaped() {
+        let html = r#"<p title="a &quot;b&quot; &amp; <c>">1 &lt; 2 &amp;&amp; {x} &gt; 0</p>"#;
+        assert_eq!(round_trip(html), html);
+        assert_eq!(
+            rsx_to_html(r#"div { class: "a{{b}}", "{name} & <i>" }"#).unwrap(),
+            "<div class=\"a{b}\">{name} &amp; &lt;i&gt;</div>"
+        );
+    }
+
+    #[test]
+    fn inner_html_is_written_verbatim() {
+        let html = rsx_to_html(r#"div { class: "x", dangerous_inner_html: "<b>bold</b> &amp;" }"#)
+            .unwrap();
+        assert_eq!(html, "<div class=\"x\"><b>bold</b> &amp;</div>");
+        // Converting back parses the inner markup into nodes
+        let rsx = convert(&html, &Options::default()).unwrap();
+        assert!(rsx.contains(r#"b { "bold" }"#), "{rsx}");
+        assert!(rsx.contains(r#"" &""#), "{rsx}");
+    }
+}
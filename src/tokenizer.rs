@@ -0,0 +1,272 @@
+//! A small HTML tokenizer for the preprocessor.
+//!
+//! It follows the shape of the tokenizer in the HTML spec closely enough to tell markup from text:
+//! `<` only opens a tag when followed by a letter, comments, CDATA sections and doctypes are
+//! skipped whole, attribute values are read with their quoting rules, and the content of raw text
+//! elements like `script` runs to the matching end tag. Tokens carry byte ranges into the input
+//! rather than copies, so the preprocessor can keep track of where its output came from.
+
+use std::ops::Range;
+
+/// Elements whose content is text up to the matching end tag, even if it contains `<`.
+pub(crate) const RAW_TEXT_ELEMENTS: [&str; 4] = ["script", "style", "title", "textarea"];
+
+/// Raw text elements whose content can contain character references, so it is still text.
+const ESCAPABLE_RAW_TEXT_ELEMENTS: [&str; 2] = ["title", "textarea"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Token {
+    pub kind: TokenKind,
+    /// Byte range of the whole token in the input.
+    pub span: Range<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum TokenKind {
+    /// Character data, including `<` that doesn't start markup.
+    Text,
+    /// The content of `script` or `style`, which must be copied verbatim.
+    RawText,
+    StartTag(Tag),
+    EndTag {
+        /// Lowercased tag name.
+        name: String,
+    },
+    /// `<!-- ... -->`
+    Comment,
+    /// `<![CDATA[ ... ]]>`
+    Cdata,
+    /// `<!DOCTYPE ...>`, `<?...>` and other declarations the spec treats as bogus comments.
+    Declaration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Tag {
+    /// Lowercased tag name.
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    /// The tag ends with `/>`.
+    pub self_closing: bool,
+}
+
+impl Tag {
+    /// Returns the value of the first attribute called `name` (ASCII case-insensitive).
+    pub fn attribute<'a>(&self, html: &'a str, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|attribute| html[attribute.name.clone()].eq_ignore_ascii_case(name))
+            .and_then(|attribute| attribute.value.clone())
+            .map(|value| &html[value])
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Attribute {
+    /// Byte range of the whole attribute, including the value and its quotes.
+    pub span: Range<usize>,
+    /// Byte range of the attribute name.
+    pub name: Range<usize>,
+    /// Byte range of the value, without its quotes.
+    pub value: Option<Range<usize>>,
+}
+
+/// Splits `html` into tokens that cover the input exactly, in order.
+pub(crate) fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut text_start = 0;
+    let mut pos = 0;
+
+    while let Some(found) = html[pos..].find('<') {
+        let start = pos + found;
+        let Some((kind, end)) = markup(html, start) else {
+            pos = start + 1;
+            continue;
+        };
+
+        push_text(&mut tokens, TokenKind::Text, text_start..start);
+        let raw_text = match &kind {
+            TokenKind::StartTag(tag) if !tag.self_closing => RAW_TEXT_ELEMENTS
+                .contains(&tag.name.as_str())
+                .then(|| tag.name.clone()),
+            _ => None,
+        };
+        tokens.push(Token {
+            kind,
+            span: start..end,
+        });
+        pos = end;
+        text_start = end;
+
+        if let Some(name) = raw_text {
+            let content_end = raw_text_end(html, end, &name);
+            let kind = if ESCAPABLE_RAW_TEXT_ELEMENTS.contains(&name.as_str()) {
+                TokenKind::Text
+            } else {
+                TokenKind::RawText
+            };
+            push_text(&mut tokens, kind, end..content_end);
+            pos = content_end;
+            text_start = content_end;
+        }
+    }
+    push_text(&mut tokens, TokenKind::Text, text_start..html.len());
+
+    tokens
+}
+
+fn push_text(tokens: &mut Vec<Token>, kind: TokenKind, span: Range<usize>) {
+    if !span.is_empty() {
+        tokens.push(Token { kind, span });
+    }
+}
+
+/// Reads the markup starting with the `<` at `start`, returning its kind and end offset, or `None`
+/// if the `<` is just text.
+fn markup(html: &str, start: usize) -> Option<(TokenKind, usize)> {
+    let rest = &html[start..];
+
+    if rest.starts_with("<!--") {
+        // `<!-->` and `<!--->` are complete (empty) comments
+        let end = ["<!-->", "<!--->"]
+            .iter()
+            .find(|abrupt| rest.starts_with(**abrupt))
+            .map(|abrupt| abrupt.len())
+            .or_else(|| rest[4..].find("-->").map(|end| 4 + end + 3))
+            .unwrap_or(rest.len());
+        return Some((TokenKind::Comment, start + end));
+    }
+
+    if rest.starts_with("<![CDATA[") {
+        let end = rest.find("]]>").map_or(rest.len(), |end| end + 3);
+        return Some((TokenKind::Cdata, start + end));
+    }
+
+    let mut chars = rest[1..].chars();
+    match chars.next()? {
+        '!' | '?' => {
+            let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+            Some((TokenKind::Declaration, start + end))
+        }
+        '/' => match chars.next()? {
+            c if c.is_ascii_alphabetic() => {
+                let (tag, end) = tag(html, start + 2);
+                Some((TokenKind::EndTag { name: tag.name }, end))
+            }
+            // `</>` is dropped by browsers and `</ ...>` is a bogus comment; neither is text
+            _ => {
+                let end = rest.find('>').map_or(rest.len(), |end| end + 1);
+                Some((TokenKind::Declaration, start + end))
+            }
+        },
+        c if c.is_ascii_alphabetic() => {
+            let (tag, end) = tag(html, start + 1);
+            Some((TokenKind::StartTag(tag), end))
+        }
+        _ => None,
+    }
+}
+
+/// Reads a tag name and its attributes starting at `pos`, up to and including the closing `>`.
+///
+/// An unterminated tag runs to the end of the input.
+fn tag(html: &str, mut pos: usize) -> (Tag, usize) {
+    let bytes = html.as_bytes();
+    let is_space = |b: u8| b.is_ascii_whitespace();
+
+    let name_start = pos;
+    while pos < bytes.len() && !is_space(bytes[pos]) && !matches!(bytes[pos], b'/' | b'>') {
+        pos += 1;
+    }
+    let mut tag = Tag {
+        name: html[name_start..pos].to_ascii_lowercase(),
+        attributes: Vec::new(),
+        self_closing: false,
+    };
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'>' => return (tag, pos + 1),
+            b'/' => {
+                pos += 1;
+                tag.self_closing = bytes.get(pos) == Some(&b'>');
+            }
+            b if is_space(b) => pos += 1,
+            _ => {
+                // The first character of a name may be `=`, so it is always consumed
+                let name_start = pos;
+                pos += 1;
+                while pos < bytes.len()
+                    && !is_space(bytes[pos])
+                    && !matches!(bytes[pos], b'/' | b'>' | b'=')
+                {
+                    pos += 1;
+                }
+                let name = name_start..pos;
+
+                let mut after_name = pos;
+                while after_name < bytes.len() && is_space(bytes[after_name]) {
+                    after_name += 1;
+                }
+                if bytes.get(after_name) != Some(&b'=') {
+                    tag.attributes.push(Attribute {
+                        span: name.clone(),
+                        name,
+                        value: None,
+                    });
+                    continue;
+                }
+
+                pos = after_name + 1;
+                while pos < bytes.len() && is_space(bytes[pos]) {
+                    pos += 1;
+                }
+                let value = match bytes.get(pos) {
+                    Some(&quote @ (b'"' | b'\'')) => {
+                        let value_start = pos + 1;
+                        let value_end = html[value_start..]
+                            .find(quote as char)
+                            .map_or(html.len(), |end| value_start + end);
+                        pos = (value_end + 1).min(html.len());
+                        value_start..value_end
+                    }
+                    _ => {
+                        let value_start = pos;
+                        while pos < bytes.len() && !is_space(bytes[pos]) && bytes[pos] != b'>' {
+                            pos += 1;
+                        }
+                        value_start..pos
+                    }
+                };
+                tag.attributes.push(Attribute {
+                    span: name.start..pos,
+                    name,
+                    value: Some(value),
+                });
+            }
+        }
+    }
+
+    (tag, html.len())
+}
+
+/// Finds where the content of a raw text element that starts at `pos` ends: at its end tag, or at
+/// the end of the input.
+fn raw_text_end(html: &str, pos: usize, name: &str) -> usize {
+    let mut search = pos;
+    while let Some(found) = html[search..].find("</") {
+        let start = search + found;
+        let after = &html[start + 2..];
+        let closes = after
+            .get(..name.len())
+            .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+            && after[name.len()..]
+                .chars()
+                .next()
+                .is_none_or(|c| c.is_ascii_whitespace() || c == '/' || c == '>');
+        if closes {
+            return start;
+        }
+        search = start + 2;
+    }
+    html.len()
+}
@@ -0,0 +1,173 @@
+use crate::error::{ConvertError, Position, Span};
+use crate::preprocess::Preprocessed;
+use crate::tokenizer::RAW_TEXT_ELEMENTS;
+use dioxus_rsx_rosetta::{Dom, Node};
+use std::fmt::Display;
+
+/// Maps a span reported against the preprocessed text back onto the original input.
+fn original_span(html: &str, source: &Preprocessed, start: Position, end: Position) -> Span {
+    let start = source.original_offset(start.to_offset(&source.text));
+    let end = source.original_offset(end.to_offset(&source.text));
+    Span::from_offsets(html, start, end)
+}
+
+/// Converts an `html_parser` error into a [`ConvertError::Parse`] pointing at the original input.
+///
+/// Grammar errors carry a `--> line:col` header followed by an `= expected ...` note; everything
+/// else is reported without a location.
+pub(crate) fn parse_error(err: impl Display, html: &str, source: &Preprocessed) -> ConvertError {
+    let text = err.to_string();
+
+    let span = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("--> "))
+        .and_then(|location| location.split_once(':'))
+        .and_then(|(line, column)| {
+            let start = Position {
+                line: line.trim().parse().ok()?,
+                column: column.trim().parse().ok()?,
+            };
+            Some(original_span(html, source, start, start))
+        });
+
+    let message = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("= "))
+        .unwrap_or(&text)
+        .to_string();
+
+    ConvertError::Parse { message, span }
+}
+
+/// Checks a parsed DOM for markup that `html_parser` accepted but that would produce broken RSX.
+///
+/// `html_parser` falls back to plain text when it cannot match a tag, so an unclosed or mismatched
+/// tag shows up as text containing `<`. Element names that are not valid Rust identifiers would make
+/// rosetta panic, so they are reported as unsupported instead.
+pub(crate) fn check_dom(dom: &Dom, html: &str, source: &Preprocessed) -> Result<(), ConvertError> {
+    if let Some(message) = dom.errors.first() {
+        return Err(ConvertError::Parse {
+            message: message.clone(),
+            span: None,
+        });
+    }
+
+    let mut cursor = 0;
+    check_nodes(&dom.children, html, source, &mut cursor)
+}
+
+fn check_nodes(
+    nodes: &[Node],
+    html: &str,
+    source: &Preprocessed,
+    cursor: &mut usize,
+) -> Result<(), ConvertError> {
+    for node in nodes {
+        match node {
+            Node::Element(el) => {
+                let span = &el.source_span;
+                let start = Position {
+                    line: span.start_line,
+                    column: span.start_column,
+                };
+                let end = Position {
+                    line: span.end_line,
+                    column: span.end_column,
+                };
+                *cursor = start.to_offset(&source.text);
+
+                if !is_supported_element_name(&el.name) {
+                    return Err(ConvertError::Unsupported {
+                        construct: format!("element name `{}`", el.name),
+                        span: Some(original_span(html, source, start, end)),
+                    });
+                }
+
+                // Script, style, title and textarea contents are raw text, so `<` is expected there
+                if !RAW_TEXT_ELEMENTS.contains(&el.name.to_lowercase().as_str()) {
+                    check_nodes(&el.children, html, source, cursor)?;
+                }
+            }
+            Node::Text(text) => {
+                let Some(found) = source.text[*cursor..].find(text.as_str()) else {
+                    continue;
+                };
+                let start = *cursor + found;
+                *cursor = start + text.len();
+
+                if let Some(tag) = find_stray_tag(text) {
+                    return Err(ConvertError::Parse {
+                        message: "unclosed or mismatched tag".to_string(),
+                        span: Some(Span::from_offsets(
+                            html,
+                            source.original_offset(start + tag),
+                            source.original_offset(*cursor),
+                        )),
+                    });
+                }
+            }
+            Node::Comment(_) => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the byte offset of the first `<` in text that looks like the start of a tag.
+fn find_stray_tag(text: &str) -> Option<usize> {
+    text.match_indices('<').map(|(i, _)| i).find(|&i| {
+        text[i + 1..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '/')
+    })
+}
+
+/// Element names become Rust identifiers unless they contain a dash (web components).
+fn is_supported_element_name(name: &str) -> bool {
+    name.contains('-') || name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Options, convert};
+
+    /// Converts `html`, which must fail, and returns the input its error span covers.
+    fn spanned(html: &str) -> &str {
+        let error = convert(html, &Options::default()).unwrap_err();
+        let span = error
+            .span()
+            .unwrap_or_else(|| panic!("no span in {error:?}"));
+        &html[span.start.to_offset(html)..span.end.to_offset(html)]
+    }
+
+    #[test]
+    fn spans_skip_preprocessing_shifts() {
+        // `className` becomes `class`, the handler is marked and the whitespace is collapsed
+        let html = "<div className=\"a\"   onClick=\"go()\">\n     <p>   x  <b>y</p>\n</div>";
+        assert_eq!(spanned(html), "<b>y");
+
+        let html = "<div\n  className=\"x\">\n  <<a>\n</div>";
+        assert_eq!(spanned(html), "<a>\n");
+    }
+
+    #[test]
+    fn spans_count_multibyte_text() {
+        let html = "<p>こんにちは 🌍</p>\n<p>naïve <b>café</p>";
+        let error = convert(html, &Options::default()).unwrap_err();
+        let span = error.span().unwrap();
+        assert_eq!((span.start.line, span.start.column), (2, 10));
+        assert_eq!((span.end.line, span.end.column), (2, 17));
+        assert_eq!(spanned(html), "<b>café");
+
+        assert_eq!(spanned("<p class=\"ü\">é   <span>\n</p>"), "<span>\n");
+    }
+
+    #[test]
+    fn unsupported_element_names_are_spanned() {
+        assert_eq!(
+            spanned("<div>\n\n<my$el>ö</my$el></div>"),
+            "<my$el>ö</my$el>"
+        );
+    }
+}
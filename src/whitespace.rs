@@ -0,0 +1,241 @@
+//! Whitespace-sensitive elements.
+//!
+//! Text inside `pre`, `code`, `textarea` and elements styled with `white-space: pre` (or
+//! `pre-wrap`, `pre-line`, `break-spaces`) renders exactly as written, so the preprocessor leaves
+//! it alone and [`restore_whitespace`] puts back the whitespace `html_parser` drops while parsing.
+
+use crate::style::parse_declarations;
+use crate::to_html::VOID_ELEMENTS;
+use dioxus_rsx_rosetta::Node;
+
+/// Elements whose text content is always rendered with its whitespace intact.
+const PREFORMATTED_ELEMENTS: [&str; 3] = ["pre", "code", "textarea"];
+
+/// Returns true if whitespace inside the element is significant.
+pub(crate) fn preserves_whitespace(element: &str, style: Option<&str>) -> bool {
+    if PREFORMATTED_ELEMENTS.contains(&element.to_ascii_lowercase().as_str()) {
+        return true;
+    }
+
+    style.is_some_and(|style| {
+        parse_declarations(style).iter().any(|(property, value)| {
+            property.eq_ignore_ascii_case("white-space")
+                && matches!(
+                    value.to_ascii_lowercase().as_str(),
+                    "pre" | "pre-wrap" | "pre-line" | "break-spaces"
+                )
+        })
+    })
+}
+
+/// Returns true if the element never has children or a closing tag.
+pub(crate) fn is_void_element(element: &str) -> bool {
+    VOID_ELEMENTS.contains(&element.to_ascii_lowercase().as_str())
+}
+
+/// Puts back whitespace that `html_parser` dropped inside whitespace-sensitive elements.
+///
+/// The parser skips whitespace-only text nodes and trims the content of `textarea`, so the
+/// children of every such element are matched against its source text and the gaps between them
+/// are turned back into text.
+pub(crate) fn restore_whitespace(nodes: &mut [Node]) {
+    for node in nodes {
+        let Node::Element(element) = node else {
+            continue;
+        };
+
+        let style = element.attributes.get("style").and_then(|s| s.as_deref());
+        if !preserves_whitespace(&element.name, style) {
+            restore_whitespace(&mut element.children);
+            continue;
+        }
+
+        if let Some(content) = element_content(&element.source_span.text) {
+            restore_children(&mut element.children, content);
+        }
+
+        // Browsers ignore a newline right after the opening tag of `pre` and `textarea`
+        if matches!(
+            element.name.to_ascii_lowercase().as_str(),
+            "pre" | "textarea"
+        ) && let Some(Node::Text(text)) = element.children.first_mut()
+        {
+            let stripped = text
+                .strip_prefix("\r\n")
+                .or_else(|| text.strip_prefix('\n'));
+            if let Some(stripped) = stripped {
+                *text = stripped.to_string();
+                if text.is_empty() {
+                    element.children.remove(0);
+                }
+            }
+        }
+    }
+}
+
+/// Rebuilds `children` so that, in order, they cover `content` exactly.
+fn restore_children(children: &mut Vec<Node>, content: &str) {
+    let mut restored: Vec<Node> = Vec::with_capacity(children.len());
+    let mut pos = 0;
+
+    for mut child in children.drain(..) {
+        let source = match &child {
+            Node::Text(text) => text.clone(),
+            Node::Element(element) => element.source_span.text.clone(),
+            Node::Comment(comment) => format!("<!--{comment}-->"),
+        };
+        let Some(start) = content[pos..].find(&source) else {
+            restored.push(child);
+            continue;
+        };
+
+        push_text(&mut restored, &content[pos..pos + start]);
+        pos += start + source.len();
+
+        match &mut child {
+            Node::Text(text) => push_text(&mut restored, text),
+            Node::Element(element) => {
+                if let Some(content) = element_content(&element.source_span.text) {
+                    restore_children(&mut element.children, content);
+                }
+                restored.push(child);
+            }
+            Node::Comment(_) => restored.push(child),
+        }
+    }
+    push_text(&mut restored, &content[pos..]);
+
+    *children = restored;
+}
+
+/// Appends text, merging it into a preceding text node.
+fn push_text(nodes: &mut Vec<Node>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match nodes.last_mut() {
+        Some(Node::Text(previous)) => previous.push_str(text),
+        _ => nodes.push(Node::Text(text.to_string())),
+    }
+}
+
+/// Returns the markup between an element's opening and closing tags, or `None` for void and
+/// self-closing elements.
+fn element_content(source: &str) -> Option<&str> {
+    let mut quote = None;
+    let mut open_end = None;
+    for (i, c) in source.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => {
+                open_end = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    let open_end = open_end?;
+    if source[..open_end].ends_with('/') {
+        return None;
+    }
+    let content = &source[open_end + 1..];
+    Some(
+        content
+            .rfind("</")
+            .map_or(content, |close| &content[..close]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus_rsx_rosetta::Dom;
+
+    /// Parses `html` and restores whitespace, returning the text of the first element's children,
+    /// with child elements written as `<name>` and their own text in brackets.
+    fn restored(html: &str) -> Vec<String> {
+        let mut dom = Dom::parse(html).unwrap();
+        restore_whitespace(&mut dom.children);
+        let Node::Element(element) = &dom.children[0] else {
+            panic!("expected an element");
+        };
+        describe(&element.children)
+    }
+
+    fn describe(nodes: &[Node]) -> Vec<String> {
+        nodes
+            .iter()
+            .map(|node| match node {
+                Node::Text(text) => text.clone(),
+                Node::Element(element) => {
+                    format!("<{}>{:?}", element.name, describe(&element.children))
+                }
+                Node::Comment(comment) => format!("<!--{comment}-->"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn preformatted_elements_and_styles() {
+        for element in ["pre", "code", "textarea", "PRE"] {
+            assert!(preserves_whitespace(element, None), "{element}");
+        }
+        assert!(!preserves_whitespace("div", None));
+        assert!(!preserves_whitespace("span", Some("color: red")));
+
+        for value in ["pre", "pre-wrap", "Pre-Line", "break-spaces"] {
+            let style = format!("color: red; WHITE-SPACE: {value}");
+            assert!(preserves_whitespace("div", Some(&style)), "{style}");
+        }
+        for value in ["normal", "nowrap"] {
+            let style = format!("white-space: {value}");
+            assert!(!preserves_whitespace("div", Some(&style)), "{style}");
+        }
+    }
+
+    #[test]
+    fn whitespace_between_inline_children_is_restored() {
+        assert_eq!(
+            restored("<pre>  <b>bold</b>   <i> it </i>\n  end\n</pre>"),
+            ["  ", r#"<b>["bold"]"#, "   ", r#"<i>[" it "]"#, "\n  end\n"]
+        );
+        assert_eq!(
+            restored(r#"<div style="white-space: pre-wrap">a  <span>  b</span></div>"#),
+            ["a  ", r#"<span>["  b"]"#]
+        );
+    }
+
+    #[test]
+    fn leading_newline_of_pre_and_textarea_is_dropped() {
+        assert_eq!(restored("<pre>\n  indented\n</pre>"), ["  indented\n"]);
+        assert_eq!(
+            restored("<textarea>\n\n  text  </textarea>"),
+            ["\n  text  "]
+        );
+        assert_eq!(restored("<code>\nkept</code>"), ["\nkept"]);
+        assert_eq!(restored("<pre>\n</pre>"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn other_elements_are_left_alone() {
+        assert_eq!(
+            restored("<div>\n  <b>a</b>  <i>b</i>\n</div>"),
+            [r#"<b>["a"]"#, r#"<i>["b"]"#]
+        );
+        assert_eq!(
+            restored("<div><pre> a </pre> <p> b </p></div>"),
+            [r#"<pre>[" a "]"#, r#"<p>[" b "]"#]
+        );
+    }
+
+    #[test]
+    fn void_elements() {
+        for element in ["br", "IMG", "input", "param", "wbr"] {
+            assert!(is_void_element(element), "{element}");
+        }
+        assert!(!is_void_element("div"));
+    }
+}
@@ -0,0 +1,90 @@
+//! Snapshot tests for the conversion pipeline.
+//!
+//! Every `tests/fixtures/*.html` file is converted with the default options (preprocessing,
+//! rosetta and autofmt) and compared with the `.rsx` file next to it. Run the tests with
+//! `UPDATE_SNAPSHOTS=1` to write the current output instead, then review the diff.
+
+use dioxus_html_rsx::{Options, check_round_trip, convert};
+use std::path::{Path, PathBuf};
+
+fn fixtures() -> Vec<PathBuf> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let mut fixtures: Vec<PathBuf> = std::fs::read_dir(&dir)
+        .expect("tests/fixtures should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "html"))
+        .collect();
+    fixtures.sort();
+    fixtures
+}
+
+#[test]
+fn fixtures_match_snapshots() {
+    let update = std::env::var_os("UPDATE_SNAPSHOTS").is_some();
+    let mut failures = Vec::new();
+
+    let fixtures = fixtures();
+    assert!(!fixtures.is_empty(), "no fixtures found");
+
+    for fixture in fixtures {
+        let name = fixture.file_name().unwrap().to_string_lossy().into_owned();
+        let html = std::fs::read_to_string(&fixture).unwrap();
+        let actual = match convert(&html, &Options::default()) {
+            Ok(rsx) => format!("{}\n", rsx.trim_matches('\n')),
+            Err(error) => {
+                failures.push(format!("{name}: conversion failed: {error}"));
+                continue;
+            }
+        };
+
+        let snapshot = fixture.with_extension("rsx");
+        if update {
+            std::fs::write(&snapshot, &actual).unwrap();
+            continue;
+        }
+
+        match std::fs::read_to_string(&snapshot) {
+            Ok(expected) if expected == actual => {}
+            Ok(expected) => failures.push(format!(
+                "{name}: output differs from snapshot\n--- expected\n{expected}--- actual\n{actual}"
+            )),
+            Err(_) => failures.push(format!("{name}: missing snapshot {}", snapshot.display())),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} fixture(s) failed (run with UPDATE_SNAPSHOTS=1 to accept changes):\n\n{}",
+        failures.len(),
+        failures.join("\n\n")
+    );
+}
+
+#[test]
+fn fixtures_survive_a_round_trip() {
+    let mut failures = Vec::new();
+    for fixture in fixtures() {
+        let name = fixture.file_name().unwrap().to_string_lossy().into_owned();
+        let html = std::fs::read_to_string(&fixture).unwrap();
+        match check_round_trip(&html, &Options::default()) {
+            Ok(report) if report.is_lossless() => {}
+            Ok(report) => failures.push(format!(
+                "{name}:\n{}",
+                report
+                    .differences
+                    .iter()
+                    .map(|difference| format!("  {difference}"))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )),
+            Err(error) => failures.push(format!("{name}: round trip failed: {error}")),
+        }
+    }
+
+    assert!(
+        failures.is_empty(),
+        "{} fixture(s) changed in a round trip:\n\n{}",
+        failures.len(),
+        failures.join("\n\n")
+    );
+}